@@ -0,0 +1,574 @@
+//! Generates static tables from the game data, so the data is validated at compile time and does not
+//! need to be parsed at runtime.
+//!
+//! Only the fields that this crate uses are read, see `src/raw.rs`, but every item and effect is
+//! kept. Any item can be a material or the item to craft, and the tables are what `GameData`
+//! exposes. Data that is loaded from a directory at runtime is used the same way, so dropping rows
+//! here would make the built-in data differ from loaded data.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+#[path = "src/raw.rs"]
+#[allow(dead_code)]
+mod raw;
+
+use raw::*;
+
+const DATA_DIR: &str = "../atelier-data/sophie";
+
+/// Data that is not part of the extracted game data, relative to this crate.
+const CRATE_DATA_DIR: &str = "data";
+
+fn main() {
+    for file in DATA_FILES {
+        println!("cargo:rerun-if-changed={DATA_DIR}/{file}");
+    }
+    println!("cargo:rerun-if-changed={CRATE_DATA_DIR}/{KETTLES_FILE}");
+    println!("cargo:rerun-if-changed={CRATE_DATA_DIR}/{NAMES_DIR}");
+    for optional in [KETTLES_FILE, NAMES_DIR] {
+        if Path::new(DATA_DIR).join(optional).exists() {
+            println!("cargo:rerun-if-changed={DATA_DIR}/{optional}");
+        }
+    }
+
+    let mut data =
+        RawGameData::read_from_dir(Path::new(DATA_DIR)).unwrap_or_else(|e| panic!("{e}"));
+    // kettles and translations that are maintained in this crate take precedence over those in
+    // the game data
+    data.kettles
+        .extend(read_kettles(Path::new(CRATE_DATA_DIR)).unwrap_or_else(|e| panic!("{e}")));
+    data.names
+        .extend(read_names(Path::new(CRATE_DATA_DIR)).unwrap_or_else(|e| panic!("{e}")));
+
+    let errors = data.validate();
+    if !errors.is_empty() {
+        let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+        panic!("invalid game data:\n{}", errors.join("\n"));
+    }
+
+    let tags = Tags::new(
+        &data.items,
+        &data.item_effects,
+        &data.ingredients,
+        &data.item_effect_thresholds,
+    );
+
+    let mut code = String::new();
+    write_items(&mut code, &tags, &data.items);
+    write_item_effects(&mut code, &tags, &data.item_effects);
+    write_ingredients(&mut code, &tags, &data.ingredients);
+    write_item_boards(&mut code, &data.item_boards);
+    write_item_effect_thresholds(&mut code, &tags, &data.item_effect_thresholds);
+    write_shapes(&mut code, &data.shapes);
+    write_kettles(&mut code, &tags, &data.kettles);
+    write_localized_names(&mut code, &tags, &data.names);
+
+    let mut tags_code = String::new();
+    for tag_enum in tags.all() {
+        write_tag_enum(&mut tags_code, tag_enum);
+    }
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("data.rs"), code).expect("write generated data");
+    std::fs::write(out_dir.join("tags.rs"), tags_code).expect("write generated tags");
+}
+
+/// A generated enum with a variant for each tag of a kind.
+struct TagEnum {
+    name: &'static str,
+    kind: &'static str,
+    doc: &'static str,
+    /// The tags and the names of their variants, in the order of declaration.
+    variants: Vec<(String, String)>,
+}
+
+impl TagEnum {
+    fn new(
+        name: &'static str,
+        kind: &'static str,
+        doc: &'static str,
+        prefix: &str,
+        tags: impl IntoIterator<Item = String>,
+    ) -> Self {
+        let mut variants: Vec<(String, String)> = vec![];
+        for tag in tags {
+            if variants.iter().any(|(t, _)| *t == tag) {
+                continue;
+            }
+
+            let variant = variant_name(tag.strip_prefix(prefix).unwrap_or(&tag));
+            if let Some((other, _)) = variants.iter().find(|(_, v)| *v == variant) {
+                panic!("{kind} tags {other} and {tag} both map to variant {name}::{variant}");
+            }
+            variants.push((tag, variant));
+        }
+
+        Self {
+            name,
+            kind,
+            doc,
+            variants,
+        }
+    }
+
+    /// The path to the variant of a tag, for use in generated code.
+    fn path(&self, tag: &str) -> String {
+        let (_, variant) = self
+            .variants
+            .iter()
+            .find(|(t, _)| t == tag)
+            .unwrap_or_else(|| panic!("unknown {} tag {tag}", self.kind));
+        format!("{}::{variant}", self.name)
+    }
+}
+
+/// Turns a tag like `MAT_UNI` into a variant name like `MatUni`.
+fn variant_name(tag: &str) -> String {
+    let mut name = String::new();
+    for part in tag.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars.map(|c| c.to_ascii_lowercase()));
+        }
+    }
+
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "Tag");
+    }
+    name
+}
+
+struct Tags {
+    items: TagEnum,
+    categories: TagEnum,
+    effects: TagEnum,
+    use_types: TagEnum,
+    mat_colors: TagEnum,
+    action_kinds: TagEnum,
+    damage_attributes: TagEnum,
+}
+
+impl Tags {
+    fn new(
+        items: &[RawItem],
+        item_effects: &[RawItemEffect],
+        ingredients: &BTreeMap<String, Vec<RawIngredient>>,
+        item_effect_thresholds: &BTreeMap<String, Vec<Vec<RawItemEffectThreshold>>>,
+    ) -> Self {
+        // items and effects come first in the order of their tables, so a tag can be used as an index
+        // into them. tags that are referenced but have no entry are added after them.
+        let ingredient_tags = ingredients.values().flatten().map(|i| &i.ingredient);
+        let referenced_items = ingredients
+            .keys()
+            .chain(item_effect_thresholds.keys())
+            .chain(
+                ingredient_tags
+                    .clone()
+                    .filter(|t| !t.starts_with(CATEGORY_PREFIX)),
+            )
+            .collect::<BTreeSet<_>>();
+        let referenced_categories = items
+            .iter()
+            .flat_map(|item| &item.categories)
+            .chain(ingredient_tags.filter(|t| t.starts_with(CATEGORY_PREFIX)))
+            .collect::<BTreeSet<_>>();
+        let referenced_effects = item_effect_thresholds
+            .values()
+            .flatten()
+            .flatten()
+            .map(|t| &t.item_effect_tag)
+            .collect::<BTreeSet<_>>();
+        let actions = item_effects.iter().flat_map(|effect| &effect.actions);
+
+        Self {
+            items: TagEnum::new(
+                "ItemTag",
+                "item",
+                "The tag of an item, such as `ITEM_MIX_UNI_BAG`.",
+                "ITEM_",
+                items
+                    .iter()
+                    .map(|item| item.tag.clone())
+                    .chain(referenced_items.into_iter().cloned()),
+            ),
+            categories: TagEnum::new(
+                "CategoryTag",
+                "category",
+                "The tag of an item category, such as `ITEM_CATEGORY_ORE`.",
+                CATEGORY_PREFIX,
+                referenced_categories.into_iter().cloned(),
+            ),
+            effects: TagEnum::new(
+                "EffectTag",
+                "effect",
+                "The tag of an item effect, such as `ITEM_EFF_BOLT_DAMAGE_01`.",
+                "ITEM_EFF_",
+                item_effects
+                    .iter()
+                    .map(|effect| effect.tag.clone())
+                    .chain(referenced_effects.into_iter().cloned()),
+            ),
+            use_types: TagEnum::new(
+                "UseType",
+                "use type",
+                "The way an item is used, such as `ITEM_USE_KETTLE`.",
+                "ITEM_USE_",
+                items
+                    .iter()
+                    .map(|item| item.use_type.clone())
+                    .collect::<BTreeSet<_>>(),
+            ),
+            mat_colors: TagEnum::new(
+                "MatColor",
+                "material color",
+                "The color of an item when used as a material, such as `ITEM_MAT_COLOR_RED`.",
+                "ITEM_MAT_COLOR_",
+                MAT_COLOR_TAGS.map(String::from),
+            ),
+            action_kinds: TagEnum::new(
+                "ActionKind",
+                "action kind",
+                "The kind of action an item effect performs, such as `ACT_ATK_DAMAGE`.",
+                "ACT_",
+                actions
+                    .clone()
+                    .map(|action| action.act_tag.clone())
+                    .filter(|tag| tag != NO_ACTION_TAG)
+                    .collect::<BTreeSet<_>>(),
+            ),
+            damage_attributes: TagEnum::new(
+                "DamageAttribute",
+                "damage attribute",
+                "The attribute of the damage of an item effect, such as `ATT_BOLT`.",
+                "ATT_",
+                actions
+                    .map(|action| action.attribute_tag.clone())
+                    .filter(|tag| tag != NO_ATTRIBUTE_TAG)
+                    .collect::<BTreeSet<_>>(),
+            ),
+        }
+    }
+
+    fn all(&self) -> [&TagEnum; 7] {
+        [
+            &self.items,
+            &self.categories,
+            &self.effects,
+            &self.use_types,
+            &self.mat_colors,
+            &self.action_kinds,
+            &self.damage_attributes,
+        ]
+    }
+
+    /// The path to an optional tag, where `none_tag` is used for `None`.
+    fn optional_path(tag_enum: &TagEnum, tag: &str, none_tag: &str) -> String {
+        if tag == none_tag {
+            "None".to_string()
+        } else {
+            format!("Some({})", tag_enum.path(tag))
+        }
+    }
+
+    fn ingredient_path(&self, tag: &str) -> String {
+        if tag.starts_with(CATEGORY_PREFIX) {
+            format!("IngredientTag::Category({})", self.categories.path(tag))
+        } else {
+            format!("IngredientTag::Item({})", self.items.path(tag))
+        }
+    }
+}
+
+fn write_tag_enum(code: &mut String, tag_enum: &TagEnum) {
+    let TagEnum {
+        name,
+        kind,
+        doc,
+        variants,
+    } = tag_enum;
+
+    writeln!(code, "/// {doc}").unwrap();
+    writeln!(
+        code,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]"
+    )
+    .unwrap();
+    writeln!(code, "pub enum {name} {{").unwrap();
+    for (tag, variant) in variants {
+        writeln!(code, "    /// `{tag}`").unwrap();
+        writeln!(code, "    {variant},").unwrap();
+    }
+    writeln!(code, "}}").unwrap();
+
+    writeln!(code, "impl {name} {{").unwrap();
+    writeln!(
+        code,
+        "    /// All {kind} tags, in the order of declaration."
+    )
+    .unwrap();
+    writeln!(code, "    pub const ALL: &'static [Self] = &[").unwrap();
+    for (_, variant) in variants {
+        writeln!(code, "        Self::{variant},").unwrap();
+    }
+    writeln!(code, "    ];").unwrap();
+    writeln!(code, "    /// The tag as it appears in the game data.").unwrap();
+    writeln!(code, "    pub const fn as_str(self) -> &'static str {{").unwrap();
+    writeln!(code, "        match self {{").unwrap();
+    for (tag, variant) in variants {
+        writeln!(code, "            Self::{variant} => {tag:?},").unwrap();
+    }
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code, "}}").unwrap();
+
+    writeln!(code, "impl std::str::FromStr for {name} {{").unwrap();
+    writeln!(code, "    type Err = ParseTagError;").unwrap();
+    writeln!(
+        code,
+        "    fn from_str(s: &str) -> Result<Self, Self::Err> {{"
+    )
+    .unwrap();
+    writeln!(code, "        match s {{").unwrap();
+    for (tag, variant) in variants {
+        writeln!(code, "            {tag:?} => Ok(Self::{variant}),").unwrap();
+    }
+    writeln!(
+        code,
+        "            _ => Err(ParseTagError {{ kind: {kind:?}, tag: s.to_string() }}),"
+    )
+    .unwrap();
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code, "}}").unwrap();
+
+    writeln!(code, "impl std::fmt::Display for {name} {{").unwrap();
+    writeln!(
+        code,
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{"
+    )
+    .unwrap();
+    writeln!(code, "        f.write_str(self.as_str())").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code, "}}").unwrap();
+}
+
+fn write_items(code: &mut String, tags: &Tags, items: &[RawItem]) {
+    writeln!(code, "pub static ITEMS: &[Item] = &[").unwrap();
+    for item in items {
+        let categories = item
+            .categories
+            .iter()
+            .map(|c| tags.categories.path(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            code,
+            "    Item {{ name: {:?}, tag: {}, shape_type: {:?}, use_type: {}, color: {}, categories: &[{categories}] }},",
+            item.name,
+            tags.items.path(&item.tag),
+            item.shape_type,
+            tags.use_types.path(&item.use_type),
+            tags.mat_colors.path(&item.color),
+        )
+        .unwrap();
+    }
+    writeln!(code, "];").unwrap();
+}
+
+fn write_item_effects(code: &mut String, tags: &Tags, item_effects: &[RawItemEffect]) {
+    writeln!(code, "pub static ITEM_EFFECTS: &[ItemEffect] = &[").unwrap();
+    for effect in item_effects {
+        let actions = effect
+            .actions
+            .iter()
+            .map(|action| {
+                let params = (0..2)
+                    .map(|i| match param_range(&action.min[i], &action.max[i]) {
+                        Ok(Some((min, max))) => {
+                            format!("Some(ParamRange {{ min: {min:?}, max: {max:?} }})")
+                        }
+                        Ok(None) => "None".to_string(),
+                        Err(param) => panic!(
+                            "{ITEM_EFFECTS_FILE}: effect {} has non-numeric parameter {param:?}",
+                            effect.tag
+                        ),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "EffectAction {{ act_tag: {}, attribute_tag: {}, params: [{params}] }}",
+                    Tags::optional_path(&tags.action_kinds, &action.act_tag, NO_ACTION_TAG),
+                    Tags::optional_path(
+                        &tags.damage_attributes,
+                        &action.attribute_tag,
+                        NO_ATTRIBUTE_TAG
+                    ),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            code,
+            "    ItemEffect {{ name: {:?}, tag: {}, group_tag: {:?}, actions: [{actions}] }},",
+            effect.name,
+            tags.effects.path(&effect.tag),
+            effect.group_tag,
+        )
+        .unwrap();
+    }
+    writeln!(code, "];").unwrap();
+}
+
+fn write_ingredients(
+    code: &mut String,
+    tags: &Tags,
+    ingredients: &BTreeMap<String, Vec<RawIngredient>>,
+) {
+    let mut map = phf_codegen::Map::new();
+    for (tag, ingredients) in ingredients {
+        let value = ingredients
+            .iter()
+            .map(|i| {
+                format!(
+                    "Ingredient {{ ingredient: {}, count: {} }}",
+                    tags.ingredient_path(&i.ingredient),
+                    i.count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        map.entry(tag.as_str(), &format!("&[{value}]"));
+    }
+    writeln!(
+        code,
+        "pub static INGREDIENTS: phf::Map<&'static str, &'static [Ingredient]> = {};",
+        map.build()
+    )
+    .unwrap();
+}
+
+fn write_item_boards(code: &mut String, item_boards: &BTreeMap<String, RawItemBoard>) {
+    let mut map = phf_codegen::Map::new();
+    for (tag, board) in item_boards {
+        map.entry(
+            tag.as_str(),
+            &format!(
+                "ItemBoard {{ colors: {:?}, bonus_levels: {:?} }}",
+                board.colors, board.bonus_levels
+            ),
+        );
+    }
+    writeln!(
+        code,
+        "pub static ITEM_BOARDS: phf::Map<&'static str, ItemBoard> = {};",
+        map.build()
+    )
+    .unwrap();
+}
+
+fn write_item_effect_thresholds(
+    code: &mut String,
+    tags: &Tags,
+    item_effect_thresholds: &BTreeMap<String, Vec<Vec<RawItemEffectThreshold>>>,
+) {
+    let mut map = phf_codegen::Map::new();
+    for (tag, groups) in item_effect_thresholds {
+        let value = groups
+            .iter()
+            .map(|thresholds| {
+                let thresholds = thresholds
+                    .iter()
+                    .map(|t| {
+                        format!(
+                            "ItemEffectThreshold {{ item_effect_tag: {}, threshold: {} }}",
+                            tags.effects.path(&t.item_effect_tag),
+                            t.threshold
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("&[{thresholds}]")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        map.entry(tag.as_str(), &format!("&[{value}]"));
+    }
+    writeln!(
+        code,
+        "pub static ITEM_EFFECT_THRESHOLDS: phf::Map<&'static str, &'static [&'static [ItemEffectThreshold]]> = {};",
+        map.build()
+    )
+    .unwrap();
+}
+
+fn write_shapes(code: &mut String, shapes: &BTreeMap<String, Vec<usize>>) {
+    let mut map = phf_codegen::Map::new();
+    for (tag, indices) in shapes {
+        map.entry(tag.as_str(), &format!("&{indices:?}"));
+    }
+    writeln!(
+        code,
+        "pub static SHAPES: phf::Map<&'static str, &'static [usize]> = {};",
+        map.build()
+    )
+    .unwrap();
+}
+
+fn write_localized_names(code: &mut String, tags: &Tags, names: &BTreeMap<String, RawNames>) {
+    writeln!(code, "pub static LOCALIZED_NAMES: &[LocalizedNames] = &[").unwrap();
+    for (language, names) in names {
+        let entries = |tag_enum: &TagEnum, names: &BTreeMap<String, String>| {
+            names
+                .iter()
+                .map(|(tag, name)| format!("({}, {name:?})", tag_enum.path(tag)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(
+            code,
+            "    LocalizedNames {{ language: {language:?}, items: &[{}], item_effects: &[{}] }},",
+            entries(&tags.items, &names.items),
+            entries(&tags.effects, &names.item_effects),
+        )
+        .unwrap();
+    }
+    writeln!(code, "];").unwrap();
+}
+
+fn write_kettles(code: &mut String, tags: &Tags, kettles: &BTreeMap<String, RawKettle>) {
+    let mut map = phf_codegen::Map::new();
+    for (tag, kettle) in kettles {
+        let bonus_scores = match kettle.bonus_scores {
+            RawBonusScores::Flat([a, b, c]) => format!("Flat({a}, {b}, {c})"),
+            RawBonusScores::Percentage([a, b, c]) => format!("Percentage({a}, {b}, {c})"),
+        };
+        let properties = kettle.properties.iter().fold(
+            "synth_solver::CauldronProperties::empty()".to_string(),
+            |acc, p| format!("{acc}.union(synth_solver::CauldronProperties::{p})"),
+        );
+        let (_, transformations) = TRANSFORMATION_TYPES
+            .iter()
+            .find(|(t, _)| *t == kettle.transformations)
+            .unwrap();
+
+        map.entry(
+            tag.as_str(),
+            &format!(
+                "Kettle {{ item_tag: {}, sizes: &{:?}, bonus_scores: synth_solver::BonusScores::{bonus_scores}, properties: {properties}, transformations: synth_solver::TransformationType::{transformations} }}",
+                tags.items.path(tag),
+                kettle.sizes,
+            ),
+        );
+    }
+    writeln!(
+        code,
+        "pub static KETTLES: phf::Map<&'static str, Kettle> = {};",
+        map.build()
+    )
+    .unwrap();
+}
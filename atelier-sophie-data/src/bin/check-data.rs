@@ -0,0 +1,54 @@
+//! Checks a data directory for problems, including references between files that the build script
+//! doesn't check.
+//!
+//! Usage: `check-data [data directory]`. If no directory is given, the directory in the
+//! `ATELIER_SOPHIE_DATA_DIR` environment variable or the `atelier-data` submodule is checked.
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use atelier_sophie_data::{
+    raw::{read_kettles, RawGameData},
+    DATA_DIR_ENV_VAR,
+};
+
+const DEFAULT_DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../atelier-data/sophie");
+/// The data that is maintained in this crate, which is used for kettles the data directory has no
+/// data for.
+const CRATE_DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data");
+
+fn main() -> ExitCode {
+    let data_dir = std::env::args_os()
+        .nth(1)
+        .or_else(|| std::env::var_os(DATA_DIR_ENV_VAR))
+        .map_or_else(|| PathBuf::from(DEFAULT_DATA_DIR), PathBuf::from);
+
+    let (mut data, crate_kettles) = match RawGameData::read_from_dir(&data_dir)
+        .and_then(|data| Ok((data, read_kettles(Path::new(CRATE_DATA_DIR))?)))
+    {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for (tag, kettle) in crate_kettles {
+        data.kettles.entry(tag).or_insert(kettle);
+    }
+
+    let mut errors = data.validate();
+    errors.extend(data.check_references());
+    for error in &errors {
+        println!("{error}");
+    }
+
+    if errors.is_empty() {
+        eprintln!("No problems found in {}", data_dir.display());
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("Found {} problems in {}", errors.len(), data_dir.display());
+        ExitCode::FAILURE
+    }
+}
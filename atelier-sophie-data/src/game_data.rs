@@ -0,0 +1,359 @@
+//! A set of game data that all lookups are made against.
+//!
+//! By default this is the data that was built into the crate, but another set can be loaded at
+//! runtime and made current with [`GameData::set_current`].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{OnceLock, RwLock},
+};
+
+use crate::*;
+
+static BUILTIN: OnceLock<GameData> = OnceLock::new();
+static CURRENT: RwLock<Option<&'static GameData>> = RwLock::new(None);
+
+/// Game data with indices for fast lookups.
+pub struct GameData {
+    items: &'static [Item],
+    item_effects: &'static [ItemEffect],
+    ingredients: HashMap<ItemTag, &'static [Ingredient]>,
+    item_boards: HashMap<ItemTag, &'static ItemBoard>,
+    item_effect_thresholds: HashMap<ItemTag, &'static [&'static [ItemEffectThreshold]]>,
+    shapes: HashMap<&'static str, &'static [usize]>,
+    kettles: HashMap<ItemTag, &'static Kettle>,
+    names: BTreeMap<&'static str, NameTable>,
+
+    item_indices: HashMap<ItemTag, usize>,
+    effect_indices: HashMap<EffectTag, usize>,
+    items_by_category: HashMap<CategoryTag, Vec<usize>>,
+    items_by_use_type: HashMap<UseType, Vec<usize>>,
+    recipes_by_item: HashMap<ItemTag, Vec<ItemTag>>,
+    recipes_by_category: HashMap<CategoryTag, Vec<ItemTag>>,
+    effects_by_group: HashMap<&'static str, Vec<usize>>,
+}
+
+impl GameData {
+    /// Create game data from its tables and build the indices for it.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        items: &'static [Item],
+        item_effects: &'static [ItemEffect],
+        ingredients: HashMap<ItemTag, &'static [Ingredient]>,
+        item_boards: HashMap<ItemTag, &'static ItemBoard>,
+        item_effect_thresholds: HashMap<ItemTag, &'static [&'static [ItemEffectThreshold]]>,
+        shapes: HashMap<&'static str, &'static [usize]>,
+        kettles: HashMap<ItemTag, &'static Kettle>,
+        localized_names: impl IntoIterator<Item = &'static LocalizedNames>,
+    ) -> Self {
+        let mut items_by_category = HashMap::<_, Vec<_>>::new();
+        let mut items_by_use_type = HashMap::<_, Vec<_>>::new();
+        for (i, item) in items.iter().enumerate() {
+            for &category in item.categories {
+                items_by_category.entry(category).or_default().push(i);
+            }
+            items_by_use_type.entry(item.use_type).or_default().push(i);
+        }
+
+        let mut recipes_by_item = HashMap::<_, Vec<_>>::new();
+        let mut recipes_by_category = HashMap::<_, Vec<_>>::new();
+        for (&recipe, recipe_ingredients) in &ingredients {
+            for ingredient in recipe_ingredients.iter() {
+                match ingredient.ingredient {
+                    IngredientTag::Item(tag) => recipes_by_item.entry(tag).or_default(),
+                    IngredientTag::Category(tag) => recipes_by_category.entry(tag).or_default(),
+                }
+                .push(recipe);
+            }
+        }
+        // items can also be used for recipes that take one of their categories
+        for item in items {
+            for category in item.categories {
+                if let Some(recipes) = recipes_by_category.get(category) {
+                    recipes_by_item.entry(item.tag).or_default().extend(recipes);
+                }
+            }
+        }
+        for recipes in recipes_by_item
+            .values_mut()
+            .chain(recipes_by_category.values_mut())
+        {
+            recipes.sort_unstable();
+            recipes.dedup();
+        }
+
+        let mut effects_by_group = HashMap::<_, Vec<_>>::new();
+        for (i, effect) in item_effects.iter().enumerate() {
+            effects_by_group
+                .entry(effect.group_tag)
+                .or_default()
+                .push(i);
+        }
+
+        // later tables of the same language take precedence over earlier ones
+        let mut names = BTreeMap::<_, NameTable>::new();
+        for localized_names in localized_names {
+            let table = names.entry(localized_names.language).or_default();
+            table.items.extend(localized_names.items.iter().copied());
+            (table.item_effects).extend(localized_names.item_effects.iter().copied());
+        }
+
+        Self {
+            items,
+            item_effects,
+            ingredients,
+            item_boards,
+            item_effect_thresholds,
+            shapes,
+            kettles,
+            names,
+
+            item_indices: items.iter().enumerate().map(|(i, x)| (x.tag, i)).collect(),
+            effect_indices: (item_effects.iter().enumerate())
+                .map(|(i, x)| (x.tag, i))
+                .collect(),
+            items_by_category,
+            items_by_use_type,
+            recipes_by_item,
+            recipes_by_category,
+            effects_by_group,
+        }
+    }
+
+    /// The game data that was built into this crate.
+    pub fn builtin() -> &'static Self {
+        BUILTIN.get_or_init(|| {
+            let parse = |tag: &str| tag.parse().expect("generated tag should parse");
+
+            Self::new(
+                ITEMS,
+                ITEM_EFFECTS,
+                (INGREDIENTS.entries())
+                    .map(|(&tag, &ingredients)| (parse(tag), ingredients))
+                    .collect(),
+                (ITEM_BOARDS.entries())
+                    .map(|(&tag, board)| (parse(tag), board))
+                    .collect(),
+                (ITEM_EFFECT_THRESHOLDS.entries())
+                    .map(|(&tag, &thresholds)| (parse(tag), thresholds))
+                    .collect(),
+                SHAPES
+                    .entries()
+                    .map(|(&tag, &indices)| (tag, indices))
+                    .collect(),
+                KETTLES
+                    .values()
+                    .map(|kettle| (kettle.item_tag, kettle))
+                    .collect(),
+                LOCALIZED_NAMES,
+            )
+        })
+    }
+
+    /// The game data that lookups are currently made against.
+    pub fn current() -> &'static Self {
+        CURRENT.read().unwrap().unwrap_or_else(|| Self::builtin())
+    }
+
+    /// Make all lookups use the given game data.
+    ///
+    /// Use [`GameData::leak`] to get a static reference to loaded game data.
+    pub fn set_current(data: &'static Self) {
+        *CURRENT.write().unwrap() = Some(data);
+    }
+
+    /// Leak this game data so it can be used with [`GameData::set_current`].
+    pub fn leak(self) -> &'static Self {
+        Box::leak(Box::new(self))
+    }
+
+    /// All items, in the order of the item data.
+    pub fn items(&self) -> &'static [Item] {
+        self.items
+    }
+
+    /// All item effects, in the order of the effect data.
+    pub fn item_effects(&self) -> &'static [ItemEffect] {
+        self.item_effects
+    }
+
+    pub fn item(&self, tag: ItemTag) -> Option<&'static Item> {
+        self.item_indices.get(&tag).map(|&i| &self.items[i])
+    }
+
+    pub fn effect(&self, tag: EffectTag) -> Option<&'static ItemEffect> {
+        self.effect_indices
+            .get(&tag)
+            .map(|&i| &self.item_effects[i])
+    }
+
+    pub fn ingredients(&self, tag: ItemTag) -> Option<&'static [Ingredient]> {
+        self.ingredients.get(&tag).copied()
+    }
+
+    pub fn board(&self, tag: ItemTag) -> Option<&'static ItemBoard> {
+        self.item_boards.get(&tag).copied()
+    }
+
+    pub fn effect_thresholds(
+        &self,
+        tag: ItemTag,
+    ) -> Option<&'static [&'static [ItemEffectThreshold]]> {
+        self.item_effect_thresholds.get(&tag).copied()
+    }
+
+    /// Get the tile indices of a shape, in the order they are filled as the shape grows.
+    pub fn shape(&self, shape_tag: &str) -> Option<&'static [usize]> {
+        self.shapes.get(shape_tag).copied()
+    }
+
+    /// Get the kettle data of a kettle item.
+    pub fn kettle(&self, tag: ItemTag) -> Option<&'static Kettle> {
+        self.kettles.get(&tag).copied()
+    }
+
+    /// All languages that names are available in, starting with [`DEFAULT_LANGUAGE`].
+    pub fn languages(&self) -> impl Iterator<Item = &'static str> + '_ {
+        std::iter::once(DEFAULT_LANGUAGE)
+            .chain((self.names.keys().copied()).filter(|&language| language != DEFAULT_LANGUAGE))
+    }
+
+    /// Get the name of an item in a language, falling back to the name in the game data.
+    pub fn item_name(&self, item: &Item, language: &str) -> &'static str {
+        (self.names.get(language))
+            .and_then(|table| table.items.get(&item.tag).copied())
+            .unwrap_or(item.name)
+    }
+
+    /// Get the name of an effect in a language, falling back to the name in the game data.
+    pub fn effect_name(&self, effect: &ItemEffect, language: &str) -> &'static str {
+        (self.names.get(language))
+            .and_then(|table| table.item_effects.get(&effect.tag).copied())
+            .unwrap_or(effect.name)
+    }
+
+    /// Find the items that have a name in any language that contains the query, ignoring case.
+    pub fn search_items<'a>(&'a self, query: &str) -> impl Iterator<Item = &'static Item> + 'a {
+        let query = query.to_lowercase();
+        self.items.iter().filter(move |item| {
+            std::iter::once(item.name)
+                .chain(
+                    self.names
+                        .values()
+                        .filter_map(|t| t.items.get(&item.tag).copied()),
+                )
+                .any(|name| name.to_lowercase().contains(&query))
+        })
+    }
+
+    pub fn items_in_category(&self, tag: CategoryTag) -> impl Iterator<Item = &'static Item> + '_ {
+        self.indexed_items(self.items_by_category.get(&tag))
+    }
+
+    pub fn items_with_use_type(&self, tag: UseType) -> impl Iterator<Item = &'static Item> + '_ {
+        self.indexed_items(self.items_by_use_type.get(&tag))
+    }
+
+    /// Get all items that can be used for an ingredient.
+    pub fn candidates(
+        &self,
+        ingredient: IngredientTag,
+    ) -> impl Iterator<Item = &'static Item> + '_ {
+        let (item, category) = match ingredient {
+            IngredientTag::Item(tag) => (self.item(tag), None),
+            IngredientTag::Category(tag) => (None, Some(tag)),
+        };
+
+        item.into_iter()
+            .chain((category.into_iter()).flat_map(|category| self.items_in_category(category)))
+    }
+
+    /// Get the items whose recipes can use an item, either directly or through its categories.
+    pub fn recipes_using_item(&self, tag: ItemTag) -> &[ItemTag] {
+        self.recipes_by_item.get(&tag).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the items whose recipes have a category as an ingredient.
+    pub fn recipes_using_category(&self, tag: CategoryTag) -> &[ItemTag] {
+        self.recipes_by_category
+            .get(&tag)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    pub fn effects_in_group(
+        &self,
+        group_tag: &str,
+    ) -> impl Iterator<Item = &'static ItemEffect> + '_ {
+        let items = self.item_effects;
+        (self.effects_by_group.get(group_tag).into_iter())
+            .flatten()
+            .map(move |&i| &items[i])
+    }
+
+    fn indexed_items<'a>(
+        &self,
+        indices: Option<&'a Vec<usize>>,
+    ) -> impl Iterator<Item = &'static Item> + 'a {
+        let items = self.items;
+        indices.into_iter().flatten().map(move |&i| &items[i])
+    }
+}
+
+/// The names in a language, by tag.
+#[derive(Default)]
+struct NameTable {
+    items: HashMap<ItemTag, &'static str>,
+    item_effects: HashMap<EffectTag, &'static str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_names_take_precedence() {
+        static BUILTIN: LocalizedNames = LocalizedNames {
+            language: "xx",
+            items: &[(ItemTag::ALL[0], "Built-in Item")],
+            item_effects: &[(EffectTag::ALL[0], "Built-in Effect")],
+        };
+        static LOADED: LocalizedNames = LocalizedNames {
+            language: "xx",
+            items: &[(ItemTag::ALL[0], "Loaded Item")],
+            item_effects: &[],
+        };
+
+        let data = GameData::new(
+            ITEMS,
+            ITEM_EFFECTS,
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+            [&BUILTIN, &LOADED],
+        );
+        assert_eq!(data.item_name(&ITEMS[0], "xx"), "Loaded Item");
+        assert_eq!(data.effect_name(&ITEM_EFFECTS[0], "xx"), "Built-in Effect");
+    }
+
+    #[test]
+    fn builtin_japanese_names() {
+        let data = GameData::builtin();
+        assert!(data.languages().any(|language| language == "ja"));
+
+        let uni_bag = ItemTag::MixUniBag.item().unwrap();
+        assert_eq!(data.item_name(uni_bag, "ja"), "うに袋");
+        // a missing translation falls back to the name in the game data
+        let kettle = ItemTag::MixTimewornAlchemyKettle.item().unwrap();
+        assert_eq!(data.item_name(kettle, "ja"), kettle.name);
+
+        let found = data
+            .search_items("うに")
+            .map(|item| item.tag)
+            .collect::<Vec<_>>();
+        assert!(found.contains(&ItemTag::MatUni), "{found:?}");
+        assert!(found.contains(&ItemTag::MixUniBag), "{found:?}");
+        assert!(!found.contains(&ItemTag::MatStone), "{found:?}");
+    }
+}
@@ -1,73 +1,174 @@
-use std::collections::HashMap;
-
-// TODO: move everything to a build script, so we only include the data we actually need
-
-const INGREDIENTS_JSON: &str =
-    include_str!("../../atelier-data/sophie/manually_extracted/ingredients.json");
-const ITEM_BOARDS_JSON: &str =
-    include_str!("../../atelier-data/sophie/manually_extracted/item_boards.json");
-const ITEM_EFFECT_THRESHOLDS_JSON: &str =
-    include_str!("../../atelier-data/sophie/manually_extracted/item_effect_thresholds.json");
-const ITEM_EFFECTS_JSON: &str = include_str!("../../atelier-data/sophie/item_effects.json");
-const ITEMS_JSON: &str = include_str!("../../atelier-data/sophie/items.json");
-const SHAPES_JSON: &str = include_str!("../../atelier-data/sophie/manually_extracted/shapes.json");
-
-lazy_static::lazy_static! {
-    pub static ref INGREDIENTS: HashMap<String, Vec<Ingredient>> = serde_json::from_str(INGREDIENTS_JSON).unwrap();
-    pub static ref ITEM_BOARDS: HashMap<String, ItemBoard> = serde_json::from_str(ITEM_BOARDS_JSON).unwrap();
-    pub static ref ITEM_EFFECT_THRESHOLDS: HashMap<String, Vec<Vec<ItemEffectThreshold>>> = serde_json::from_str(ITEM_EFFECT_THRESHOLDS_JSON).unwrap();
-    pub static ref ITEM_EFFECTS: Vec<ItemEffect> = serde_json::from_str(ITEM_EFFECTS_JSON).unwrap();
-    pub static ref ITEMS: Vec<Item> = serde_json::from_str(ITEMS_JSON).unwrap();
-    pub static ref SHAPES: HashMap<String, Vec<usize>> = serde_json::from_str(SHAPES_JSON).unwrap();
-}
+mod game_data;
+#[cfg(feature = "loader")]
+mod loader;
+mod lookup;
+pub mod problem;
+#[cfg(feature = "loader")]
+pub mod raw;
+pub mod recipes;
+mod tags;
+
+pub use game_data::GameData;
+#[cfg(feature = "loader")]
+pub use loader::{LoadError, DATA_DIR_ENV_VAR};
+pub use lookup::*;
+pub use tags::*;
+
+// The tables below are generated by the build script from the JSON files in `atelier-data`, which
+// are validated at compile time. Lookups should go through `GameData` so that data that is loaded
+// at runtime is used instead when it is set.
+include!(concat!(env!("OUT_DIR"), "/data.rs"));
 
-#[derive(serde::Deserialize)]
 pub struct Ingredient {
-    pub ingredient: String,
+    pub ingredient: IngredientTag,
     pub count: usize,
 }
 
-#[derive(serde::Deserialize)]
 pub struct Item {
-    pub name: String,
-    pub tag: String,
-    pub shape_type: String,
-    pub use_type: String,
-    pub color: String,
-    pub categories: Vec<String>,
+    pub name: &'static str,
+    pub tag: ItemTag,
+    pub shape_type: &'static str,
+    pub use_type: UseType,
+    pub color: MatColor,
+    pub categories: &'static [CategoryTag],
 }
 
-#[derive(serde::Deserialize)]
 pub struct ItemBoard {
-    pub colors: [String; 6],
-    pub bonus_levels: [[String; 6]; 3],
+    pub colors: [&'static str; 6],
+    pub bonus_levels: [[&'static str; 6]; 3],
 }
 
-#[derive(serde::Deserialize)]
 pub struct ItemEffectThreshold {
-    pub item_effect_tag: String,
+    pub item_effect_tag: EffectTag,
     pub threshold: u32,
 }
 
-#[derive(serde::Deserialize)]
 pub struct ItemEffect {
-    pub name: String,
-    pub tag: String,
-    /// Hit sound effect
-    pub hit_se: Option<String>,
-    pub group_tag: String,
+    pub name: &'static str,
+    pub tag: EffectTag,
+    pub group_tag: &'static str,
     pub actions: [EffectAction; 2],
 }
-#[derive(serde::Deserialize)]
+
 pub struct EffectAction {
-    /// The tag, or `ACT_NONE` for none.
-    pub act_tag: String,
-    /// The damage attribute, or `ATT_NONE` for none.
-    pub attribute_tag: String,
-    /// Minimum parameters for this effect.
-    pub min: [Option<String>; 2],
-    /// Maximum parameters for this effect.
-    pub max: [Option<String>; 2],
+    /// The kind of action, or `None` for `ACT_NONE`.
+    pub act_tag: Option<ActionKind>,
+    /// The damage attribute, or `None` for `ATT_NONE`.
+    pub attribute_tag: Option<DamageAttribute>,
+    /// The range of each parameter of this action.
+    pub params: [Option<ParamRange>; 2],
+}
+
+impl EffectAction {
+    /// Get the strength of each parameter, where `ratio` goes from 0 for the minimum to 1 for the
+    /// maximum.
+    pub fn strength(&self, ratio: f32) -> [Option<f32>; 2] {
+        self.params.map(|param| param.map(|param| param.at(ratio)))
+    }
+}
+
+/// The range of a parameter of an effect action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParamRange {
+    /// Interpolate between the minimum and maximum, where `ratio` is clamped between 0 and 1.
+    pub fn at(self, ratio: f32) -> f32 {
+        self.min + (self.max - self.min) * ratio.clamp(0., 1.)
+    }
+}
+
+/// The language of the names in the game data.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// The names of items and effects in a language, for those that have a translation.
+pub struct LocalizedNames {
+    pub language: &'static str,
+    pub items: &'static [(ItemTag, &'static str)],
+    pub item_effects: &'static [(EffectTag, &'static str)],
+}
+
+/// The properties of a kettle, which decide the cauldron that is used for a synthesis.
+///
+/// This data is not part of the extracted game data and is maintained in this crate, in
+/// `data/kettles.json`. Kettles without an entry have no kettle data and can't be used for a
+/// synthesis, `check-data` lists them.
+pub struct Kettle {
+    pub item_tag: ItemTag,
+    /// The cauldron sizes that can be used with this kettle.
+    pub sizes: &'static [usize],
+    pub bonus_scores: synth_solver::BonusScores,
+    /// The innate properties of this kettle.
+    pub properties: synth_solver::CauldronProperties,
+    /// The transformations that materials can have when placed.
+    pub transformations: synth_solver::TransformationType,
+}
+
+/// An item effect that is reached by meeting a threshold of a goal.
+pub struct AchievedEffect {
+    /// The index of the goal, which is the index of the effect group of the item.
+    pub goal_index: usize,
+    /// The amount of thresholds that were met, starting at 1 for the first threshold.
+    pub tier: usize,
+    /// The effect value needed to reach this effect.
+    pub threshold: u32,
+    /// The effect value needed to reach the next tier, or `None` if this is the highest tier.
+    pub next_threshold: Option<u32>,
+    /// The effect that is reached.
+    pub effect: &'static ItemEffect,
+}
+
+impl AchievedEffect {
+    /// Estimate the strength of each action of the effect for the given effect value.
+    ///
+    /// This is a heuristic, the game data only gives a minimum and maximum for each parameter and
+    /// not how the game picks a value between them. The estimate interpolates linearly by how far
+    /// the effect value is between this threshold and the next one, and the highest tier always
+    /// has the maximum strength.
+    pub fn strength(&self, effect_value: u32) -> [[Option<f32>; 2]; 2] {
+        let ratio = match self.next_threshold {
+            Some(next_threshold) if next_threshold > self.threshold => {
+                effect_value.saturating_sub(self.threshold) as f32
+                    / (next_threshold - self.threshold) as f32
+            }
+            _ => 1.,
+        };
+
+        self.effect.actions.each_ref().map(|a| a.strength(ratio))
+    }
+}
+
+/// Resolve the effects that are reached for an item, given the amount of thresholds that are met
+/// for each goal.
+///
+/// This returns an entry for each goal, which is `None` if no threshold was met for that goal or
+/// if the item or effect could not be found.
+pub fn achieved_effects(
+    item_tag: ItemTag,
+    achieved_goals: &[usize],
+) -> Vec<Option<AchievedEffect>> {
+    let thresholds = item_tag.effect_thresholds();
+
+    achieved_goals
+        .iter()
+        .enumerate()
+        .map(|(goal_index, &tier)| {
+            let goal_thresholds = thresholds?.get(goal_index)?;
+            let threshold = goal_thresholds.get(tier.checked_sub(1)?)?;
+            let effect = threshold.item_effect_tag.effect()?;
+
+            Some(AchievedEffect {
+                goal_index,
+                tier,
+                threshold: threshold.threshold,
+                next_threshold: goal_thresholds.get(tier).map(|t| t.threshold),
+                effect,
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -75,12 +176,171 @@ mod tests {
     use super::*;
 
     #[test]
-    fn ensure_json_deserializes_correctly() {
-        let _ = &*INGREDIENTS;
-        let _ = &*ITEM_BOARDS;
-        let _ = &*ITEM_EFFECT_THRESHOLDS;
-        let _ = &*ITEMS;
-        let _ = &*ITEM_BOARDS;
-        let _ = &*SHAPES;
+    fn known_items_are_generated() {
+        let uni_bag = ItemTag::MixUniBag.item().unwrap();
+        assert_eq!(uni_bag.name, "Uni Bag");
+        assert_eq!(uni_bag.tag.as_str(), "ITEM_MIX_UNI_BAG");
+        assert!(ItemTag::MixUniBag.ingredients().is_some());
+        assert!(ItemTag::MixUniBag.board().is_some());
+
+        let kettle = problem::DEFAULT_KETTLE.item().unwrap();
+        assert_eq!(kettle.use_type, UseType::Kettle);
+    }
+
+    #[test]
+    fn every_kettle_has_kettle_data() {
+        let data = GameData::builtin();
+        for item in data.items_with_use_type(UseType::Kettle) {
+            assert!(
+                item.tag.kettle().is_some(),
+                "{} has no kettle data",
+                item.tag
+            );
+        }
+        assert!(ItemTag::MixSilverKettle.kettle().is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "loader")]
+    fn generated_tables_match_game_data() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../atelier-data/sophie");
+        let raw = raw::RawGameData::read_from_dir(std::path::Path::new(dir)).unwrap();
+
+        assert_eq!(ITEMS.len(), raw.items.len());
+        for (item, raw_item) in ITEMS.iter().zip(&raw.items) {
+            assert_eq!(item.tag.as_str(), raw_item.tag);
+            assert_eq!(item.name, raw_item.name);
+            assert_eq!(item.use_type.as_str(), raw_item.use_type);
+            let categories = item.categories.iter().map(|c| c.as_str());
+            assert!(categories.eq(raw_item.categories.iter().map(String::as_str)));
+        }
+        assert_eq!(ITEM_EFFECTS.len(), raw.item_effects.len());
+        for (effect, raw_effect) in ITEM_EFFECTS.iter().zip(&raw.item_effects) {
+            assert_eq!(effect.tag.as_str(), raw_effect.tag);
+            assert_eq!(effect.group_tag, raw_effect.group_tag);
+        }
+
+        assert_eq!(INGREDIENTS.len(), raw.ingredients.len());
+        for (tag, raw_ingredients) in &raw.ingredients {
+            let ingredients = INGREDIENTS[tag.as_str()];
+            assert_eq!(ingredients.len(), raw_ingredients.len());
+            for (ingredient, raw_ingredient) in ingredients.iter().zip(raw_ingredients) {
+                assert_eq!(ingredient.ingredient.to_string(), raw_ingredient.ingredient);
+                assert_eq!(ingredient.count, raw_ingredient.count);
+            }
+        }
+
+        assert_eq!(ITEM_BOARDS.len(), raw.item_boards.len());
+        for (tag, raw_board) in &raw.item_boards {
+            assert_eq!(ITEM_BOARDS[tag.as_str()].colors, raw_board.colors);
+        }
+
+        assert_eq!(
+            ITEM_EFFECT_THRESHOLDS.len(),
+            raw.item_effect_thresholds.len()
+        );
+        for (tag, raw_groups) in &raw.item_effect_thresholds {
+            let groups = ITEM_EFFECT_THRESHOLDS[tag.as_str()];
+            assert_eq!(groups.len(), raw_groups.len());
+            for (thresholds, raw_thresholds) in groups.iter().zip(raw_groups) {
+                let thresholds = thresholds
+                    .iter()
+                    .map(|t| (t.item_effect_tag.as_str(), t.threshold));
+                let raw_thresholds = raw_thresholds
+                    .iter()
+                    .map(|t| (t.item_effect_tag.as_str(), t.threshold));
+                assert!(thresholds.eq(raw_thresholds));
+            }
+        }
+
+        assert_eq!(SHAPES.len(), raw.shapes.len());
+        for (tag, indices) in &raw.shapes {
+            assert_eq!(SHAPES[tag.as_str()], indices.as_slice());
+        }
+    }
+
+    #[test]
+    fn tags_round_trip() {
+        for item in ITEMS {
+            assert_eq!(item.tag.to_string().parse::<ItemTag>(), Ok(item.tag));
+            assert_eq!(item.use_type.as_str().parse::<UseType>(), Ok(item.use_type));
+            assert_eq!(item.color.as_str().parse::<MatColor>(), Ok(item.color));
+        }
+        for effect in ITEM_EFFECTS {
+            assert_eq!(effect.tag.to_string().parse::<EffectTag>(), Ok(effect.tag));
+        }
+        for ingredient in INGREDIENTS.values().flat_map(|i| i.iter()) {
+            let tag = ingredient.ingredient;
+            assert_eq!(tag.to_string().parse::<IngredientTag>(), Ok(tag));
+        }
+
+        assert_eq!(
+            "ITEM_NOT_AN_ITEM".parse::<ItemTag>(),
+            Err(ParseTagError {
+                kind: "item",
+                tag: "ITEM_NOT_AN_ITEM".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn effect_strength_is_estimated() {
+        let effect = (ITEM_EFFECTS.iter())
+            .find(|effect| effect.name == "Fire Damage+")
+            .expect("Fire Damage+ should be in the game data");
+        let [damage, none] = &effect.actions;
+        assert_eq!(damage.act_tag, Some(ActionKind::AtkDamage));
+        assert_eq!(damage.attribute_tag, Some(DamageAttribute::Fire));
+        assert_eq!((none.act_tag, none.attribute_tag), (None, None));
+        let [Some(ParamRange { min, max }), None] = damage.params else {
+            panic!(
+                "expected a single damage parameter, got {:?}",
+                damage.params
+            );
+        };
+        assert!(min < max, "{min} should be less than {max}");
+
+        let achieved = |threshold, next_threshold| AchievedEffect {
+            goal_index: 0,
+            tier: 1,
+            threshold,
+            next_threshold,
+            effect,
+        };
+        let damage_at = |effect: &AchievedEffect, effect_value| effect.strength(effect_value)[0];
+
+        let lower_tier = achieved(50, Some(100));
+        assert_eq!(damage_at(&lower_tier, 50), [Some(min), None]);
+        assert_eq!(damage_at(&lower_tier, 75), [Some((min + max) / 2.), None]);
+        assert_eq!(damage_at(&lower_tier, 150), [Some(max), None]);
+        assert_eq!(lower_tier.strength(75)[1], [None, None]);
+
+        let highest_tier = achieved(100, None);
+        assert_eq!(damage_at(&highest_tier, 100), [Some(max), None]);
+    }
+
+    #[test]
+    fn achieved_effects_resolve_thresholds() {
+        for (item_tag, thresholds) in ITEM_EFFECT_THRESHOLDS.entries() {
+            let item_tag = item_tag.parse().unwrap();
+            let none_achieved = vec![0; thresholds.len()];
+            assert!(achieved_effects(item_tag, &none_achieved)
+                .iter()
+                .all(Option::is_none));
+
+            let all_achieved = thresholds.iter().map(|t| t.len()).collect::<Vec<_>>();
+            let effects = achieved_effects(item_tag, &all_achieved);
+            assert_eq!(effects.len(), thresholds.len());
+            for (goal_index, effect) in effects.iter().enumerate() {
+                let Some(effect) = effect else {
+                    continue;
+                };
+                let last_threshold = thresholds[goal_index].last().unwrap();
+                assert_eq!(effect.goal_index, goal_index);
+                assert_eq!(effect.tier, thresholds[goal_index].len());
+                assert_eq!(effect.threshold, last_threshold.threshold);
+                assert_eq!(effect.effect.tag, last_threshold.item_effect_tag);
+            }
+        }
     }
 }
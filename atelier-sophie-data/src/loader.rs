@@ -0,0 +1,450 @@
+//! Loading game data from a data directory at runtime.
+//!
+//! The data directory has the same layout as `atelier-data/sophie`, so a modified copy of the game
+//! data can be used without rebuilding. Tags in the loaded data must be known at compile time,
+//! since the tag enums are generated from the built-in data. Data with new items, categories or
+//! effects fails to load with an error for every unknown tag, and needs a rebuild of this crate
+//! with the new data instead.
+//!
+//! Kettle data and names that the data directory doesn't have are taken from the built-in data.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    path::Path,
+    str::FromStr,
+};
+
+use synth_solver::{BonusScores, CauldronProperties, TransformationType};
+
+use crate::raw::*;
+use crate::*;
+
+/// The environment variable that [`GameData::load_from_env`] reads the data directory from.
+pub const DATA_DIR_ENV_VAR: &str = "ATELIER_SOPHIE_DATA_DIR";
+
+impl GameData {
+    /// Load game data from a data directory, such as a modified copy of `atelier-data/sophie`.
+    ///
+    /// The loaded data is leaked, so this is meant to be called once or after the user chooses a
+    /// different directory. Fails if the data has tags that are not in the built-in data, see the
+    /// [module documentation](self).
+    pub fn load_from_dir(dir: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let raw = RawGameData::read_from_dir(dir.as_ref()).map_err(|e| LoadError(vec![e]))?;
+
+        let mut errors = raw.validate();
+        let mut converter = Converter {
+            errors: &mut errors,
+        };
+        let data = converter.convert(raw);
+
+        if errors.is_empty() {
+            Ok(data)
+        } else {
+            Err(LoadError(errors))
+        }
+    }
+
+    /// Load game data from the directory in the [`DATA_DIR_ENV_VAR`] environment variable.
+    ///
+    /// Returns `None` if the variable is not set.
+    pub fn load_from_env() -> Option<Result<Self, LoadError>> {
+        let dir = std::env::var_os(DATA_DIR_ENV_VAR)?;
+        Some(Self::load_from_dir(dir))
+    }
+}
+
+/// Converts raw data into game data, collecting all unknown tags as errors.
+struct Converter<'a> {
+    errors: &'a mut Vec<DataError>,
+}
+
+impl Converter<'_> {
+    fn convert(&mut self, raw: RawGameData) -> GameData {
+        let items = raw
+            .items
+            .into_iter()
+            .filter_map(|item| self.item(item))
+            .collect::<Vec<_>>();
+        let item_effects = raw
+            .item_effects
+            .into_iter()
+            .filter_map(|effect| self.item_effect(effect))
+            .collect::<Vec<_>>();
+
+        let ingredients = (raw.ingredients.into_iter())
+            .filter_map(|(tag, ingredients)| {
+                let ingredients = (ingredients.into_iter())
+                    .map(|i| {
+                        Some(Ingredient {
+                            ingredient: self.tag(INGREDIENTS_FILE, &tag, &i.ingredient)?,
+                            count: i.count,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                let tag = self.tag(INGREDIENTS_FILE, &tag, &tag)?;
+                Some((tag, leak_slice(ingredients)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let item_boards = (raw.item_boards.into_iter())
+            .filter_map(|(tag, board)| {
+                let tag = self.tag(ITEM_BOARDS_FILE, &tag, &tag)?;
+                let board = ItemBoard {
+                    colors: board.colors.map(leak_str),
+                    bonus_levels: board.bonus_levels.map(|levels| levels.map(leak_str)),
+                };
+                Some((tag, &*Box::leak(Box::new(board))))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let item_effect_thresholds = (raw.item_effect_thresholds.into_iter())
+            .filter_map(|(tag, groups)| {
+                let groups = (groups.into_iter())
+                    .map(|thresholds| {
+                        let thresholds = (thresholds.into_iter())
+                            .map(|t| {
+                                Some(ItemEffectThreshold {
+                                    item_effect_tag: self.tag(
+                                        ITEM_EFFECT_THRESHOLDS_FILE,
+                                        &tag,
+                                        &t.item_effect_tag,
+                                    )?,
+                                    threshold: t.threshold,
+                                })
+                            })
+                            .collect::<Option<Vec<_>>>()?;
+                        Some(leak_slice(thresholds))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                let tag = self.tag(ITEM_EFFECT_THRESHOLDS_FILE, &tag, &tag)?;
+                Some((tag, leak_slice(groups)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let shapes = (raw.shapes.into_iter())
+            .map(|(tag, indices)| (leak_str(tag), leak_slice(indices)))
+            .collect();
+
+        // kettles without an entry in the data directory keep the data of this crate
+        let mut kettles = (KETTLES.values())
+            .map(|kettle| (kettle.item_tag, kettle))
+            .collect::<HashMap<_, _>>();
+        for (tag, kettle) in raw.kettles {
+            if let Some(kettle) = self.kettle(&tag, kettle) {
+                _ = kettles.insert(kettle.item_tag, Box::leak(Box::new(kettle)));
+            }
+        }
+
+        // unknown tags are already reported by validation and for the items and effects themselves
+        let localized_names = (raw.names.into_iter())
+            .map(|(language, names)| LocalizedNames {
+                language: leak_str(language),
+                items: leak_slice(parse_names(names.items)),
+                item_effects: leak_slice(parse_names(names.item_effects)),
+            })
+            .collect::<Vec<_>>();
+
+        GameData::new(
+            leak_slice(items),
+            leak_slice(item_effects),
+            ingredients,
+            item_boards,
+            item_effect_thresholds,
+            shapes,
+            kettles,
+            // names in the data directory take precedence over the built-in ones
+            LOCALIZED_NAMES.iter().chain(leak_slice(localized_names)),
+        )
+    }
+
+    fn item(&mut self, item: RawItem) -> Option<Item> {
+        let key = &item.tag;
+        let categories = (item.categories.iter())
+            .map(|c| self.tag(ITEMS_FILE, key, c))
+            .collect::<Option<Vec<_>>>();
+
+        Some(Item {
+            tag: self.tag(ITEMS_FILE, key, key)?,
+            use_type: self.tag(ITEMS_FILE, key, &item.use_type)?,
+            color: self.tag(ITEMS_FILE, key, &item.color)?,
+            categories: leak_slice(categories?),
+            shape_type: leak_str(item.shape_type),
+            name: leak_str(item.name),
+        })
+    }
+
+    fn item_effect(&mut self, effect: RawItemEffect) -> Option<ItemEffect> {
+        let key = &effect.tag;
+        let [first, second] = &effect.actions;
+
+        Some(ItemEffect {
+            tag: self.tag(ITEM_EFFECTS_FILE, key, key)?,
+            actions: [self.action(key, first)?, self.action(key, second)?],
+            name: leak_str(effect.name),
+            group_tag: leak_str(effect.group_tag),
+        })
+    }
+
+    fn action(&mut self, key: &str, action: &RawEffectAction) -> Option<EffectAction> {
+        let act_tag = match action.act_tag.as_str() {
+            NO_ACTION_TAG => None,
+            tag => Some(self.tag(ITEM_EFFECTS_FILE, key, tag)?),
+        };
+        let attribute_tag = match action.attribute_tag.as_str() {
+            NO_ATTRIBUTE_TAG => None,
+            tag => Some(self.tag(ITEM_EFFECTS_FILE, key, tag)?),
+        };
+
+        // non-numeric parameters are already reported by validation
+        let params = [0, 1].map(|i| {
+            let range = param_range(&action.min[i], &action.max[i]).ok()??;
+            Some(ParamRange {
+                min: range.0,
+                max: range.1,
+            })
+        });
+
+        Some(EffectAction {
+            act_tag,
+            attribute_tag,
+            params,
+        })
+    }
+
+    fn kettle(&mut self, key: &str, kettle: RawKettle) -> Option<Kettle> {
+        let bonus_scores = match kettle.bonus_scores {
+            RawBonusScores::Flat([a, b, c]) => BonusScores::Flat(a, b, c),
+            RawBonusScores::Percentage([a, b, c]) => BonusScores::Percentage(a, b, c),
+        };
+        // invalid properties and transformations are already reported by validation
+        let properties = (kettle.properties.iter())
+            .filter_map(|property| CauldronProperties::from_name(property))
+            .fold(CauldronProperties::empty(), CauldronProperties::union);
+        let transformations = match kettle.transformations.as_str() {
+            "FLIP_HORIZONTAL" => TransformationType::FlipHorizontal,
+            "FLIP_VERTICAL" => TransformationType::FlipVertical,
+            "ROTATE" => TransformationType::Rotate,
+            _ => TransformationType::None,
+        };
+
+        Some(Kettle {
+            item_tag: self.tag(KETTLES_FILE, key, key)?,
+            sizes: leak_slice(kettle.sizes),
+            bonus_scores,
+            properties,
+            transformations,
+        })
+    }
+
+    /// Parse a tag, recording an error for the entry with the given key if it is unknown.
+    fn tag<T>(&mut self, file: &str, key: &str, tag: &str) -> Option<T>
+    where
+        T: FromStr<Err = ParseTagError>,
+    {
+        tag.parse::<T>()
+            .map_err(|e| {
+                let message = format!("{e}, new tags need a rebuild with the new game data");
+                self.errors.push(DataError::new(file, key, message));
+            })
+            .ok()
+    }
+}
+
+fn parse_names<T: FromStr>(names: BTreeMap<String, String>) -> Vec<(T, &'static str)> {
+    (names.into_iter())
+        .filter_map(|(tag, name)| Some((tag.parse().ok()?, leak_str(name))))
+        .collect()
+}
+
+fn leak_str(s: String) -> &'static str {
+    s.leak()
+}
+
+fn leak_slice<T>(vec: Vec<T>) -> &'static [T] {
+    vec.leak()
+}
+
+/// The problems that were found while loading game data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError(pub Vec<DataError>);
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to load game data:")?;
+        for error in &self.0 {
+            write!(f, "\n{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for LoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../atelier-data/sophie");
+
+    #[test]
+    fn load_matches_builtin() {
+        let loaded = GameData::load_from_dir(DATA_DIR).unwrap();
+        let builtin = GameData::builtin();
+
+        assert_eq!(loaded.items().len(), builtin.items().len());
+        for (loaded, builtin) in loaded.items().iter().zip(builtin.items()) {
+            assert_eq!(loaded.tag, builtin.tag);
+            assert_eq!(loaded.name, builtin.name);
+            assert_eq!(loaded.categories, builtin.categories);
+        }
+        assert_eq!(loaded.item_effects().len(), builtin.item_effects().len());
+
+        for (tag, thresholds) in ITEM_EFFECT_THRESHOLDS.entries() {
+            let tag = tag.parse().unwrap();
+            assert_eq!(
+                loaded.effect_thresholds(tag).unwrap().len(),
+                thresholds.len()
+            );
+            assert_eq!(
+                loaded.board(tag).unwrap().colors,
+                builtin.board(tag).unwrap().colors
+            );
+            assert_eq!(
+                loaded.recipes_using_item(tag),
+                builtin.recipes_using_item(tag)
+            );
+        }
+        for (&tag, &indices) in SHAPES.entries() {
+            assert_eq!(loaded.shape(tag), Some(indices));
+        }
+        for kettle in KETTLES.values() {
+            assert!(std::ptr::eq(
+                loaded.kettle(kettle.item_tag).unwrap(),
+                kettle
+            ));
+        }
+    }
+
+    /// Copy the game data to a temporary directory so it can be modified.
+    fn copy_data_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("atelier-data-{name}-{}", std::process::id()));
+        for file in DATA_FILES {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::copy(Path::new(DATA_DIR).join(file), path).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn load_reports_file_and_key() {
+        let dir = copy_data_dir("invalid");
+
+        let items_path = dir.join(ITEMS_FILE);
+        let items = std::fs::read_to_string(&items_path).unwrap();
+        let tag = ITEMS[0].tag.as_str();
+        let items = items.replacen(&format!("\"{tag}\""), "\"ITEM_NOT_AN_ITEM\"", 1);
+        std::fs::write(&items_path, items).unwrap();
+
+        let result = GameData::load_from_dir(&dir);
+        _ = std::fs::remove_dir_all(&dir);
+
+        // new tags are not supported, since the tag enums are generated at compile time
+        let errors = result.err().unwrap().0;
+        assert!(errors.contains(&DataError::new(
+            ITEMS_FILE,
+            "ITEM_NOT_AN_ITEM",
+            "Unknown item tag ITEM_NOT_AN_ITEM, new tags need a rebuild with the new game data"
+        )));
+    }
+
+    #[test]
+    fn load_kettles() {
+        let dir = copy_data_dir("kettles");
+        let kettle = problem::DEFAULT_KETTLE;
+        std::fs::write(
+            dir.join(KETTLES_FILE),
+            format!(
+                r#"{{"{kettle}": {{
+                    "sizes": [5],
+                    "bonus_scores": {{"percentage": [10, 20, 30]}},
+                    "properties": ["SYNERGY", "TUNING"],
+                    "transformations": "FLIP_VERTICAL"
+                }}}}"#
+            ),
+        )
+        .unwrap();
+
+        let loaded = GameData::load_from_dir(&dir);
+        _ = std::fs::remove_dir_all(&dir);
+
+        let loaded = loaded.unwrap().leak();
+        let kettle = loaded.kettle(kettle).unwrap();
+        assert_eq!(kettle.sizes, [5]);
+        assert_eq!(kettle.bonus_scores, BonusScores::Percentage(10, 20, 30));
+        assert_eq!(
+            kettle.properties,
+            CauldronProperties::SYNERGY | CauldronProperties::TUNING
+        );
+        assert_eq!(kettle.transformations, TransformationType::FlipVertical);
+    }
+
+    #[test]
+    fn load_localized_names() {
+        let dir = copy_data_dir("names");
+        let item = &ITEMS[0];
+        let effect = &ITEM_EFFECTS[0];
+        std::fs::create_dir_all(dir.join(NAMES_DIR)).unwrap();
+        std::fs::write(
+            dir.join(names_file("xx")),
+            format!(
+                r#"{{"items": {{"{}": "Localized Item"}}, "item_effects": {{"{}": "Localized Effect"}}}}"#,
+                item.tag, effect.tag
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join(names_file("yy")),
+            r#"{"items": {"ITEM_NOT_AN_ITEM": "Unknown"}}"#,
+        )
+        .unwrap();
+
+        let result = GameData::load_from_dir(&dir);
+        std::fs::remove_file(dir.join(names_file("yy"))).unwrap();
+        let loaded = GameData::load_from_dir(&dir);
+        _ = std::fs::remove_dir_all(&dir);
+
+        let errors = result.err().unwrap().0;
+        assert_eq!(
+            errors,
+            [DataError::new(
+                names_file("yy"),
+                "ITEM_NOT_AN_ITEM",
+                "unknown item"
+            )]
+        );
+
+        let loaded = loaded.unwrap();
+        assert_eq!(
+            loaded.languages().collect::<Vec<_>>(),
+            // the built-in languages are kept
+            [DEFAULT_LANGUAGE, "ja", "xx"]
+        );
+        assert_eq!(loaded.item_name(item, "xx"), "Localized Item");
+        assert_eq!(loaded.item_name(item, DEFAULT_LANGUAGE), item.name);
+        assert_eq!(loaded.effect_name(effect, "xx"), "Localized Effect");
+        assert_eq!(loaded.effect_name(effect, "zz"), effect.name);
+
+        let found = |query: &str| {
+            loaded
+                .search_items(query)
+                .map(|i| i.tag)
+                .collect::<Vec<_>>()
+        };
+        assert!(found("localized item").contains(&item.tag));
+        assert!(found(&item.name.to_uppercase()).contains(&item.tag));
+        assert!(found("not a name of any item").is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+//! Indexed queries on the current game data, see [`GameData::current`].
+
+use crate::*;
+
+impl ItemTag {
+    /// Get the item with this tag, or `None` if the tag is only referenced by other data.
+    pub fn item(self) -> Option<&'static Item> {
+        GameData::current().item(self)
+    }
+
+    /// Get the ingredients of the recipe for this item, or `None` if it cannot be synthesized.
+    pub fn ingredients(self) -> Option<&'static [Ingredient]> {
+        GameData::current().ingredients(self)
+    }
+
+    /// Get the synthesis board for this item, or `None` if it cannot be synthesized.
+    pub fn board(self) -> Option<&'static ItemBoard> {
+        GameData::current().board(self)
+    }
+
+    /// Get the effect thresholds for each effect group of this item, or `None` if it cannot be
+    /// synthesized.
+    pub fn effect_thresholds(self) -> Option<&'static [&'static [ItemEffectThreshold]]> {
+        GameData::current().effect_thresholds(self)
+    }
+
+    /// Get the kettle data for this item, or `None` if it is not a kettle or has no kettle data.
+    pub fn kettle(self) -> Option<&'static Kettle> {
+        GameData::current().kettle(self)
+    }
+
+    /// Get the items whose recipes can use this item, either directly or through one of its
+    /// categories.
+    pub fn used_in_recipes(self) -> &'static [ItemTag] {
+        GameData::current().recipes_using_item(self)
+    }
+}
+
+impl Item {
+    /// Get the name of this item in a language, falling back to the name in the game data.
+    pub fn localized_name(&self, language: &str) -> &'static str {
+        GameData::current().item_name(self, language)
+    }
+}
+
+impl ItemEffect {
+    /// Get the name of this effect in a language, falling back to the name in the game data.
+    pub fn localized_name(&self, language: &str) -> &'static str {
+        GameData::current().effect_name(self, language)
+    }
+}
+
+impl CategoryTag {
+    /// Get all items in this category.
+    pub fn items(self) -> impl Iterator<Item = &'static Item> {
+        GameData::current().items_in_category(self)
+    }
+
+    /// Get the items whose recipes have this category as an ingredient.
+    pub fn used_in_recipes(self) -> &'static [ItemTag] {
+        GameData::current().recipes_using_category(self)
+    }
+}
+
+impl UseType {
+    /// Get all items with this use type.
+    pub fn items(self) -> impl Iterator<Item = &'static Item> {
+        GameData::current().items_with_use_type(self)
+    }
+}
+
+impl EffectTag {
+    /// Get the effect with this tag, or `None` if the tag is only referenced by other data.
+    pub fn effect(self) -> Option<&'static ItemEffect> {
+        GameData::current().effect(self)
+    }
+}
+
+impl IngredientTag {
+    /// Get all items that can be used for this ingredient.
+    pub fn candidates(self) -> impl Iterator<Item = &'static Item> {
+        GameData::current().candidates(self)
+    }
+}
+
+/// Get all effects with the given group tag, such as `ITEM_EFF_GROUP_BOLT_DAMAGE`.
+pub fn effects_by_group(group_tag: &str) -> impl Iterator<Item = &'static ItemEffect> + '_ {
+    GameData::current().effects_in_group(group_tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookups_match_linear_search() {
+        for item in ITEMS {
+            assert!(std::ptr::eq(item.tag.item().unwrap(), item));
+
+            for &category in item.categories {
+                assert!(category.items().any(|i| i.tag == item.tag));
+            }
+            assert!(item.use_type.items().any(|i| i.tag == item.tag));
+        }
+
+        for effect in ITEM_EFFECTS {
+            assert!(std::ptr::eq(effect.tag.effect().unwrap(), effect));
+            assert!(effects_by_group(effect.group_tag).any(|e| e.tag == effect.tag));
+        }
+    }
+
+    #[test]
+    fn recipes_using_ingredients() {
+        for (recipe, ingredients) in INGREDIENTS.entries() {
+            let recipe = recipe.parse::<ItemTag>().unwrap();
+            assert_eq!(recipe.ingredients().unwrap().len(), ingredients.len());
+
+            for ingredient in ingredients.iter() {
+                if let IngredientTag::Category(category) = ingredient.ingredient {
+                    assert!(category.used_in_recipes().contains(&recipe));
+                }
+                for candidate in ingredient.ingredient.candidates() {
+                    assert!(candidate.tag.used_in_recipes().contains(&recipe));
+                }
+            }
+        }
+    }
+}
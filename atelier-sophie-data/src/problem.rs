@@ -0,0 +1,476 @@
+//! Turns game data into inputs for the solver.
+//!
+//! A [`SynthProblem`] is created through [`SynthProblem::builder`], which looks up the board, goals
+//! and material shapes of the chosen items and validates them against the recipe of the item to
+//! craft.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use synth_solver::{
+    solver::{SolverProblem, SolverSettings},
+    tinyvec::ArrayVec,
+    Cauldron, Color, Goal, Material, Shape, Tile,
+};
+
+use crate::*;
+
+/// The kettle that is available from the start of the game, which is used if no kettle is chosen.
+pub const DEFAULT_KETTLE: ItemTag = ItemTag::MixTimewornAlchemyKettle;
+
+/// The inputs for the solver, created from the game data.
+#[derive(Clone)]
+pub struct SynthProblem {
+    /// The item that is crafted.
+    pub item_tag: ItemTag,
+    /// The kettle that is used.
+    pub kettle_tag: ItemTag,
+    pub cauldron: Cauldron,
+    /// The materials, grouped by the ingredient of the recipe they are used for.
+    pub materials: Vec<Vec<Material>>,
+    pub goals: Vec<Goal>,
+    pub settings: SolverSettings,
+}
+
+impl SynthProblem {
+    /// Start building a problem for crafting the given item.
+    pub fn builder(item_tag: ItemTag) -> SynthProblemBuilder {
+        SynthProblemBuilder {
+            item_tag,
+            kettle_tag: DEFAULT_KETTLE,
+            size: 4,
+            bonus_level: 0,
+            material_groups: vec![],
+            settings: SolverSettings::default(),
+        }
+    }
+
+    /// The parts of this problem that the solver uses, without the item and kettle.
+    pub fn to_solver_problem(&self) -> SolverProblem {
+        SolverProblem {
+            cauldron: self.cauldron.clone(),
+            materials: self.materials.clone(),
+            goals: self.goals.clone(),
+            settings: self.settings.clone(),
+        }
+    }
+}
+
+/// A material chosen for an ingredient of a recipe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaterialChoice {
+    pub item_tag: ItemTag,
+    pub effect_value: u32,
+    /// The amount of tiles of the shape of the item that are used, from 1 to 9.
+    pub shape_size: usize,
+    /// The color to use instead of the color of the item.
+    pub color_override: Option<Color>,
+    pub quality: u32,
+}
+
+impl MaterialChoice {
+    pub fn new(item_tag: ItemTag, effect_value: u32, shape_size: usize) -> Self {
+        Self {
+            item_tag,
+            effect_value,
+            shape_size,
+            color_override: None,
+            quality: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn with_color_override(self, color: Color) -> Self {
+        Self {
+            color_override: Some(color),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_quality(self, quality: u32) -> Self {
+        Self { quality, ..self }
+    }
+}
+
+/// A builder for [`SynthProblem`].
+pub struct SynthProblemBuilder {
+    item_tag: ItemTag,
+    kettle_tag: ItemTag,
+    size: usize,
+    bonus_level: usize,
+    material_groups: Vec<Vec<MaterialChoice>>,
+    settings: SolverSettings,
+}
+
+impl SynthProblemBuilder {
+    /// Set the kettle to use. Defaults to [`DEFAULT_KETTLE`].
+    #[must_use]
+    pub fn kettle(self, kettle_tag: ItemTag) -> Self {
+        Self { kettle_tag, ..self }
+    }
+
+    /// Set the size of the cauldron, which must be one of the sizes of the kettle. Defaults to 4.
+    #[must_use]
+    pub fn size(self, size: usize) -> Self {
+        Self { size, ..self }
+    }
+
+    /// Set the bonus level of the board, from 0 to 2. Defaults to 0.
+    #[must_use]
+    pub fn bonus_level(self, bonus_level: usize) -> Self {
+        Self {
+            bonus_level,
+            ..self
+        }
+    }
+
+    /// Add the materials for the next ingredient of the recipe.
+    #[must_use]
+    pub fn material_group(mut self, materials: Vec<MaterialChoice>) -> Self {
+        self.material_groups.push(materials);
+        self
+    }
+
+    /// Set the solver settings. The transformations are replaced with those of the kettle.
+    #[must_use]
+    pub fn settings(self, settings: SolverSettings) -> Self {
+        Self { settings, ..self }
+    }
+
+    pub fn build(self) -> Result<SynthProblem, ProblemError> {
+        let ingredients = self
+            .item_tag
+            .ingredients()
+            .ok_or(ProblemError::NotSynthesizable(self.item_tag))?;
+
+        if self.material_groups.len() != ingredients.len() {
+            return Err(ProblemError::MaterialGroupCount {
+                expected: ingredients.len(),
+                actual: self.material_groups.len(),
+            });
+        }
+
+        let materials = ingredients
+            .iter()
+            .zip(&self.material_groups)
+            .enumerate()
+            .map(|(group_index, (ingredient, group))| {
+                if group.len() != ingredient.count {
+                    return Err(ProblemError::MaterialCount {
+                        group_index,
+                        expected: ingredient.count,
+                        actual: group.len(),
+                    });
+                }
+
+                group
+                    .iter()
+                    .map(|choice| {
+                        if !ingredient
+                            .ingredient
+                            .candidates()
+                            .any(|item| item.tag == choice.item_tag)
+                        {
+                            return Err(ProblemError::InvalidMaterial {
+                                group_index,
+                                item_tag: choice.item_tag,
+                                ingredient: ingredient.ingredient,
+                            });
+                        }
+
+                        create_material(choice)
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        let kettle = self
+            .kettle_tag
+            .kettle()
+            .ok_or(ProblemError::NotAKettle(self.kettle_tag))?;
+
+        Ok(SynthProblem {
+            item_tag: self.item_tag,
+            kettle_tag: self.kettle_tag,
+            cauldron: create_cauldron(self.item_tag, self.kettle_tag, self.size, self.bonus_level)?,
+            materials,
+            goals: create_goals(self.item_tag)?,
+            settings: SolverSettings {
+                transformations: kettle.transformations,
+                ..self.settings
+            },
+        })
+    }
+}
+
+/// Create an empty cauldron for crafting the given item in the given kettle.
+pub fn create_cauldron(
+    item_tag: ItemTag,
+    kettle_tag: ItemTag,
+    size: usize,
+    bonus_level: usize,
+) -> Result<Cauldron, ProblemError> {
+    let item = item_tag.item().ok_or(ProblemError::UnknownItem(item_tag))?;
+    let kettle = kettle_tag
+        .kettle()
+        .ok_or(ProblemError::NotAKettle(kettle_tag))?;
+
+    if !kettle.sizes.contains(&size) {
+        return Err(ProblemError::InvalidSize(size));
+    }
+
+    Ok(Cauldron {
+        size,
+        tiles: create_tiles(item_tag, size, bonus_level)?,
+        bonus_scores: kettle.bonus_scores,
+        color: item.color.into(),
+        properties: kettle.properties,
+        overlaps: 0,
+    })
+}
+
+/// Create the goals for crafting the given item, one for each effect group.
+pub fn create_goals(item_tag: ItemTag) -> Result<Vec<Goal>, ProblemError> {
+    let thresholds = item_tag
+        .effect_thresholds()
+        .ok_or(ProblemError::NotSynthesizable(item_tag))?;
+
+    Ok(thresholds
+        .iter()
+        .map(|thresholds_group| Goal {
+            effect_value_thresholds: thresholds_group
+                .iter()
+                .map(|threshold| threshold.threshold)
+                .collect(),
+        })
+        .collect())
+}
+
+/// Create a material from the item that was chosen.
+pub fn create_material(choice: &MaterialChoice) -> Result<Material, ProblemError> {
+    let item = choice
+        .item_tag
+        .item()
+        .ok_or(ProblemError::UnknownItem(choice.item_tag))?;
+
+    let mut material = Material::new(
+        choice.color_override.unwrap_or_else(|| item.color.into()),
+        choice.effect_value,
+        create_shape(item.shape_type, choice.shape_size)?,
+    );
+    material.quality = choice.quality;
+    Ok(material)
+}
+
+/// Create a shape from the first `size` tiles of a shape in the game data.
+pub fn create_shape(shape_tag: &'static str, size: usize) -> Result<Shape, ProblemError> {
+    let indices = GameData::current()
+        .shape(shape_tag)
+        .ok_or(ProblemError::UnknownShape(shape_tag))?;
+
+    if !(1..=indices.len()).contains(&size) {
+        return Err(ProblemError::InvalidShapeSize(size));
+    }
+
+    Ok(Shape::from_indices(indices.iter().take(size).copied()))
+}
+
+fn create_tiles(
+    item_tag: ItemTag,
+    size: usize,
+    bonus_level: usize,
+) -> Result<ArrayVec<[Option<Tile>; 6 * 6]>, ProblemError> {
+    let board = item_tag
+        .board()
+        .ok_or(ProblemError::NotSynthesizable(item_tag))?;
+    let bonus_levels = board
+        .bonus_levels
+        .get(bonus_level)
+        .ok_or(ProblemError::InvalidBonusLevel(bonus_level))?;
+
+    // with size 4, take the middle 4x4 slots. with size 5, take the 5x5 slots to the top left.
+    // with size 6, take everything.
+    let offset = match size {
+        4 => 1,
+        5 | 6 => 0,
+        _ => return Err(ProblemError::InvalidSize(size)),
+    };
+
+    // board characters are validated by the build script
+    Ok(board
+        .colors
+        .iter()
+        .zip(bonus_levels)
+        .skip(offset)
+        .take(size)
+        .flat_map(|(row_col, row_bonus)| {
+            Iterator::zip(
+                row_col.chars().skip(offset).take(size),
+                row_bonus.chars().skip(offset).take(size),
+            )
+        })
+        .map(|(color, bonus)| {
+            Some(Tile {
+                color: Color::from_char(color)?,
+                level: bonus.to_digit(10).unwrap_or(0),
+                played_material_index: None,
+            })
+        })
+        .collect())
+}
+
+impl From<MatColor> for Color {
+    fn from(color: MatColor) -> Self {
+        Self::from_mat_color_tag(color.as_str())
+    }
+}
+
+/// An error that occurs when creating a problem from the game data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemError {
+    /// The item is not in the game data
+    UnknownItem(ItemTag),
+    /// The item has no recipe, board or effect thresholds
+    NotSynthesizable(ItemTag),
+    /// The item is not a kettle, or has no kettle data
+    NotAKettle(ItemTag),
+    /// The cauldron size is not supported by the kettle
+    InvalidSize(usize),
+    /// The bonus level is not 0, 1 or 2
+    InvalidBonusLevel(usize),
+    /// The shape of an item is not in the game data
+    UnknownShape(&'static str),
+    /// The shape size is not between 1 and the amount of tiles in the shape
+    InvalidShapeSize(usize),
+    /// The amount of material groups does not match the amount of ingredients in the recipe
+    MaterialGroupCount { expected: usize, actual: usize },
+    /// The amount of materials in a group does not match the count of the ingredient
+    MaterialCount {
+        group_index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// A material cannot be used for the ingredient of its group
+    InvalidMaterial {
+        group_index: usize,
+        item_tag: ItemTag,
+        ingredient: IngredientTag,
+    },
+}
+
+impl Display for ProblemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownItem(tag) => write!(f, "Item {tag} is not in the game data"),
+            Self::NotSynthesizable(tag) => write!(f, "Item {tag} cannot be synthesized"),
+            Self::NotAKettle(tag) => write!(f, "Item {tag} is not a known kettle"),
+            Self::InvalidSize(size) => write!(f, "Invalid cauldron size {size} for this kettle"),
+            Self::InvalidBonusLevel(level) => write!(f, "Invalid bonus level {level}"),
+            Self::UnknownShape(tag) => write!(f, "Shape {tag} is not in the game data"),
+            Self::InvalidShapeSize(size) => write!(f, "Invalid shape size {size}"),
+            Self::MaterialGroupCount { expected, actual } => {
+                write!(f, "Expected {expected} material groups, but got {actual}")
+            }
+            Self::MaterialCount {
+                group_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Expected {expected} materials in group {group_index}, but got {actual}"
+            ),
+            Self::InvalidMaterial {
+                group_index,
+                item_tag,
+                ingredient,
+            } => write!(
+                f,
+                "Item {item_tag} in group {group_index} cannot be used for ingredient {ingredient}"
+            ),
+        }
+    }
+}
+
+impl Error for ProblemError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a problem for the first synthesizable item, using the first candidate for each
+    /// ingredient.
+    fn first_problem() -> SynthProblemBuilder {
+        let item = ITEMS
+            .iter()
+            .find(|item| {
+                item.tag.board().is_some()
+                    && item.tag.effect_thresholds().is_some()
+                    && item.tag.ingredients().is_some_and(|ingredients| {
+                        ingredients
+                            .iter()
+                            .all(|i| i.ingredient.candidates().next().is_some())
+                    })
+            })
+            .expect("find synthesizable item");
+
+        item.tag.ingredients().unwrap().iter().fold(
+            SynthProblem::builder(item.tag),
+            |builder, ingredient| {
+                let candidate = ingredient.ingredient.candidates().next().unwrap();
+                builder.material_group(vec![
+                    MaterialChoice::new(candidate.tag, 10, 3);
+                    ingredient.count
+                ])
+            },
+        )
+    }
+
+    #[test]
+    fn build_problem() {
+        for size in 4..=6 {
+            let problem = first_problem().size(size).build().unwrap();
+            assert_eq!(problem.cauldron.size, size);
+            assert_eq!(
+                problem.cauldron.bonus_scores,
+                DEFAULT_KETTLE.kettle().unwrap().bonus_scores
+            );
+            assert_eq!(problem.cauldron.tiles.len(), size * size);
+            assert_eq!(
+                problem.goals.len(),
+                problem.item_tag.effect_thresholds().unwrap().len()
+            );
+            assert!(problem.materials.iter().flatten().all(|m| m
+                .shape
+                .to_matrix()
+                .iter()
+                .flatten()
+                .filter(|&&b| b)
+                .count()
+                == 3));
+        }
+    }
+
+    #[test]
+    fn build_problem_errors() {
+        assert_eq!(
+            first_problem().size(7).build().err(),
+            Some(ProblemError::InvalidSize(7))
+        );
+        assert_eq!(
+            first_problem().bonus_level(3).build().err(),
+            Some(ProblemError::InvalidBonusLevel(3))
+        );
+        let item_tag = first_problem().item_tag;
+        assert_eq!(
+            first_problem().kettle(item_tag).build().err(),
+            Some(ProblemError::NotAKettle(item_tag))
+        );
+        assert!(matches!(
+            first_problem().material_group(vec![]).build(),
+            Err(ProblemError::MaterialGroupCount { .. })
+        ));
+    }
+}
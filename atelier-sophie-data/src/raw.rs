@@ -0,0 +1,593 @@
+//! The game data as it is stored in the JSON files, and validation of its contents.
+//!
+//! This module is also included by the build script, so it may only depend on `std`, `serde` and
+//! `serde_json`.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    path::Path,
+};
+
+pub const INGREDIENTS_FILE: &str = "manually_extracted/ingredients.json";
+pub const ITEM_BOARDS_FILE: &str = "manually_extracted/item_boards.json";
+pub const ITEM_EFFECT_THRESHOLDS_FILE: &str = "manually_extracted/item_effect_thresholds.json";
+pub const ITEM_EFFECTS_FILE: &str = "item_effects.json";
+pub const ITEMS_FILE: &str = "items.json";
+pub const SHAPES_FILE: &str = "manually_extracted/shapes.json";
+
+/// All files that are read from a data directory.
+pub const DATA_FILES: [&str; 6] = [
+    INGREDIENTS_FILE,
+    ITEM_BOARDS_FILE,
+    ITEM_EFFECT_THRESHOLDS_FILE,
+    ITEM_EFFECTS_FILE,
+    ITEMS_FILE,
+    SHAPES_FILE,
+];
+
+/// The kettle data, which is not part of the extracted game data. It is optional in a data directory,
+/// kettles without an entry use the data that is maintained in this crate.
+pub const KETTLES_FILE: &str = "kettles.json";
+
+/// The directory with a name table for each language, such as `names/ja.json`. It is optional, since
+/// the game data itself already has names.
+pub const NAMES_DIR: &str = "names";
+
+pub const MAT_COLOR_TAGS: [&str; 5] = [
+    "ITEM_MAT_COLOR_RED",
+    "ITEM_MAT_COLOR_BLUE",
+    "ITEM_MAT_COLOR_GREEN",
+    "ITEM_MAT_COLOR_YELLOW",
+    "ITEM_MAT_COLOR_WHITE",
+];
+
+pub const KETTLE_USE_TYPE: &str = "ITEM_USE_KETTLE";
+pub const CAULDRON_PROPERTIES: [&str; 4] =
+    ["SYNERGY", "TUNING", "COLOR_SURROUNDINGS", "COMBINED_EFFECT"];
+/// The transformation types in the kettle data, with the names of their
+/// `synth_solver::TransformationType` variants.
+pub const TRANSFORMATION_TYPES: [(&str, &str); 4] = [
+    ("NONE", "None"),
+    ("FLIP_HORIZONTAL", "FlipHorizontal"),
+    ("FLIP_VERTICAL", "FlipVertical"),
+    ("ROTATE", "Rotate"),
+];
+
+pub const CATEGORY_PREFIX: &str = "ITEM_CATEGORY_";
+pub const NO_ACTION_TAG: &str = "ACT_NONE";
+pub const NO_ATTRIBUTE_TAG: &str = "ATT_NONE";
+
+#[derive(serde::Deserialize)]
+pub struct RawIngredient {
+    pub ingredient: String,
+    pub count: usize,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RawItem {
+    pub name: String,
+    pub tag: String,
+    pub shape_type: String,
+    pub use_type: String,
+    pub color: String,
+    pub categories: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RawItemBoard {
+    pub colors: [String; 6],
+    pub bonus_levels: [[String; 6]; 3],
+}
+
+#[derive(serde::Deserialize)]
+pub struct RawItemEffectThreshold {
+    pub item_effect_tag: String,
+    pub threshold: u32,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RawItemEffect {
+    pub name: String,
+    pub tag: String,
+    pub group_tag: String,
+    pub actions: [RawEffectAction; 2],
+}
+
+#[derive(serde::Deserialize)]
+pub struct RawEffectAction {
+    pub act_tag: String,
+    pub attribute_tag: String,
+    pub min: [Option<String>; 2],
+    pub max: [Option<String>; 2],
+}
+
+#[derive(serde::Deserialize)]
+pub struct RawKettle {
+    pub sizes: Vec<usize>,
+    pub bonus_scores: RawBonusScores,
+    pub properties: Vec<String>,
+    pub transformations: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawBonusScores {
+    Flat([u32; 3]),
+    Percentage([u32; 3]),
+}
+
+/// The names of items and effects in a language, keyed by tag.
+#[derive(serde::Deserialize)]
+pub struct RawNames {
+    #[serde(default)]
+    pub items: BTreeMap<String, String>,
+    #[serde(default)]
+    pub item_effects: BTreeMap<String, String>,
+}
+
+/// The contents of all files in a data directory.
+///
+/// Maps are sorted, so anything generated from them is deterministic.
+pub struct RawGameData {
+    pub ingredients: BTreeMap<String, Vec<RawIngredient>>,
+    pub item_boards: BTreeMap<String, RawItemBoard>,
+    pub item_effect_thresholds: BTreeMap<String, Vec<Vec<RawItemEffectThreshold>>>,
+    pub item_effects: Vec<RawItemEffect>,
+    pub items: Vec<RawItem>,
+    pub shapes: BTreeMap<String, Vec<usize>>,
+    /// The kettle data, keyed by item tag.
+    pub kettles: BTreeMap<String, RawKettle>,
+    /// The name tables, keyed by language.
+    pub names: BTreeMap<String, RawNames>,
+}
+
+impl RawGameData {
+    pub fn read_from_dir(dir: &Path) -> Result<Self, DataError> {
+        Ok(Self {
+            ingredients: read_json(dir, INGREDIENTS_FILE)?,
+            item_boards: read_json(dir, ITEM_BOARDS_FILE)?,
+            item_effect_thresholds: read_json(dir, ITEM_EFFECT_THRESHOLDS_FILE)?,
+            item_effects: read_json(dir, ITEM_EFFECTS_FILE)?,
+            items: read_json(dir, ITEMS_FILE)?,
+            shapes: read_json(dir, SHAPES_FILE)?,
+            kettles: read_kettles(dir)?,
+            names: read_names(dir)?,
+        })
+    }
+
+    /// Check that every entry is well-formed, returning all problems that were found.
+    pub fn validate(&self) -> Vec<DataError> {
+        let mut errors = vec![];
+
+        let mut seen_tags = HashSet::new();
+        for item in &self.items {
+            if !seen_tags.insert(item.tag.as_str()) {
+                errors.push(DataError::new(ITEMS_FILE, &item.tag, "duplicate item tag"));
+            }
+            if !MAT_COLOR_TAGS.contains(&item.color.as_str()) {
+                errors.push(DataError::new(
+                    ITEMS_FILE,
+                    &item.tag,
+                    format!("invalid color {}", item.color),
+                ));
+            }
+        }
+
+        let mut seen_tags = HashSet::new();
+        for effect in &self.item_effects {
+            if !seen_tags.insert(effect.tag.as_str()) {
+                errors.push(DataError::new(
+                    ITEM_EFFECTS_FILE,
+                    &effect.tag,
+                    "duplicate effect tag",
+                ));
+            }
+            for action in &effect.actions {
+                for i in 0..2 {
+                    if let Err(param) = param_range(&action.min[i], &action.max[i]) {
+                        errors.push(DataError::new(
+                            ITEM_EFFECTS_FILE,
+                            &effect.tag,
+                            format!("non-numeric parameter {param:?}"),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (tag, board) in &self.item_boards {
+            for row in &board.colors {
+                if row.chars().count() != 6 || row.chars().any(|c| !"RBGYW ".contains(c)) {
+                    errors.push(DataError::new(
+                        ITEM_BOARDS_FILE,
+                        tag,
+                        format!("invalid color row {row:?}"),
+                    ));
+                }
+            }
+            for row in board.bonus_levels.iter().flatten() {
+                if row.chars().count() != 6 || row.chars().any(|c| !" 123".contains(c)) {
+                    errors.push(DataError::new(
+                        ITEM_BOARDS_FILE,
+                        tag,
+                        format!("invalid bonus level row {row:?}"),
+                    ));
+                }
+            }
+        }
+
+        let items = self
+            .items
+            .iter()
+            .map(|i| i.tag.as_str())
+            .collect::<HashSet<_>>();
+        for (tag, kettle) in &self.kettles {
+            errors.extend(
+                (self.validate_kettle(tag, kettle).into_iter())
+                    .map(|message| DataError::new(KETTLES_FILE, tag, message)),
+            );
+        }
+
+        let effects = (self.item_effects.iter())
+            .map(|e| e.tag.as_str())
+            .collect::<HashSet<_>>();
+        for (language, names) in &self.names {
+            let file = names_file(language);
+            for tag in names.items.keys().filter(|t| !items.contains(t.as_str())) {
+                errors.push(DataError::new(&file, tag, "unknown item"));
+            }
+            for tag in (names.item_effects.keys()).filter(|t| !effects.contains(t.as_str())) {
+                errors.push(DataError::new(&file, tag, "unknown effect"));
+            }
+        }
+
+        for (tag, indices) in &self.shapes {
+            if let Some(index) = indices.iter().find(|&&i| i >= 9) {
+                errors.push(DataError::new(
+                    SHAPES_FILE,
+                    tag,
+                    format!("out of range index {index}"),
+                ));
+            }
+        }
+
+        errors
+    }
+
+    fn validate_kettle(&self, tag: &str, kettle: &RawKettle) -> Vec<String> {
+        let mut errors = vec![];
+        if !(self.items.iter()).any(|item| item.tag == tag && item.use_type == KETTLE_USE_TYPE) {
+            errors.push("not a kettle item".to_string());
+        }
+        if kettle.sizes.is_empty() || kettle.sizes.iter().any(|size| !(4..=6).contains(size)) {
+            errors.push(format!("invalid sizes {:?}", kettle.sizes));
+        }
+        for property in
+            (kettle.properties.iter()).filter(|p| !CAULDRON_PROPERTIES.contains(&p.as_str()))
+        {
+            errors.push(format!("invalid property {property}"));
+        }
+        if !(TRANSFORMATION_TYPES.iter()).any(|(t, _)| *t == kettle.transformations) {
+            errors.push(format!(
+                "invalid transformations {}",
+                kettle.transformations
+            ));
+        }
+        errors
+    }
+
+    /// Check that every tag that is referenced by other data exists, returning all problems that
+    /// were found.
+    ///
+    /// Unlike [`RawGameData::validate`], these problems don't stop the data from being used, but
+    /// they mean that some items can't be crafted, that some effects are missing or that some
+    /// kettles can't be used for a synthesis.
+    pub fn check_references(&self) -> Vec<DataError> {
+        let mut errors = vec![];
+
+        let items = (self.items.iter())
+            .map(|item| (item.tag.as_str(), item))
+            .collect::<HashMap<_, _>>();
+        let categories = (self.items.iter())
+            .flat_map(|item| &item.categories)
+            .map(String::as_str)
+            .collect::<HashSet<_>>();
+        let effects = (self.item_effects.iter())
+            .map(|effect| effect.tag.as_str())
+            .collect::<HashSet<_>>();
+
+        for item in &self.items {
+            if !self.shapes.contains_key(&item.shape_type) {
+                errors.push(DataError::new(
+                    ITEMS_FILE,
+                    &item.tag,
+                    format!("unknown shape {}", item.shape_type),
+                ));
+            }
+            if item.use_type == KETTLE_USE_TYPE && !self.kettles.contains_key(&item.tag) {
+                errors.push(DataError::new(
+                    KETTLES_FILE,
+                    &item.tag,
+                    "kettle has no kettle data",
+                ));
+            }
+        }
+
+        for (tag, ingredients) in &self.ingredients {
+            if !items.contains_key(tag.as_str()) {
+                errors.push(DataError::new(INGREDIENTS_FILE, tag, "unknown item"));
+            }
+            if !self.item_boards.contains_key(tag) {
+                errors.push(DataError::new(INGREDIENTS_FILE, tag, "item has no board"));
+            }
+            if !self.item_effect_thresholds.contains_key(tag) {
+                errors.push(DataError::new(
+                    INGREDIENTS_FILE,
+                    tag,
+                    "item has no effect thresholds",
+                ));
+            }
+
+            for ingredient in ingredients {
+                let ingredient = &ingredient.ingredient;
+                if ingredient.starts_with(CATEGORY_PREFIX) {
+                    if !categories.contains(ingredient.as_str()) {
+                        errors.push(DataError::new(
+                            INGREDIENTS_FILE,
+                            tag,
+                            format!("no items in ingredient category {ingredient}"),
+                        ));
+                    }
+                } else if !items.contains_key(ingredient.as_str()) {
+                    errors.push(DataError::new(
+                        INGREDIENTS_FILE,
+                        tag,
+                        format!("unknown ingredient item {ingredient}"),
+                    ));
+                }
+            }
+        }
+
+        for tag in self.item_boards.keys() {
+            if !items.contains_key(tag.as_str()) {
+                errors.push(DataError::new(ITEM_BOARDS_FILE, tag, "unknown item"));
+            }
+        }
+
+        for (tag, groups) in &self.item_effect_thresholds {
+            if !items.contains_key(tag.as_str()) {
+                errors.push(DataError::new(
+                    ITEM_EFFECT_THRESHOLDS_FILE,
+                    tag,
+                    "unknown item",
+                ));
+            }
+            for threshold in groups.iter().flatten() {
+                if !effects.contains(threshold.item_effect_tag.as_str()) {
+                    errors.push(DataError::new(
+                        ITEM_EFFECT_THRESHOLDS_FILE,
+                        tag,
+                        format!("unknown effect {}", threshold.item_effect_tag),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+pub fn read_json<T: serde::de::DeserializeOwned>(dir: &Path, file: &str) -> Result<T, DataError> {
+    let json = std::fs::read_to_string(dir.join(file))
+        .map_err(|e| DataError::new(file, "", format!("failed to read file: {e}")))?;
+    serde_json::from_str(&json)
+        .map_err(|e| DataError::new(file, "", format!("failed to parse file: {e}")))
+}
+
+/// Read the kettle data in a data directory, if it exists.
+pub fn read_kettles(dir: &Path) -> Result<BTreeMap<String, RawKettle>, DataError> {
+    if dir.join(KETTLES_FILE).exists() {
+        read_json(dir, KETTLES_FILE)
+    } else {
+        Ok(BTreeMap::new())
+    }
+}
+
+/// Read the name tables from the names directory in a data directory, if it exists.
+pub fn read_names(dir: &Path) -> Result<BTreeMap<String, RawNames>, DataError> {
+    let mut names = BTreeMap::new();
+    let entries = match std::fs::read_dir(dir.join(NAMES_DIR)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(names),
+        Err(e) => {
+            return Err(DataError::new(
+                NAMES_DIR,
+                "",
+                format!("failed to read directory: {e}"),
+            ))
+        }
+    };
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| DataError::new(NAMES_DIR, "", format!("failed to read directory: {e}")))?
+            .path();
+        if path.extension().is_some_and(|e| e == "json") {
+            if let Some(language) = path.file_stem().and_then(|s| s.to_str()) {
+                names.insert(language.to_string(), read_json(dir, &names_file(language))?);
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+/// The path of the name table of a language, relative to the data directory.
+pub fn names_file(language: &str) -> String {
+    format!("{NAMES_DIR}/{language}.json")
+}
+
+/// Parses the minimum and maximum of a parameter. If only one of them is set, it is used for both.
+///
+/// Returns the parameter that is not a number as the error.
+pub fn param_range(
+    min: &Option<String>,
+    max: &Option<String>,
+) -> Result<Option<(f32, f32)>, String> {
+    let parse = |param: &String| param.trim().parse::<f32>().map_err(|_| param.clone());
+
+    Ok(match (min, max) {
+        (None, None) => None,
+        (Some(value), None) | (None, Some(value)) => {
+            let value = parse(value)?;
+            Some((value, value))
+        }
+        (Some(min), Some(max)) => Some((parse(min)?, parse(max)?)),
+    })
+}
+
+/// A problem in the game data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataError {
+    /// The file the problem was found in, relative to the data directory.
+    pub file: String,
+    /// The key of the entry with the problem, such as an item tag. Empty if the problem is with the
+    /// whole file.
+    pub key: String,
+    pub message: String,
+}
+
+impl DataError {
+    pub fn new(
+        file: impl Into<String>,
+        key: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            file: file.into(),
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for DataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "{}: {}", self.file, self.message)
+        } else {
+            write!(f, "{}: {}: {}", self.file, self.key, self.message)
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_references_reports_missing_entries() {
+        let data = RawGameData {
+            ingredients: serde_json::from_str(
+                r#"{"ITEM_A": [
+                    {"ingredient": "ITEM_B", "count": 1},
+                    {"ingredient": "ITEM_CATEGORY_X", "count": 1}
+                ]}"#,
+            )
+            .unwrap(),
+            item_boards: BTreeMap::new(),
+            item_effect_thresholds: serde_json::from_str(
+                r#"{"ITEM_A": [[{"item_effect_tag": "ITEM_EFF_A", "threshold": 10}]]}"#,
+            )
+            .unwrap(),
+            item_effects: vec![],
+            items: serde_json::from_str(
+                r#"[{
+                    "name": "A",
+                    "tag": "ITEM_A",
+                    "shape_type": "SHAPE_A",
+                    "use_type": "ITEM_USE_MATERIAL",
+                    "color": "ITEM_MAT_COLOR_RED",
+                    "categories": []
+                }]"#,
+            )
+            .unwrap(),
+            shapes: BTreeMap::new(),
+            kettles: BTreeMap::new(),
+            names: BTreeMap::new(),
+        };
+
+        let errors = data
+            .check_references()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "items.json: ITEM_A: unknown shape SHAPE_A",
+                "manually_extracted/ingredients.json: ITEM_A: item has no board",
+                "manually_extracted/ingredients.json: ITEM_A: unknown ingredient item ITEM_B",
+                "manually_extracted/ingredients.json: ITEM_A: no items in ingredient category ITEM_CATEGORY_X",
+                "manually_extracted/item_effect_thresholds.json: ITEM_A: unknown effect ITEM_EFF_A",
+            ]
+        );
+    }
+
+    #[test]
+    fn kettles_are_checked() {
+        let item = |tag: &str, use_type: &str| {
+            serde_json::from_str::<RawItem>(&format!(
+                r#"{{
+                    "name": "",
+                    "tag": "{tag}",
+                    "shape_type": "SHAPE_A",
+                    "use_type": "{use_type}",
+                    "color": "ITEM_MAT_COLOR_RED",
+                    "categories": []
+                }}"#
+            ))
+            .unwrap()
+        };
+        let data = RawGameData {
+            ingredients: BTreeMap::new(),
+            item_boards: BTreeMap::new(),
+            item_effect_thresholds: BTreeMap::new(),
+            item_effects: vec![],
+            items: vec![
+                item("ITEM_A", "ITEM_USE_MATERIAL"),
+                item("ITEM_K", "ITEM_USE_KETTLE"),
+            ],
+            shapes: serde_json::from_str(r#"{"SHAPE_A": [0]}"#).unwrap(),
+            kettles: serde_json::from_str(
+                r#"{"ITEM_A": {
+                    "sizes": [3, 4],
+                    "bonus_scores": {"flat": [3, 5, 7]},
+                    "properties": ["SYNERGY", "TIME_LIMIT"],
+                    "transformations": "ROTATE"
+                }}"#,
+            )
+            .unwrap(),
+            names: BTreeMap::new(),
+        };
+
+        let errors =
+            |errors: Vec<DataError>| errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            errors(data.validate()),
+            [
+                "kettles.json: ITEM_A: not a kettle item",
+                "kettles.json: ITEM_A: invalid sizes [3, 4]",
+                "kettles.json: ITEM_A: invalid property TIME_LIMIT",
+            ]
+        );
+        // a kettle without data is not an error, but it can't be used for a synthesis
+        assert_eq!(
+            errors(data.check_references()),
+            ["kettles.json: ITEM_K: kettle has no kettle data"]
+        );
+    }
+}
@@ -0,0 +1,664 @@
+//! The recipes as a graph from items to the items that can be used for their ingredients, and
+//! planning of the syntheses that are needed to craft an item.
+//!
+//! An item depends on another item if that item is a candidate for one of its ingredients and can
+//! be synthesized itself. Items that can't be synthesized are gathered, so they are the leaves of
+//! the graph.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{problem::MaterialChoice, *};
+
+/// The dependencies between all synthesizable items.
+pub struct RecipeGraph {
+    dependencies: HashMap<ItemTag, Vec<ItemTag>>,
+}
+
+impl RecipeGraph {
+    /// Build the graph of all recipes in the game data.
+    pub fn new(data: &GameData) -> Self {
+        let dependencies = (data.items().iter())
+            .filter_map(|item| {
+                let ingredients = data.ingredients(item.tag)?;
+                let mut dependencies = vec![];
+                for ingredient in ingredients {
+                    for candidate in data.candidates(ingredient.ingredient) {
+                        if data.ingredients(candidate.tag).is_some()
+                            && !dependencies.contains(&candidate.tag)
+                        {
+                            dependencies.push(candidate.tag);
+                        }
+                    }
+                }
+                Some((item.tag, dependencies))
+            })
+            .collect();
+
+        Self { dependencies }
+    }
+
+    /// Get the synthesizable items that can be used in the recipe of an item. This is empty if
+    /// the item can't be synthesized.
+    pub fn dependencies(&self, item_tag: ItemTag) -> &[ItemTag] {
+        self.dependencies.get(&item_tag).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The most syntheses on a chain from the crafted item down to a gathered item. Candidates that
+/// need a longer chain are skipped.
+pub const MAX_CHAIN_LENGTH: usize = 16;
+
+/// Plans the syntheses that are needed to craft an item, choosing an item for every ingredient.
+///
+/// By default, an ingredient uses its item, or for a category the first candidate that can't be
+/// synthesized. If every candidate is synthesized, the first one that can be planned is used, which
+/// skips the candidates that need the item they are used for. Other candidates can be chosen with
+/// [`CraftingPlanner::choose`].
+pub struct CraftingPlanner<'a> {
+    data: &'a GameData,
+    choices: HashMap<(ItemTag, usize), ItemTag>,
+    gathered: HashSet<ItemTag>,
+}
+
+impl<'a> CraftingPlanner<'a> {
+    pub fn new(data: &'a GameData) -> Self {
+        Self {
+            data,
+            choices: HashMap::new(),
+            gathered: HashSet::new(),
+        }
+    }
+
+    /// Use the given item for an ingredient of a recipe.
+    #[must_use]
+    pub fn choose(mut self, recipe: ItemTag, ingredient_index: usize, item_tag: ItemTag) -> Self {
+        _ = self.choices.insert((recipe, ingredient_index), item_tag);
+        self
+    }
+
+    /// Use an item that is already available instead of synthesizing it.
+    #[must_use]
+    pub fn gathered(mut self, item_tag: ItemTag) -> Self {
+        _ = self.gathered.insert(item_tag);
+        self
+    }
+
+    /// Plan the syntheses for crafting an item.
+    pub fn plan(&self, target: ItemTag) -> Result<CraftingPlan, PlanError> {
+        let mut chosen = HashMap::new();
+        self.plan_item(target, &mut vec![], &mut chosen)?;
+
+        // candidates that could not be planned may have left plans for items that aren't used
+        let order = topological_order([target], |item_tag| {
+            Ok((chosen[&item_tag].iter())
+                .map(|&(_, item_tag)| item_tag)
+                .filter(|&item_tag| self.is_synthesized(item_tag))
+                .collect())
+        })?;
+
+        let step_indices = (order.iter().enumerate())
+            .map(|(i, &item_tag)| (item_tag, i))
+            .collect::<HashMap<_, _>>();
+        let steps = order
+            .iter()
+            .map(|item_tag| PlanStep {
+                item_tag: *item_tag,
+                ingredients: chosen[item_tag]
+                    .iter()
+                    .map(|&(ingredient, item_tag)| PlannedIngredient {
+                        ingredient: ingredient.ingredient,
+                        count: ingredient.count,
+                        item_tag,
+                        source_step: step_indices.get(&item_tag).copied(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(CraftingPlan { steps })
+    }
+
+    fn is_synthesized(&self, item_tag: ItemTag) -> bool {
+        !self.gathered.contains(&item_tag) && self.data.ingredients(item_tag).is_some()
+    }
+
+    /// Choose the ingredients of an item and plan the syntheses they need, depth-first. `chain`
+    /// holds the items that are being planned, which the ingredients can't use.
+    fn plan_item(
+        &self,
+        item_tag: ItemTag,
+        chain: &mut Vec<ItemTag>,
+        chosen: &mut HashMap<ItemTag, Vec<(&'static Ingredient, ItemTag)>>,
+    ) -> Result<(), PlanError> {
+        if chosen.contains_key(&item_tag) {
+            return Ok(());
+        }
+        let ingredients =
+            (self.data.ingredients(item_tag)).ok_or(PlanError::NotSynthesizable(item_tag))?;
+        if chain.len() >= MAX_CHAIN_LENGTH {
+            let mut items = chain.clone();
+            items.push(item_tag);
+            return Err(PlanError::ChainTooLong(items));
+        }
+
+        chain.push(item_tag);
+        let ingredients = (ingredients.iter().enumerate())
+            .map(|(ingredient_index, ingredient)| {
+                let choice =
+                    self.plan_ingredient(item_tag, ingredient_index, ingredient, chain, chosen)?;
+                Ok((ingredient, choice))
+            })
+            .collect::<Result<Vec<_>, _>>();
+        _ = chain.pop();
+
+        _ = chosen.insert(item_tag, ingredients?);
+        Ok(())
+    }
+
+    fn plan_ingredient(
+        &self,
+        recipe: ItemTag,
+        ingredient_index: usize,
+        ingredient: &Ingredient,
+        chain: &mut Vec<ItemTag>,
+        chosen: &mut HashMap<ItemTag, Vec<(&'static Ingredient, ItemTag)>>,
+    ) -> Result<ItemTag, PlanError> {
+        let candidates = (self.data.candidates(ingredient.ingredient))
+            .map(|item| item.tag)
+            .collect::<Vec<_>>();
+
+        if let Some(&choice) = self.choices.get(&(recipe, ingredient_index)) {
+            if !candidates.contains(&choice) {
+                return Err(PlanError::InvalidChoice {
+                    recipe,
+                    ingredient_index,
+                    item_tag: choice,
+                });
+            }
+            self.plan_candidate(choice, chain, chosen)?;
+            return Ok(choice);
+        }
+
+        if let Some(&item_tag) =
+            (candidates.iter()).find(|&&item_tag| !self.is_synthesized(item_tag))
+        {
+            return Ok(item_tag);
+        }
+        let mut error = PlanError::NoCandidates {
+            recipe,
+            ingredient: ingredient.ingredient,
+        };
+        for item_tag in candidates {
+            match self.plan_candidate(item_tag, chain, chosen) {
+                Ok(()) => return Ok(item_tag),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+
+    /// Plan the syntheses for an item that is used for an ingredient, failing if the item is
+    /// already being planned.
+    fn plan_candidate(
+        &self,
+        item_tag: ItemTag,
+        chain: &mut Vec<ItemTag>,
+        chosen: &mut HashMap<ItemTag, Vec<(&'static Ingredient, ItemTag)>>,
+    ) -> Result<(), PlanError> {
+        if !self.is_synthesized(item_tag) {
+            return Ok(());
+        }
+        if let Some(start) = chain.iter().position(|&tag| tag == item_tag) {
+            let mut cycle = chain[start..].to_vec();
+            cycle.push(item_tag);
+            return Err(PlanError::Cycle(cycle));
+        }
+        // a plan that was made for another chain can't be reused if it needs an item on this one
+        if let Some(cycle) =
+            self.cached_cycle(&mut vec![item_tag], &mut HashSet::new(), chain, chosen)
+        {
+            return Err(PlanError::Cycle(cycle));
+        }
+        self.plan_item(item_tag, chain, chosen)
+    }
+
+    /// Find an item on the chain that the cached plan for the last item of `path` needs, directly
+    /// or through other synthesized items, and return the cycle it makes.
+    fn cached_cycle(
+        &self,
+        path: &mut Vec<ItemTag>,
+        visited: &mut HashSet<ItemTag>,
+        chain: &[ItemTag],
+        chosen: &HashMap<ItemTag, Vec<(&'static Ingredient, ItemTag)>>,
+    ) -> Option<Vec<ItemTag>> {
+        let ingredients = chosen.get(path.last()?)?;
+        for &(_, dependency) in ingredients {
+            if !self.is_synthesized(dependency) || !visited.insert(dependency) {
+                continue;
+            }
+            path.push(dependency);
+            if let Some(start) = chain.iter().position(|&tag| tag == dependency) {
+                let mut cycle = chain[start..].to_vec();
+                cycle.extend_from_slice(path);
+                return Some(cycle);
+            }
+            if let Some(cycle) = self.cached_cycle(path, visited, chain, chosen) {
+                return Some(cycle);
+            }
+            _ = path.pop();
+        }
+        None
+    }
+}
+
+/// The syntheses that are needed to craft an item, in the order they should be done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CraftingPlan {
+    /// Each synthesized item comes before the steps that use it. The last step crafts the target.
+    pub steps: Vec<PlanStep>,
+}
+
+impl CraftingPlan {
+    /// The items that are synthesized to be used in other syntheses.
+    pub fn intermediates(&self) -> impl Iterator<Item = ItemTag> + '_ {
+        let count = self.steps.len().saturating_sub(1);
+        self.steps.iter().take(count).map(|step| step.item_tag)
+    }
+
+    /// Create the material groups for a step, to be passed to
+    /// [`SynthProblemBuilder::material_group`](crate::problem::SynthProblemBuilder::material_group).
+    ///
+    /// `material` creates the material for an item. For items that are synthesized in an earlier
+    /// step, its effect value is replaced by the effect value that step produced, taken from
+    /// `produced_effect_values` by step index.
+    pub fn material_groups(
+        &self,
+        step_index: usize,
+        produced_effect_values: &[u32],
+        mut material: impl FnMut(ItemTag) -> MaterialChoice,
+    ) -> Vec<Vec<MaterialChoice>> {
+        self.steps[step_index]
+            .ingredients
+            .iter()
+            .map(|ingredient| {
+                let mut choice = material(ingredient.item_tag);
+                if let Some(&effect_value) =
+                    (ingredient.source_step).and_then(|step| produced_effect_values.get(step))
+                {
+                    choice.effect_value = effect_value;
+                }
+                vec![choice; ingredient.count]
+            })
+            .collect()
+    }
+}
+
+/// A synthesis in a [`CraftingPlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStep {
+    pub item_tag: ItemTag,
+    /// The chosen item for each ingredient of the recipe, in the order of the recipe.
+    pub ingredients: Vec<PlannedIngredient>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedIngredient {
+    pub ingredient: IngredientTag,
+    pub count: usize,
+    /// The item that is used for the ingredient.
+    pub item_tag: ItemTag,
+    /// The index of the step that synthesizes the item, or `None` if it is gathered.
+    pub source_step: Option<usize>,
+}
+
+/// Visit the dependencies of the roots depth-first, returning every item after its dependencies.
+fn topological_order(
+    roots: impl IntoIterator<Item = ItemTag>,
+    mut dependencies: impl FnMut(ItemTag) -> Result<Vec<ItemTag>, PlanError>,
+) -> Result<Vec<ItemTag>, PlanError> {
+    let mut order = vec![];
+    let mut done = HashSet::new();
+    // the items that are being visited, with the dependencies that are left to visit
+    let mut stack: Vec<(ItemTag, std::vec::IntoIter<ItemTag>)> = vec![];
+
+    for root in roots {
+        if done.contains(&root) {
+            continue;
+        }
+        stack.push((root, dependencies(root)?.into_iter()));
+
+        while let Some((item_tag, remaining)) = stack.last_mut() {
+            let Some(dependency) = remaining.next() else {
+                _ = done.insert(*item_tag);
+                order.push(*item_tag);
+                _ = stack.pop();
+                continue;
+            };
+
+            if done.contains(&dependency) {
+                continue;
+            }
+            if let Some(start) = stack.iter().position(|(tag, _)| *tag == dependency) {
+                let mut cycle = stack[start..]
+                    .iter()
+                    .map(|(tag, _)| *tag)
+                    .collect::<Vec<_>>();
+                cycle.push(dependency);
+                return Err(PlanError::Cycle(cycle));
+            }
+            stack.push((dependency, dependencies(dependency)?.into_iter()));
+        }
+    }
+
+    Ok(order)
+}
+
+/// An error that occurs when planning the syntheses for an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    /// The item has no recipe
+    NotSynthesizable(ItemTag),
+    /// No item in the game data can be used for an ingredient
+    NoCandidates {
+        recipe: ItemTag,
+        ingredient: IngredientTag,
+    },
+    /// The chosen item can't be used for the ingredient
+    InvalidChoice {
+        recipe: ItemTag,
+        ingredient_index: usize,
+        item_tag: ItemTag,
+    },
+    /// The items depend on each other. The first and last item are the same.
+    Cycle(Vec<ItemTag>),
+    /// The items need a chain of more than [`MAX_CHAIN_LENGTH`] syntheses
+    ChainTooLong(Vec<ItemTag>),
+}
+
+impl Display for PlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotSynthesizable(tag) => write!(f, "Item {tag} cannot be synthesized"),
+            Self::NoCandidates { recipe, ingredient } => write!(
+                f,
+                "No item can be used for ingredient {ingredient} of {recipe}"
+            ),
+            Self::InvalidChoice {
+                recipe,
+                ingredient_index,
+                item_tag,
+            } => write!(
+                f,
+                "Item {item_tag} cannot be used for ingredient {ingredient_index} of {recipe}"
+            ),
+            Self::Cycle(items) => {
+                let items = items.iter().map(|tag| tag.as_str()).collect::<Vec<_>>();
+                write!(f, "Recipes depend on each other: {}", items.join(" -> "))
+            }
+            Self::ChainTooLong(items) => {
+                let items = items.iter().map(|tag| tag.as_str()).collect::<Vec<_>>();
+                write!(
+                    f,
+                    "Recipes need more than {MAX_CHAIN_LENGTH} syntheses: {}",
+                    items.join(" -> ")
+                )
+            }
+        }
+    }
+}
+
+impl Error for PlanError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // any tags will do for the materials, so these don't depend on the contents of the game data
+    const UNI: ItemTag = ItemTag::ALL[0];
+    const STONE: ItemTag = ItemTag::ALL[1];
+    const BEEHIVE: ItemTag = ItemTag::ALL[2];
+
+    fn item(tag: ItemTag, categories: &'static [CategoryTag]) -> Item {
+        Item {
+            name: "",
+            tag,
+            shape_type: "",
+            use_type: UseType::ALL[0],
+            color: MatColor::White,
+            categories,
+        }
+    }
+
+    fn ingredient(ingredient: IngredientTag, count: usize) -> Ingredient {
+        Ingredient { ingredient, count }
+    }
+
+    fn game_data(
+        items: Vec<Item>,
+        ingredients: HashMap<ItemTag, &'static [Ingredient]>,
+    ) -> GameData {
+        GameData::new(
+            items.leak(),
+            &[],
+            ingredients,
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+            &[],
+        )
+    }
+
+    /// A kettle that needs an uni bag, which can use the kettle for its category ingredient. The
+    /// beehive can be used for it too, and is gathered unless it is given a recipe.
+    fn test_data(synthesized_beehive: bool) -> GameData {
+        let category = CategoryTag::ALL[0];
+        let items = vec![
+            item(UNI, &[]),
+            item(STONE, &[]),
+            item(ItemTag::MixTimewornAlchemyKettle, vec![category].leak()),
+            item(BEEHIVE, vec![category].leak()),
+            item(ItemTag::MixUniBag, &[]),
+        ];
+        let mut ingredients = HashMap::from([
+            (
+                ItemTag::MixTimewornAlchemyKettle,
+                &*vec![
+                    ingredient(IngredientTag::Item(STONE), 1),
+                    ingredient(IngredientTag::Item(ItemTag::MixUniBag), 2),
+                ]
+                .leak(),
+            ),
+            (
+                ItemTag::MixUniBag,
+                &*vec![
+                    ingredient(IngredientTag::Item(UNI), 2),
+                    ingredient(IngredientTag::Category(category), 1),
+                ]
+                .leak(),
+            ),
+        ]);
+        if synthesized_beehive {
+            _ = ingredients.insert(
+                BEEHIVE,
+                &*vec![ingredient(IngredientTag::Item(STONE), 1)].leak(),
+            );
+        }
+
+        game_data(items, ingredients)
+    }
+
+    #[test]
+    fn plan_orders_syntheses() {
+        let data = test_data(false);
+        let plan = CraftingPlanner::new(&data)
+            .plan(ItemTag::MixTimewornAlchemyKettle)
+            .unwrap();
+
+        assert_eq!(
+            plan.intermediates().collect::<Vec<_>>(),
+            [ItemTag::MixUniBag]
+        );
+        let [uni_bag, kettle] = &plan.steps[..] else {
+            panic!("expected 2 steps, got {plan:?}");
+        };
+        assert_eq!(kettle.item_tag, ItemTag::MixTimewornAlchemyKettle);
+        assert_eq!(
+            uni_bag.ingredients[1].item_tag, BEEHIVE,
+            "gathered items are preferred for categories"
+        );
+        assert_eq!(uni_bag.ingredients[1].source_step, None);
+        assert_eq!(kettle.ingredients[1].item_tag, ItemTag::MixUniBag);
+        assert_eq!(kettle.ingredients[1].source_step, Some(0));
+
+        let groups =
+            plan.material_groups(1, &[80], |item_tag| MaterialChoice::new(item_tag, 10, 3));
+        assert_eq!(groups[0], [MaterialChoice::new(STONE, 10, 3)]);
+        assert_eq!(
+            groups[1],
+            [MaterialChoice::new(ItemTag::MixUniBag, 80, 3); 2]
+        );
+
+        let plan = CraftingPlanner::new(&data)
+            .gathered(ItemTag::MixUniBag)
+            .plan(ItemTag::MixTimewornAlchemyKettle)
+            .unwrap();
+        assert_eq!(plan.steps.len(), 1);
+        assert_eq!(plan.steps[0].ingredients[1].source_step, None);
+    }
+
+    #[test]
+    fn plan_detects_cycles() {
+        let data = test_data(false);
+        let kettle = ItemTag::MixTimewornAlchemyKettle;
+
+        let result = CraftingPlanner::new(&data)
+            .choose(ItemTag::MixUniBag, 1, kettle)
+            .plan(kettle);
+        assert_eq!(
+            result,
+            Err(PlanError::Cycle(vec![kettle, ItemTag::MixUniBag, kettle]))
+        );
+
+        let result = CraftingPlanner::new(&data)
+            .choose(ItemTag::MixUniBag, 1, UNI)
+            .plan(kettle);
+        assert_eq!(
+            result,
+            Err(PlanError::InvalidChoice {
+                recipe: ItemTag::MixUniBag,
+                ingredient_index: 1,
+                item_tag: UNI
+            })
+        );
+        assert_eq!(
+            CraftingPlanner::new(&data).plan(UNI),
+            Err(PlanError::NotSynthesizable(UNI))
+        );
+
+        let graph = RecipeGraph::new(&data);
+        assert_eq!(graph.dependencies(kettle), [ItemTag::MixUniBag]);
+        assert_eq!(graph.dependencies(ItemTag::MixUniBag), [kettle]);
+    }
+
+    #[test]
+    fn plan_skips_items_on_the_chain() {
+        let data = test_data(true);
+        let kettle = ItemTag::MixTimewornAlchemyKettle;
+
+        // the kettle is the first candidate for the uni bag, but it is the item being crafted
+        let plan = CraftingPlanner::new(&data).plan(kettle).unwrap();
+        assert_eq!(
+            plan.intermediates().collect::<Vec<_>>(),
+            [BEEHIVE, ItemTag::MixUniBag]
+        );
+        assert_eq!(plan.steps[1].ingredients[1].item_tag, BEEHIVE);
+        assert_eq!(plan.steps[1].ingredients[1].source_step, Some(0));
+
+        // the kettle isn't being crafted here, but it needs the uni bag
+        let plan = CraftingPlanner::new(&data)
+            .plan(ItemTag::MixUniBag)
+            .unwrap();
+        assert_eq!(
+            (plan.steps.iter())
+                .map(|step| step.item_tag)
+                .collect::<Vec<_>>(),
+            [BEEHIVE, ItemTag::MixUniBag]
+        );
+    }
+
+    #[test]
+    fn plan_shares_diamond_dependencies() {
+        // the kettle needs an uni bag and a beehive, which both need the same synthesized item
+        let kettle = ItemTag::MixTimewornAlchemyKettle;
+        let shared = ItemTag::ALL[3];
+        let items = vec![
+            item(STONE, &[]),
+            item(shared, &[]),
+            item(BEEHIVE, &[]),
+            item(ItemTag::MixUniBag, &[]),
+            item(kettle, &[]),
+        ];
+        let recipe = |tags: &[ItemTag]| -> &'static [Ingredient] {
+            (tags.iter())
+                .map(|&tag| ingredient(IngredientTag::Item(tag), 1))
+                .collect::<Vec<_>>()
+                .leak()
+        };
+        let ingredients = HashMap::from([
+            (kettle, recipe(&[ItemTag::MixUniBag, BEEHIVE])),
+            (ItemTag::MixUniBag, recipe(&[shared])),
+            (BEEHIVE, recipe(&[shared, STONE])),
+            (shared, recipe(&[STONE])),
+        ]);
+        let data = game_data(items, ingredients);
+
+        let plan = CraftingPlanner::new(&data).plan(kettle).unwrap();
+        assert_eq!(
+            (plan.steps.iter())
+                .map(|step| step.item_tag)
+                .collect::<Vec<_>>(),
+            [shared, ItemTag::MixUniBag, BEEHIVE, kettle]
+        );
+        assert_eq!(plan.steps[1].ingredients[0].source_step, Some(0));
+        assert_eq!(plan.steps[2].ingredients[0].source_step, Some(0));
+        assert_eq!(plan.steps[2].ingredients[1].source_step, None);
+        assert_eq!(plan.steps[3].ingredients[1].source_step, Some(2));
+
+        // the shared item is planned once, so the plan of the beehive reuses it
+        let plan = CraftingPlanner::new(&data).plan(BEEHIVE).unwrap();
+        assert_eq!(plan.intermediates().collect::<Vec<_>>(), [shared]);
+    }
+
+    #[test]
+    fn builtin_recipes_can_be_planned() {
+        let data = GameData::builtin();
+        let plan = CraftingPlanner::new(data).plan(ItemTag::MixUniBag).unwrap();
+        assert_eq!(plan.steps.last().unwrap().item_tag, ItemTag::MixUniBag);
+
+        let mut chains = 0;
+        for (item_tag, _) in INGREDIENTS.entries() {
+            let item_tag = item_tag.parse().unwrap();
+            let plan = match CraftingPlanner::new(data).plan(item_tag) {
+                Ok(plan) => plan,
+                // some categories only have items that aren't in the game data
+                Err(PlanError::NoCandidates { .. }) => continue,
+                Err(e) => panic!("failed to plan {item_tag}: {e}"),
+            };
+            assert_eq!(plan.steps.last().unwrap().item_tag, item_tag);
+            for (i, step) in plan.steps.iter().enumerate() {
+                for ingredient in &step.ingredients {
+                    assert!(ingredient.source_step.is_none_or(|source| source < i));
+                }
+            }
+            if plan.steps.len() > 1 {
+                chains += 1;
+            }
+        }
+        assert!(chains > 0, "no recipe needs a synthesized ingredient");
+    }
+}
@@ -0,0 +1,57 @@
+//! Strongly-typed tags for the game data.
+//!
+//! The enums for items, categories, effects, use types, material colors, action kinds and damage
+//! attributes are generated by the build script from the game data. They can be converted from and
+//! to the tags used in the game data through [`FromStr`] and [`Display`].
+
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+include!(concat!(env!("OUT_DIR"), "/tags.rs"));
+
+/// The tag of an ingredient in a recipe, which is either a specific item or any item of a category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IngredientTag {
+    Item(ItemTag),
+    Category(CategoryTag),
+}
+
+impl FromStr for IngredientTag {
+    type Err = ParseTagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("ITEM_CATEGORY_") {
+            s.parse().map(Self::Category)
+        } else {
+            s.parse().map(Self::Item)
+        }
+    }
+}
+
+impl Display for IngredientTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Item(tag) => tag.fmt(f),
+            Self::Category(tag) => tag.fmt(f),
+        }
+    }
+}
+
+/// An error returned when parsing a tag that does not exist in the game data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTagError {
+    /// The kind of tag that was parsed, such as `item` or `category`.
+    pub kind: &'static str,
+    /// The tag that could not be found.
+    pub tag: String,
+}
+
+impl Display for ParseTagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown {} tag {}", self.kind, self.tag)
+    }
+}
+
+impl std::error::Error for ParseTagError {}
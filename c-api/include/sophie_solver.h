@@ -0,0 +1,221 @@
+/* The C API of the Atelier Sophie synthesis solver. Generated by cbindgen, do not edit. */
+
+#ifndef SOPHIE_SOLVER_H
+#define SOPHIE_SOLVER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Increases the effect of bonuses that have the color of the liquid by 50%.
+#define SOPHIE_PROPERTY_SYNERGY 1
+
+// Gives placed materials of the same color the bonuses of a placement.
+#define SOPHIE_PROPERTY_TUNING 2
+
+// Makes the bonuses around a placed material its color.
+#define SOPHIE_PROPERTY_COLOR_SURROUNDINGS 4
+
+// The color of a tile, material or liquid.
+typedef enum SophieColor {
+  SOPHIE_COLOR_RED = 0,
+  SOPHIE_COLOR_BLUE,
+  SOPHIE_COLOR_GREEN,
+  SOPHIE_COLOR_YELLOW,
+  SOPHIE_COLOR_WHITE,
+} SophieColor;
+
+// The result of a function that can fail.
+typedef enum SophieStatus {
+  SOPHIE_STATUS_OK = 0,
+  // A handle or pointer that is required was null.
+  SOPHIE_STATUS_NULL_POINTER,
+  // An argument was out of range, eg. a tile outside of the cauldron.
+  SOPHIE_STATUS_INVALID_ARGUMENT,
+  // A text could not be parsed.
+  SOPHIE_STATUS_PARSE_ERROR,
+  // The solver failed unexpectedly. This is a bug, the message describes it.
+  SOPHIE_STATUS_INTERNAL_ERROR,
+} SophieStatus;
+
+// The transformation of a placed material.
+typedef enum SophieTransformation {
+  SOPHIE_TRANSFORMATION_NONE = 0,
+  SOPHIE_TRANSFORMATION_FLIP_HORIZONTAL,
+  SOPHIE_TRANSFORMATION_FLIP_VERTICAL,
+  SOPHIE_TRANSFORMATION_ROTATE90,
+  SOPHIE_TRANSFORMATION_ROTATE180,
+  SOPHIE_TRANSFORMATION_ROTATE270,
+} SophieTransformation;
+
+// The transformations the solver may use for placements.
+typedef enum SophieTransformationType {
+  SOPHIE_TRANSFORMATION_TYPE_NONE = 0,
+  SOPHIE_TRANSFORMATION_TYPE_FLIP_HORIZONTAL,
+  SOPHIE_TRANSFORMATION_TYPE_FLIP_VERTICAL,
+  SOPHIE_TRANSFORMATION_TYPE_ROTATE,
+} SophieTransformationType;
+
+// A cauldron handle.
+typedef struct SophieCauldron SophieCauldron;
+
+// A problem handle.
+typedef struct SophieProblem SophieProblem;
+
+// A result handle, with the routes that `sophie_solve` found.
+typedef struct SophieResult SophieResult;
+
+// Called by `sophie_solve` with the progress from 0 to 1 and the amount of routes found so far.
+typedef void (*SophieProgressCallback)(float progress, size_t routes_found, void *user_data);
+
+// A placement of a material in a route.
+typedef struct SophieMove {
+  // The goal whose material group the material is in.
+  size_t group;
+  // The index of the material in its group.
+  size_t index;
+  // The index of the tile in the cauldron where the top left of the shape is placed, counted
+  // row by row.
+  size_t position;
+  enum SophieTransformation transformation;
+} SophieMove;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// A description of the last error on this thread. It stays valid until the next call that fails
+// on this thread.
+const char *sophie_last_error(void);
+
+// The status of the last error on this thread, eg. why a function returned a null handle.
+enum SophieStatus sophie_last_error_status(void);
+
+// Create a cauldron with `size` rows and columns of white tiles without bonuses, where `color` is
+// the color of the liquid. Returns null if the size is not between 1 and 6.
+struct SophieCauldron *sophie_cauldron_new(size_t size, enum SophieColor color);
+
+// Create a cauldron from the text format of cauldrons, eg. `"color: white\nY0 W1\n-- B0"`.
+// Returns null if the text is invalid, or if it has placed materials like `Y0:0.1`, because
+// problems start without materials.
+struct SophieCauldron *sophie_cauldron_from_text(const char *text);
+
+// Set the color and bonus level (0 to 3) of the tile at column `x` and row `y`.
+enum SophieStatus sophie_cauldron_set_tile(struct SophieCauldron *cauldron,
+                                           size_t x,
+                                           size_t y,
+                                           enum SophieColor color,
+                                           uint32_t level);
+
+// Make the tile at column `x` and row `y` a hole.
+enum SophieStatus sophie_cauldron_set_hole(struct SophieCauldron *cauldron, size_t x, size_t y);
+
+// Set the bonus scores for tiles of level 1, 2 and 3. They are percentages of the effect value
+// of the placed material if `percentage` is set, and a flat amount otherwise.
+enum SophieStatus sophie_cauldron_set_bonus_scores(struct SophieCauldron *cauldron,
+                                                   bool percentage,
+                                                   uint32_t level1,
+                                                   uint32_t level2,
+                                                   uint32_t level3);
+
+// Set the properties of the cauldron, a combination of the `SOPHIE_PROPERTY_*` flags.
+enum SophieStatus sophie_cauldron_set_properties(struct SophieCauldron *cauldron,
+                                                 uint32_t properties);
+
+// Release a cauldron. Problems that were created from it keep their copy of it.
+void sophie_cauldron_free(struct SophieCauldron *cauldron);
+
+// Create a problem for a copy of the cauldron, without goals or materials. Goals are added with
+// `sophie_problem_add_goal`, each with a group of materials that contribute to it.
+struct SophieProblem *sophie_problem_new(const struct SophieCauldron *cauldron);
+
+// Add a goal that is met at each of the `count` effect value thresholds. Its material group has
+// the index of the goal, counting from 0 in the order goals are added.
+enum SophieStatus sophie_problem_add_goal(struct SophieProblem *problem,
+                                          const uint32_t *thresholds,
+                                          size_t count);
+
+// Add a material to the group of a goal. `shape` points to the 3 rows of the shape, where the
+// bits `0b100`, `0b010` and `0b001` are the tiles from left to right.
+enum SophieStatus sophie_problem_add_material(struct SophieProblem *problem,
+                                              size_t group,
+                                              enum SophieColor color,
+                                              uint32_t effect_value,
+                                              const uint8_t *shape,
+                                              uint32_t quality);
+
+// Set the transformations the solver may use. By default materials aren't transformed.
+enum SophieStatus sophie_problem_set_transformations(struct SophieProblem *problem,
+                                                     enum SophieTransformationType transformations);
+
+// Allow `max_overlaps` placements to overlap other materials, each overlapping at most
+// `max_materials_per_overlap` materials. By default no overlaps are allowed.
+enum SophieStatus sophie_problem_set_overlap_limits(struct SophieProblem *problem,
+                                                    size_t max_overlaps,
+                                                    size_t max_materials_per_overlap);
+
+// Optimize the predicted quality of the item alongside the goals. The predicted quality is the
+// average quality of the materials left in the cauldron plus `quality_bonus`, capped at
+// `max_quality`.
+enum SophieStatus sophie_problem_set_quality_model(struct SophieProblem *problem,
+                                                   uint32_t quality_bonus,
+                                                   uint32_t max_quality);
+
+// Release a problem. Results that were solved from it stay valid.
+void sophie_problem_free(struct SophieProblem *problem);
+
+// Find the optimal routes for a problem. Returns null if the problem has no goals, or if the
+// cauldron has materials that are not in the problem.
+//
+// `progress` is called on this thread with `user_data` while solving, and may be null. If
+// `cancel` is not null, the solver stops once it is set to true, eg. by another thread with an
+// atomic store, and returns the routes found so far.
+struct SophieResult *sophie_solve(const struct SophieProblem *problem,
+                                  SophieProgressCallback progress,
+                                  void *user_data,
+                                  const bool *cancel);
+
+// The amount of routes, which each achieve a different combination of goals.
+size_t sophie_result_route_count(const struct SophieResult *result);
+
+// Write the amount of thresholds that the route meets for each goal to `achieved`, which has room
+// for `len` goals. Returns the amount of goals, or 0 if there is no such route.
+size_t sophie_result_achieved_goals(const struct SophieResult *result,
+                                    size_t route,
+                                    size_t *achieved,
+                                    size_t len);
+
+// Write the predicted quality of the route to `quality`. Returns false if the problem has no
+// quality model or there is no such route.
+bool sophie_result_quality(const struct SophieResult *result, size_t route, uint32_t *quality);
+
+// The amount of placements in the route that overlapped other materials.
+size_t sophie_result_overlaps(const struct SophieResult *result, size_t route);
+
+// The amount of moves in the route, or 0 if there is no such route.
+size_t sophie_result_move_count(const struct SophieResult *result, size_t route);
+
+// Write a move of a route to `out`.
+enum SophieStatus sophie_result_move(const struct SophieResult *result,
+                                     size_t route,
+                                     size_t index,
+                                     struct SophieMove *out);
+
+// Write the route in route notation, eg. `0.0@a1; 1.0@c3R90`, to `buffer` as a nul-terminated
+// string, truncated to fit in `len` bytes. Like `snprintf`, returns the length of the whole
+// notation without the nul byte, or 0 if there is no such route.
+size_t sophie_result_notation(const struct SophieResult *result,
+                              size_t route,
+                              char *buffer,
+                              size_t len);
+
+// Release a result.
+void sophie_result_free(struct SophieResult *result);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* SOPHIE_SOLVER_H */
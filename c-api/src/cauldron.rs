@@ -0,0 +1,179 @@
+//! Building cauldrons, either tile by tile or from the text format of cauldrons.
+
+use std::ffi::{c_char, CStr};
+
+use synth_solver::{tinyvec::ArrayVec, BonusScores, Cauldron, CauldronProperties, Color, Tile};
+
+use crate::*;
+
+/// Increases the effect of bonuses that have the color of the liquid by 50%.
+pub const SOPHIE_PROPERTY_SYNERGY: u32 = 0b001;
+/// Gives placed materials of the same color the bonuses of a placement.
+pub const SOPHIE_PROPERTY_TUNING: u32 = 0b010;
+/// Makes the bonuses around a placed material its color.
+pub const SOPHIE_PROPERTY_COLOR_SURROUNDINGS: u32 = 0b100;
+
+// the values are written out so cbindgen can put them in the header
+const _: () = assert!(SOPHIE_PROPERTY_SYNERGY == CauldronProperties::SYNERGY.bits());
+const _: () = assert!(SOPHIE_PROPERTY_TUNING == CauldronProperties::TUNING.bits());
+const _: () =
+    assert!(SOPHIE_PROPERTY_COLOR_SURROUNDINGS == CauldronProperties::COLOR_SURROUNDINGS.bits());
+
+/// A cauldron handle.
+pub struct SophieCauldron {
+    pub(crate) cauldron: Cauldron,
+}
+
+/// Create a cauldron with `size` rows and columns of white tiles without bonuses, where `color` is
+/// the color of the liquid. Returns null if the size is not between 1 and 6.
+#[no_mangle]
+pub extern "C" fn sophie_cauldron_new(size: usize, color: SophieColor) -> *mut SophieCauldron {
+    to_handle(|| {
+        if !(1..=6).contains(&size) {
+            return Err(Error::invalid(format!(
+                "the size must be between 1 and 6, got {size}"
+            )));
+        }
+        let tile = Tile {
+            color: Color::White,
+            level: 0,
+            played_material_index: None,
+        };
+        let mut tiles = ArrayVec::new();
+        tiles.extend(std::iter::repeat_n(Some(tile), size * size));
+
+        Ok(SophieCauldron {
+            cauldron: Cauldron {
+                size,
+                tiles,
+                bonus_scores: BonusScores::Flat(0, 0, 0),
+                color: color.into(),
+                properties: CauldronProperties::empty(),
+                overlaps: 0,
+            },
+        })
+    })
+}
+
+/// Create a cauldron from the text format of cauldrons, eg. `"color: white\nY0 W1\n-- B0"`.
+/// Returns null if the text is invalid, or if it has placed materials like `Y0:0.1`, because
+/// problems start without materials.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_cauldron_from_text(text: *const c_char) -> *mut SophieCauldron {
+    to_handle(|| {
+        let text = CStr::from_ptr(deref(text, "text")?)
+            .to_str()
+            .map_err(|e| Error {
+                status: SophieStatus::ParseError,
+                message: format!("the text is not UTF-8: {e}"),
+            })?;
+        let cauldron = text.parse::<Cauldron>().map_err(|e| Error {
+            status: SophieStatus::ParseError,
+            message: e.to_string(),
+        })?;
+        if let Some(index) = cauldron
+            .tiles
+            .iter()
+            .position(|tile| tile.is_some_and(|tile| tile.played_material_index.is_some()))
+        {
+            return Err(Error::invalid(format!(
+                "the tile at ({}, {}) already has a material",
+                index % cauldron.size,
+                index / cauldron.size
+            )));
+        }
+        Ok(SophieCauldron { cauldron })
+    })
+}
+
+/// Set the color and bonus level (0 to 3) of the tile at column `x` and row `y`.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_cauldron_set_tile(
+    cauldron: *mut SophieCauldron,
+    x: usize,
+    y: usize,
+    color: SophieColor,
+    level: u32,
+) -> SophieStatus {
+    to_status(|| {
+        let cauldron = &mut deref_mut(cauldron, "cauldron")?.cauldron;
+        check_position(cauldron, x, y)?;
+        if level > 3 {
+            return Err(Error::invalid(format!(
+                "the level must be between 0 and 3, got {level}"
+            )));
+        }
+
+        *cauldron.get_tile_mut((x, y)) = Some(Tile {
+            color: color.into(),
+            level,
+            played_material_index: None,
+        });
+        Ok(())
+    })
+}
+
+/// Make the tile at column `x` and row `y` a hole.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_cauldron_set_hole(
+    cauldron: *mut SophieCauldron,
+    x: usize,
+    y: usize,
+) -> SophieStatus {
+    to_status(|| {
+        let cauldron = &mut deref_mut(cauldron, "cauldron")?.cauldron;
+        check_position(cauldron, x, y)?;
+        *cauldron.get_tile_mut((x, y)) = None;
+        Ok(())
+    })
+}
+
+/// Set the bonus scores for tiles of level 1, 2 and 3. They are percentages of the effect value
+/// of the placed material if `percentage` is set, and a flat amount otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_cauldron_set_bonus_scores(
+    cauldron: *mut SophieCauldron,
+    percentage: bool,
+    level1: u32,
+    level2: u32,
+    level3: u32,
+) -> SophieStatus {
+    to_status(|| {
+        deref_mut(cauldron, "cauldron")?.cauldron.bonus_scores = if percentage {
+            BonusScores::Percentage(level1, level2, level3)
+        } else {
+            BonusScores::Flat(level1, level2, level3)
+        };
+        Ok(())
+    })
+}
+
+/// Set the properties of the cauldron, a combination of the `SOPHIE_PROPERTY_*` flags.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_cauldron_set_properties(
+    cauldron: *mut SophieCauldron,
+    properties: u32,
+) -> SophieStatus {
+    to_status(|| {
+        let cauldron = &mut deref_mut(cauldron, "cauldron")?.cauldron;
+        cauldron.properties = CauldronProperties::from_bits(properties)
+            .ok_or_else(|| Error::invalid(format!("unknown properties {properties:#x}")))?;
+        Ok(())
+    })
+}
+
+/// Release a cauldron. Problems that were created from it keep their copy of it.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_cauldron_free(cauldron: *mut SophieCauldron) {
+    free(cauldron);
+}
+
+fn check_position(cauldron: &Cauldron, x: usize, y: usize) -> Result<(), Error> {
+    if x >= cauldron.size || y >= cauldron.size {
+        return Err(Error::invalid(format!(
+            "({x}, {y}) is outside of the {0}x{0} cauldron",
+            cauldron.size
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! A C API for the solver, for embedding it in programs that aren't written in Rust. The header is
+//! `include/sophie_solver.h`, which is generated from this crate with cbindgen.
+//!
+//! Cauldrons, problems and results are opaque handles that are created by a `sophie_*_new` or
+//! `sophie_solve` function and must be released with the matching `sophie_*_free` function.
+//! Functions that can fail return a [`SophieStatus`] or a null handle, and
+//! [`sophie_last_error`] and [`sophie_last_error_status`] describe the error. Panics don't unwind
+//! into the caller, they fail the function with [`SophieStatus::InternalError`] instead.
+//!
+//! Every pointer that is passed to a function must be null or point to a valid value of its type,
+//! and a handle must not be used by two threads at the same time.
+
+#![warn(
+    clippy::cloned_instead_of_copied,
+    clippy::explicit_iter_loop,
+    clippy::return_self_not_must_use,
+    clippy::trivially_copy_pass_by_ref,
+    clippy::uninlined_format_args,
+    clippy::use_self
+)]
+#![allow(clippy::wildcard_imports)]
+// the safety requirements of all functions are the same, see the module docs
+#![allow(clippy::missing_safety_doc)]
+
+mod cauldron;
+mod problem;
+mod result;
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    ffi::{c_char, CString},
+    panic::AssertUnwindSafe,
+};
+
+use synth_solver::{Color, Transformation};
+
+pub use cauldron::*;
+pub use problem::*;
+pub use result::*;
+
+/// The result of a function that can fail.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SophieStatus {
+    Ok = 0,
+    /// A handle or pointer that is required was null.
+    NullPointer,
+    /// An argument was out of range, eg. a tile outside of the cauldron.
+    InvalidArgument,
+    /// A text could not be parsed.
+    ParseError,
+    /// The solver failed unexpectedly. This is a bug, the message describes it.
+    InternalError,
+}
+
+/// The color of a tile, material or liquid.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SophieColor {
+    Red = 0,
+    Blue,
+    Green,
+    Yellow,
+    White,
+}
+
+impl From<SophieColor> for Color {
+    fn from(color: SophieColor) -> Self {
+        match color {
+            SophieColor::Red => Self::Red,
+            SophieColor::Blue => Self::Blue,
+            SophieColor::Green => Self::Green,
+            SophieColor::Yellow => Self::Yellow,
+            SophieColor::White => Self::White,
+        }
+    }
+}
+
+/// The transformation of a placed material.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SophieTransformation {
+    None = 0,
+    FlipHorizontal,
+    FlipVertical,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl From<Option<Transformation>> for SophieTransformation {
+    fn from(transformation: Option<Transformation>) -> Self {
+        match transformation {
+            None => Self::None,
+            Some(Transformation::FlipHorizontal) => Self::FlipHorizontal,
+            Some(Transformation::FlipVertical) => Self::FlipVertical,
+            Some(Transformation::Rotate90) => Self::Rotate90,
+            Some(Transformation::Rotate180) => Self::Rotate180,
+            Some(Transformation::Rotate270) => Self::Rotate270,
+        }
+    }
+}
+
+/// An error with the status that is returned for it.
+struct Error {
+    status: SophieStatus,
+    message: String,
+}
+
+impl Error {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: SophieStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+    static LAST_ERROR_STATUS: Cell<SophieStatus> = const { Cell::new(SophieStatus::Ok) };
+}
+
+fn set_last_error(error: &Error) {
+    // messages don't contain nul bytes, but don't lose the message if they ever do
+    let message = CString::new(error.message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+    LAST_ERROR_STATUS.with(|last_status| last_status.set(error.status));
+}
+
+/// Run `f`, or return `default` if it panics, remembering the panic as the last error.
+fn catch_panic<T>(default: T, f: impl FnOnce() -> T) -> T {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        set_last_error(&Error {
+            status: SophieStatus::InternalError,
+            message: format!("the solver panicked: {}", panic_message(&*payload)),
+        });
+        default
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error")
+}
+
+/// Run `f` and return its status, remembering the error if it fails.
+fn to_status(f: impl FnOnce() -> Result<(), Error>) -> SophieStatus {
+    catch_panic(SophieStatus::InternalError, || match f() {
+        Ok(()) => SophieStatus::Ok,
+        Err(e) => {
+            set_last_error(&e);
+            e.status
+        }
+    })
+}
+
+/// Run `f` and return a new handle for its result, or null if it fails.
+fn to_handle<T>(f: impl FnOnce() -> Result<T, Error>) -> *mut T {
+    catch_panic(std::ptr::null_mut(), || match f() {
+        Ok(value) => Box::into_raw(Box::new(value)),
+        Err(e) => {
+            set_last_error(&e);
+            std::ptr::null_mut()
+        }
+    })
+}
+
+/// Borrow the value behind a handle, or fail if it is null.
+unsafe fn deref<'a, T>(handle: *const T, name: &str) -> Result<&'a T, Error> {
+    handle.as_ref().ok_or_else(|| Error {
+        status: SophieStatus::NullPointer,
+        message: format!("{name} is null"),
+    })
+}
+
+unsafe fn deref_mut<'a, T>(handle: *mut T, name: &str) -> Result<&'a mut T, Error> {
+    handle.as_mut().ok_or_else(|| Error {
+        status: SophieStatus::NullPointer,
+        message: format!("{name} is null"),
+    })
+}
+
+/// Release a handle that was returned by this API. Null handles are ignored.
+unsafe fn free<T>(handle: *mut T) {
+    catch_panic((), || {
+        if !handle.is_null() {
+            drop(Box::from_raw(handle));
+        }
+    });
+}
+
+/// A description of the last error on this thread. It stays valid until the next call that fails
+/// on this thread.
+#[no_mangle]
+pub extern "C" fn sophie_last_error() -> *const c_char {
+    catch_panic(c"".as_ptr(), || {
+        LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+    })
+}
+
+/// The status of the last error on this thread, eg. why a function returned a null handle.
+#[no_mangle]
+pub extern "C" fn sophie_last_error_status() -> SophieStatus {
+    catch_panic(SophieStatus::InternalError, || {
+        LAST_ERROR_STATUS.with(Cell::get)
+    })
+}
@@ -0,0 +1,219 @@
+//! Building problems from a cauldron, goals and materials, and solving them.
+
+use std::{
+    ffi::c_void,
+    ops::ControlFlow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use synth_solver::{
+    solver::{SolverProblem, SolverSettings},
+    Goal, Material, OverlapLimits, QualityModel, Shape, TransformationType, MAX_GOALS,
+    MAX_ITEMS_IN_GROUP,
+};
+
+use crate::*;
+
+/// The transformations the solver may use for placements.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SophieTransformationType {
+    None = 0,
+    FlipHorizontal,
+    FlipVertical,
+    Rotate,
+}
+
+/// A problem handle.
+pub struct SophieProblem {
+    problem: SolverProblem,
+}
+
+/// Called by `sophie_solve` with the progress from 0 to 1 and the amount of routes found so far.
+pub type SophieProgressCallback =
+    Option<extern "C" fn(progress: f32, routes_found: usize, user_data: *mut c_void)>;
+
+/// Create a problem for a copy of the cauldron, without goals or materials. Goals are added with
+/// `sophie_problem_add_goal`, each with a group of materials that contribute to it.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_problem_new(cauldron: *const SophieCauldron) -> *mut SophieProblem {
+    to_handle(|| {
+        Ok(SophieProblem {
+            problem: SolverProblem {
+                cauldron: deref(cauldron, "cauldron")?.cauldron.clone(),
+                materials: vec![],
+                goals: vec![],
+                settings: SolverSettings::default(),
+            },
+        })
+    })
+}
+
+/// Add a goal that is met at each of the `count` effect value thresholds. Its material group has
+/// the index of the goal, counting from 0 in the order goals are added.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_problem_add_goal(
+    problem: *mut SophieProblem,
+    thresholds: *const u32,
+    count: usize,
+) -> SophieStatus {
+    to_status(|| {
+        let problem = &mut deref_mut(problem, "problem")?.problem;
+        if problem.goals.len() == MAX_GOALS {
+            return Err(Error::invalid(format!(
+                "a problem can have at most {MAX_GOALS} goals"
+            )));
+        }
+        let thresholds = if count == 0 {
+            vec![]
+        } else {
+            std::slice::from_raw_parts(deref(thresholds, "thresholds")?, count).to_vec()
+        };
+
+        problem.goals.push(Goal {
+            effect_value_thresholds: thresholds,
+        });
+        problem.materials.push(vec![]);
+        Ok(())
+    })
+}
+
+/// Add a material to the group of a goal. `shape` points to the 3 rows of the shape, where the
+/// bits `0b100`, `0b010` and `0b001` are the tiles from left to right.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_problem_add_material(
+    problem: *mut SophieProblem,
+    group: usize,
+    color: SophieColor,
+    effect_value: u32,
+    shape: *const u8,
+    quality: u32,
+) -> SophieStatus {
+    to_status(|| {
+        let problem = &mut deref_mut(problem, "problem")?.problem;
+        let shape: [u8; 3] = std::slice::from_raw_parts(deref(shape, "shape")?, 3)
+            .try_into()
+            .unwrap();
+        if shape.iter().any(|&row| row > 0b111) || shape == [0; 3] {
+            return Err(Error::invalid(format!(
+                "the shape must have 3 rows of 3 bits and at least one tile, got {shape:?}"
+            )));
+        }
+
+        let Some(materials) = problem.materials.get_mut(group) else {
+            return Err(Error::invalid(format!("there is no goal {group}")));
+        };
+        if materials.len() == MAX_ITEMS_IN_GROUP {
+            return Err(Error::invalid(format!(
+                "a group can have at most {MAX_ITEMS_IN_GROUP} materials"
+            )));
+        }
+
+        materials.push(
+            Material::new(color.into(), effect_value, Shape::from_binary(shape))
+                .with_quality(quality),
+        );
+        Ok(())
+    })
+}
+
+/// Set the transformations the solver may use. By default materials aren't transformed.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_problem_set_transformations(
+    problem: *mut SophieProblem,
+    transformations: SophieTransformationType,
+) -> SophieStatus {
+    to_status(|| {
+        let settings = &mut deref_mut(problem, "problem")?.problem.settings;
+        settings.transformations = match transformations {
+            SophieTransformationType::None => TransformationType::None,
+            SophieTransformationType::FlipHorizontal => TransformationType::FlipHorizontal,
+            SophieTransformationType::FlipVertical => TransformationType::FlipVertical,
+            SophieTransformationType::Rotate => TransformationType::Rotate,
+        };
+        Ok(())
+    })
+}
+
+/// Allow `max_overlaps` placements to overlap other materials, each overlapping at most
+/// `max_materials_per_overlap` materials. By default no overlaps are allowed.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_problem_set_overlap_limits(
+    problem: *mut SophieProblem,
+    max_overlaps: usize,
+    max_materials_per_overlap: usize,
+) -> SophieStatus {
+    to_status(|| {
+        let settings = &mut deref_mut(problem, "problem")?.problem.settings;
+        settings.overlap_limits = OverlapLimits {
+            max_overlaps,
+            max_materials_per_overlap,
+        };
+        Ok(())
+    })
+}
+
+/// Optimize the predicted quality of the item alongside the goals. The predicted quality is the
+/// average quality of the materials left in the cauldron plus `quality_bonus`, capped at
+/// `max_quality`.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_problem_set_quality_model(
+    problem: *mut SophieProblem,
+    quality_bonus: u32,
+    max_quality: u32,
+) -> SophieStatus {
+    to_status(|| {
+        let settings = &mut deref_mut(problem, "problem")?.problem.settings;
+        settings.quality_model = Some(QualityModel {
+            quality_bonus,
+            max_quality,
+        });
+        Ok(())
+    })
+}
+
+/// Release a problem. Results that were solved from it stay valid.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_problem_free(problem: *mut SophieProblem) {
+    free(problem);
+}
+
+/// Find the optimal routes for a problem. Returns null if the problem has no goals, or if the
+/// cauldron has materials that are not in the problem.
+///
+/// `progress` is called on this thread with `user_data` while solving, and may be null. If
+/// `cancel` is not null, the solver stops once it is set to true, eg. by another thread with an
+/// atomic store, and returns the routes found so far.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_solve(
+    problem: *const SophieProblem,
+    progress: SophieProgressCallback,
+    user_data: *mut c_void,
+    cancel: *const bool,
+) -> *mut SophieResult {
+    to_handle(|| {
+        let problem = &deref(problem, "problem")?.problem;
+        if problem.goals.is_empty() {
+            return Err(Error::invalid("the problem has no goals"));
+        }
+        // the flag is only read, but `AtomicBool::from_ptr` takes a mutable pointer
+        let cancel = (!cancel.is_null()).then(|| AtomicBool::from_ptr(cancel.cast_mut()));
+
+        let routes = problem
+            .solve(Some(Box::new(move |fraction, routes| {
+                if let Some(progress) = progress {
+                    progress(fraction, routes.len(), user_data);
+                }
+                if cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            })))
+            .map_err(|e| Error::invalid(e.to_string()))?;
+
+        Ok(SophieResult {
+            routes,
+            size: problem.cauldron.size,
+        })
+    })
+}
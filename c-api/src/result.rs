@@ -0,0 +1,176 @@
+//! Reading the routes that the solver found.
+
+use std::ffi::c_char;
+
+use synth_solver::solver::{format_route, SolverResult, SolverRoute};
+
+use crate::*;
+
+/// A result handle, with the routes that `sophie_solve` found.
+pub struct SophieResult {
+    pub(crate) routes: SolverResult,
+    /// The size of the cauldron, for route notation.
+    pub(crate) size: usize,
+}
+
+/// A placement of a material in a route.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SophieMove {
+    /// The goal whose material group the material is in.
+    pub group: usize,
+    /// The index of the material in its group.
+    pub index: usize,
+    /// The index of the tile in the cauldron where the top left of the shape is placed, counted
+    /// row by row.
+    pub position: usize,
+    pub transformation: SophieTransformation,
+}
+
+/// The amount of routes, which each achieve a different combination of goals.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_route_count(result: *const SophieResult) -> usize {
+    catch_panic(0, || {
+        result.as_ref().map_or(0, |result| result.routes.len())
+    })
+}
+
+/// Write the amount of thresholds that the route meets for each goal to `achieved`, which has room
+/// for `len` goals. Returns the amount of goals, or 0 if there is no such route.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_achieved_goals(
+    result: *const SophieResult,
+    route: usize,
+    achieved: *mut usize,
+    len: usize,
+) -> usize {
+    catch_panic(0, || {
+        let Ok(route) = get_route(result, route) else {
+            return 0;
+        };
+        let goals = &route.goal_result.achieved_goals;
+        if !achieved.is_null() {
+            let len = len.min(goals.len());
+            std::slice::from_raw_parts_mut(achieved, len).copy_from_slice(&goals[..len]);
+        }
+        goals.len()
+    })
+}
+
+/// Write the predicted quality of the route to `quality`. Returns false if the problem has no
+/// quality model or there is no such route.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_quality(
+    result: *const SophieResult,
+    route: usize,
+    quality: *mut u32,
+) -> bool {
+    catch_panic(false, || {
+        let Some(value) = get_route(result, route)
+            .ok()
+            .and_then(|route| route.goal_result.quality)
+        else {
+            return false;
+        };
+        if let Some(quality) = quality.as_mut() {
+            *quality = value;
+        }
+        true
+    })
+}
+
+/// The amount of placements in the route that overlapped other materials.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_overlaps(
+    result: *const SophieResult,
+    route: usize,
+) -> usize {
+    catch_panic(0, || {
+        get_route(result, route).map_or(0, |route| route.overlaps)
+    })
+}
+
+/// The amount of moves in the route, or 0 if there is no such route.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_move_count(
+    result: *const SophieResult,
+    route: usize,
+) -> usize {
+    catch_panic(0, || {
+        get_route(result, route).map_or(0, |route| route.moves.len())
+    })
+}
+
+/// Write a move of a route to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_move(
+    result: *const SophieResult,
+    route: usize,
+    index: usize,
+    out: *mut SophieMove,
+) -> SophieStatus {
+    to_status(|| {
+        let route = get_route(result, route)?;
+        let out = deref_mut(out, "out")?;
+        let move_ = route.moves.get(index).ok_or_else(|| {
+            Error::invalid(format!(
+                "the route has {} moves, got move {index}",
+                route.moves.len()
+            ))
+        })?;
+
+        *out = SophieMove {
+            group: move_.material_index.0,
+            index: move_.material_index.1,
+            position: move_.placement.index,
+            transformation: move_.placement.transformation.into(),
+        };
+        Ok(())
+    })
+}
+
+/// Write the route in route notation, eg. `0.0@a1; 1.0@c3R90`, to `buffer` as a nul-terminated
+/// string, truncated to fit in `len` bytes. Like `snprintf`, returns the length of the whole
+/// notation without the nul byte, or 0 if there is no such route.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_notation(
+    result: *const SophieResult,
+    route: usize,
+    buffer: *mut c_char,
+    len: usize,
+) -> usize {
+    catch_panic(0, || {
+        let (Ok(size), Ok(route)) = (
+            deref(result, "result").map(|result| result.size),
+            get_route(result, route),
+        ) else {
+            return 0;
+        };
+        let notation = format_route(&route.moves, size);
+        if !buffer.is_null() && len > 0 {
+            let copied = notation.len().min(len - 1);
+            std::ptr::copy_nonoverlapping(notation.as_ptr().cast(), buffer, copied);
+            *buffer.add(copied) = 0;
+        }
+        notation.len()
+    })
+}
+
+/// Release a result.
+#[no_mangle]
+pub unsafe extern "C" fn sophie_result_free(result: *mut SophieResult) {
+    free(result);
+}
+
+unsafe fn get_route<'a>(
+    result: *const SophieResult,
+    route: usize,
+) -> Result<&'a SolverRoute, Error> {
+    let result = deref(result, "result")?;
+    result.routes.get(route).ok_or_else(|| {
+        Error::invalid(format!(
+            "there are {} routes, got route {route}",
+            result.routes.len()
+        ))
+    })
+}
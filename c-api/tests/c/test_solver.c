@@ -0,0 +1,189 @@
+// Solves the uni bag problem through the C API. Built and run by `tests/c_program.rs`.
+
+#include <stdio.h>
+#include <string.h>
+
+#include "sophie_solver.h"
+
+#define CHECK(condition)                                                        \
+    do {                                                                        \
+        if (!(condition)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,    \
+                    #condition);                                                \
+            fprintf(stderr, "last error: %s\n", sophie_last_error());          \
+            return 1;                                                           \
+        }                                                                       \
+    } while (0)
+
+static const char *UNI_BAG_ROWS[] = {
+    "BGYYW",
+    "WYYYY",
+    "RYRRY",
+    "RYRRY",
+    "WYYYY",
+};
+static const uint32_t UNI_BAG_LEVELS[5][5] = {
+    {0, 0, 0, 0, 0},
+    {0, 0, 0, 0, 1},
+    {0, 0, 1, 0, 0},
+    {0, 0, 0, 0, 1},
+    {0, 2, 0, 0, 0},
+};
+
+static SophieColor color(char c) {
+    switch (c) {
+    case 'R': return SOPHIE_COLOR_RED;
+    case 'B': return SOPHIE_COLOR_BLUE;
+    case 'G': return SOPHIE_COLOR_GREEN;
+    case 'Y': return SOPHIE_COLOR_YELLOW;
+    default: return SOPHIE_COLOR_WHITE;
+    }
+}
+
+static SophieProblem *uni_bag(const SophieCauldron *cauldron) {
+    static const uint8_t LINE[3] = {0x4, 0x4, 0x4};
+    static const uint8_t CORNER[3] = {0x4, 0x6, 0x0};
+    static const uint32_t GOALS[3][2] = {{50, 100}, {30, 50}, {30, 55}};
+
+    SophieProblem *problem = sophie_problem_new(cauldron);
+    for (size_t i = 0; i < 3; i++) {
+        sophie_problem_add_goal(problem, GOALS[i], 2);
+    }
+    sophie_problem_add_material(problem, 0, SOPHIE_COLOR_YELLOW, 15, LINE, 40);
+    sophie_problem_add_material(problem, 0, SOPHIE_COLOR_YELLOW, 15, LINE, 40);
+    sophie_problem_add_material(problem, 1, SOPHIE_COLOR_YELLOW, 10, CORNER, 25);
+    sophie_problem_add_material(problem, 2, SOPHIE_COLOR_WHITE, 15, LINE, 60);
+    return problem;
+}
+
+static void count_progress(float progress, size_t routes_found, void *user_data) {
+    (void)progress;
+    (void)routes_found;
+    *(int *)user_data += 1;
+}
+
+static void cancel_on_progress(float progress, size_t routes_found, void *user_data) {
+    (void)progress;
+    (void)routes_found;
+    *(bool *)user_data = true;
+}
+
+int main(void) {
+    // build the cauldron tile by tile
+    SophieCauldron *cauldron = sophie_cauldron_new(5, SOPHIE_COLOR_WHITE);
+    CHECK(cauldron != NULL);
+    for (size_t y = 0; y < 5; y++) {
+        for (size_t x = 0; x < 5; x++) {
+            CHECK(sophie_cauldron_set_tile(cauldron, x, y, color(UNI_BAG_ROWS[y][x]),
+                                           UNI_BAG_LEVELS[y][x]) == SOPHIE_STATUS_OK);
+        }
+    }
+    CHECK(sophie_cauldron_set_bonus_scores(cauldron, false, 3, 5, 7) == SOPHIE_STATUS_OK);
+
+    SophieProblem *problem = uni_bag(cauldron);
+    sophie_cauldron_free(cauldron);
+
+    int progress_calls = 0;
+    bool cancel = false;
+    SophieResult *result = sophie_solve(problem, count_progress, &progress_calls, &cancel);
+    CHECK(result != NULL);
+    CHECK(progress_calls > 0);
+    CHECK(sophie_result_route_count(result) == 2);
+
+    size_t best = 0;
+    size_t achieved[4] = {0};
+    for (size_t route = 0; route < 2; route++) {
+        size_t goals = sophie_result_achieved_goals(result, route, achieved, 4);
+        CHECK(goals == 3);
+        if (achieved[0] == 1 && achieved[1] == 1 && achieved[2] == 1) {
+            best = route;
+        }
+    }
+
+    size_t moves = sophie_result_move_count(result, best);
+    CHECK(moves == 4);
+    SophieMove move;
+    for (size_t i = 0; i < moves; i++) {
+        CHECK(sophie_result_move(result, best, i, &move) == SOPHIE_STATUS_OK);
+        CHECK(move.group < 3 && move.position < 25);
+        CHECK(move.transformation == SOPHIE_TRANSFORMATION_NONE);
+    }
+    CHECK(sophie_result_move(result, best, moves, &move) == SOPHIE_STATUS_INVALID_ARGUMENT);
+
+    char notation[64];
+    size_t len = sophie_result_notation(result, best, notation, sizeof notation);
+    CHECK(len > 0 && len == strlen(notation));
+    char truncated[4];
+    CHECK(sophie_result_notation(result, best, truncated, sizeof truncated) == len);
+    CHECK(strlen(truncated) == 3);
+    printf("route: %s\n", notation);
+
+    uint32_t quality;
+    CHECK(!sophie_result_quality(result, best, &quality));
+    sophie_result_free(result);
+
+    // the same cauldron from text, with quality optimized too
+    SophieCauldron *from_text = sophie_cauldron_from_text(
+        "bonus_scores: flat 3 5 7\n"
+        "B0 G0 Y0 Y0 W0\n"
+        "W0 Y0 Y0 Y0 Y1\n"
+        "R0 Y0 R1 R0 Y0\n"
+        "R0 Y0 R0 R0 Y1\n"
+        "W0 Y2 Y0 Y0 Y0\n");
+    CHECK(from_text != NULL);
+    SophieProblem *quality_problem = uni_bag(from_text);
+    sophie_cauldron_free(from_text);
+    CHECK(sophie_problem_set_quality_model(quality_problem, 0, 999) == SOPHIE_STATUS_OK);
+    result = sophie_solve(quality_problem, NULL, NULL, NULL);
+    CHECK(result != NULL);
+    CHECK(sophie_result_route_count(result) >= 2);
+    CHECK(sophie_result_quality(result, 0, &quality) && quality > 0);
+    sophie_result_free(result);
+    sophie_problem_free(quality_problem);
+
+    // cancelling still returns the routes found so far
+    cancel = false;
+    result = sophie_solve(problem, cancel_on_progress, &cancel, &cancel);
+    CHECK(result != NULL);
+    sophie_result_free(result);
+    sophie_problem_free(problem);
+
+    // errors
+    CHECK(sophie_cauldron_new(7, SOPHIE_COLOR_WHITE) == NULL);
+    CHECK(strstr(sophie_last_error(), "between 1 and 6") != NULL);
+    CHECK(sophie_cauldron_from_text("Q0") == NULL);
+    CHECK(sophie_last_error_status() == SOPHIE_STATUS_PARSE_ERROR);
+    CHECK(sophie_cauldron_from_text("Y0:9.9 W0\nW0 W0") == NULL);
+    CHECK(sophie_last_error_status() == SOPHIE_STATUS_INVALID_ARGUMENT);
+    CHECK(strstr(sophie_last_error(), "already has a material") != NULL);
+    CHECK(sophie_cauldron_set_tile(NULL, 0, 0, SOPHIE_COLOR_RED, 0) == SOPHIE_STATUS_NULL_POINTER);
+
+    cauldron = sophie_cauldron_new(4, SOPHIE_COLOR_RED);
+    CHECK(sophie_cauldron_set_tile(cauldron, 4, 0, SOPHIE_COLOR_RED, 0) ==
+          SOPHIE_STATUS_INVALID_ARGUMENT);
+    CHECK(sophie_cauldron_set_tile(cauldron, 0, 0, SOPHIE_COLOR_RED, 4) ==
+          SOPHIE_STATUS_INVALID_ARGUMENT);
+    CHECK(sophie_cauldron_set_hole(cauldron, 3, 3) == SOPHIE_STATUS_OK);
+    CHECK(sophie_cauldron_set_properties(cauldron, SOPHIE_PROPERTY_SYNERGY |
+                                                       SOPHIE_PROPERTY_TUNING) ==
+          SOPHIE_STATUS_OK);
+    CHECK(sophie_cauldron_set_properties(cauldron, 0x100) == SOPHIE_STATUS_INVALID_ARGUMENT);
+
+    problem = sophie_problem_new(cauldron);
+    CHECK(sophie_solve(problem, NULL, NULL, NULL) == NULL);
+    static const uint8_t EMPTY[3] = {0, 0, 0};
+    static const uint8_t LINE[3] = {0x4, 0x4, 0x4};
+    CHECK(sophie_problem_add_material(problem, 0, SOPHIE_COLOR_RED, 10, LINE, 0) ==
+          SOPHIE_STATUS_INVALID_ARGUMENT);
+    CHECK(sophie_problem_add_goal(problem, NULL, 0) == SOPHIE_STATUS_OK);
+    CHECK(sophie_problem_add_material(problem, 0, SOPHIE_COLOR_RED, 10, EMPTY, 0) ==
+          SOPHIE_STATUS_INVALID_ARGUMENT);
+    sophie_problem_free(problem);
+    sophie_cauldron_free(cauldron);
+
+    // null handles are ignored when released
+    sophie_result_free(NULL);
+
+    printf("ok\n");
+    return 0;
+}
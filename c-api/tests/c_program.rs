@@ -0,0 +1,35 @@
+//! Builds the C test program in `tests/c` against the library and runs it.
+
+#![cfg(target_os = "linux")]
+
+use std::{path::PathBuf, process::Command};
+
+#[test]
+fn c_program() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // cargo builds the library next to the test executable in `target/<profile>/deps`
+    let exe = std::env::current_exe().unwrap();
+    let lib_dir = exe.parent().unwrap();
+    let program = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("test_solver");
+
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg("-std=c99")
+        .args(["-Wall", "-Wextra", "-Werror"])
+        .arg(manifest_dir.join("tests/c/test_solver.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg("-lsophie_solver")
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("run the C compiler");
+    assert!(status.success(), "the C test program did not compile");
+
+    let output = Command::new(&program).output().unwrap();
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "the C test program failed");
+}
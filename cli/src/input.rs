@@ -0,0 +1,106 @@
+//! Parsing of the problem that is given on the command line.
+
+use std::{path::Path, str::FromStr};
+
+use atelier_sophie_data::{problem::MaterialChoice, ItemTag, MatColor};
+use synth_solver::Color;
+
+/// Replace every argument like `@path` with the arguments in that file.
+///
+/// The file has whitespace-separated arguments, and lines starting with `#` are ignored. This
+/// allows a problem to be kept in a file and reused, eg. `sophie-solver solve @uni_bag.args`.
+pub fn expand_args_files(args: impl IntoIterator<Item = String>) -> Result<Vec<String>, String> {
+    let mut expanded = vec![];
+    for arg in args {
+        let Some(path) = arg.strip_prefix('@') else {
+            expanded.push(arg);
+            continue;
+        };
+
+        let contents = std::fs::read_to_string(Path::new(path))
+            .map_err(|e| format!("Failed to read arguments file {path}: {e}"))?;
+        expanded.extend(
+            (contents.lines())
+                .filter(|line| !line.trim_start().starts_with('#'))
+                .flat_map(str::split_whitespace)
+                .map(str::to_string),
+        );
+    }
+    Ok(expanded)
+}
+
+/// A material group, written as materials separated by commas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterialGroupArg(pub Vec<MaterialChoice>);
+
+impl FromStr for MaterialGroupArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|material| parse_material(material.trim()))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Parse a material written as `ITEM_TAG:EFFECT_VALUE:SHAPE_SIZE[:COLOR]`.
+fn parse_material(s: &str) -> Result<MaterialChoice, String> {
+    let parts = s.split(':').collect::<Vec<_>>();
+    let [item_tag, effect_value, shape_size, rest @ ..] = &parts[..] else {
+        return Err(format!(
+            "Invalid material {s:?}, expected ITEM_TAG:EFFECT_VALUE:SHAPE_SIZE[:COLOR]"
+        ));
+    };
+
+    let item_tag = item_tag.parse::<ItemTag>().map_err(|e| e.to_string())?;
+    let effect_value = effect_value
+        .parse()
+        .map_err(|_| format!("Invalid effect value {effect_value:?} in material {s:?}"))?;
+    let shape_size = shape_size
+        .parse()
+        .map_err(|_| format!("Invalid shape size {shape_size:?} in material {s:?}"))?;
+
+    let choice = MaterialChoice::new(item_tag, effect_value, shape_size);
+    match rest {
+        [] => Ok(choice),
+        [color] => Ok(choice.with_color_override(parse_color(color)?)),
+        _ => Err(format!("Too many parts in material {s:?}")),
+    }
+}
+
+/// Parse a color, either as a name like `red` or a tag like `ITEM_MAT_COLOR_RED`.
+pub fn parse_color(s: &str) -> Result<Color, String> {
+    MatColor::ALL
+        .iter()
+        .find(|color| color.as_str() == s || format!("{color:?}").eq_ignore_ascii_case(s))
+        .map(|&color| color.into())
+        .ok_or_else(|| format!("Unknown color {s:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_material_groups() {
+        let item_tag = ItemTag::MixUniBag;
+        let group = format!("{item_tag}:50:5, {item_tag}:30:9:red");
+        assert_eq!(
+            group.parse(),
+            Ok(MaterialGroupArg(vec![
+                MaterialChoice::new(item_tag, 50, 5),
+                MaterialChoice::new(item_tag, 30, 9).with_color_override(Color::Red),
+            ]))
+        );
+
+        assert!(format!("{item_tag}:50")
+            .parse::<MaterialGroupArg>()
+            .is_err());
+        assert!(format!("{item_tag}:50:5:purple")
+            .parse::<MaterialGroupArg>()
+            .is_err());
+        assert!("ITEM_NOT_AN_ITEM:50:5".parse::<MaterialGroupArg>().is_err());
+        assert_eq!(parse_color("ITEM_MAT_COLOR_BLUE"), Ok(Color::Blue));
+    }
+}
@@ -0,0 +1,403 @@
+#![warn(
+    clippy::cloned_instead_of_copied,
+    clippy::explicit_iter_loop,
+    clippy::return_self_not_must_use,
+    clippy::trivially_copy_pass_by_ref,
+    clippy::uninlined_format_args,
+    clippy::use_self
+)]
+#![allow(clippy::wildcard_imports)]
+
+mod input;
+mod output;
+
+use std::{
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use atelier_sophie_data::{
+    problem::{SynthProblem, DEFAULT_KETTLE},
+    GameData, ItemTag,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use synth_solver::{
+    batch::Batch,
+    output_file::OutputFile,
+    problem_file::ProblemFile,
+    solver::{SolverProblem, SolverSettings},
+    CoverageTieRule, OverlapLimits, QualityModel,
+};
+
+use crate::input::MaterialGroupArg;
+
+/// Finds the best ways to place materials in a synthesis in Atelier Sophie.
+///
+/// Arguments can be read from a file by passing `@path`. Game data is loaded from the directory in
+/// the `ATELIER_SOPHIE_DATA_DIR` environment variable if it is set.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Find the Pareto front of routes for a synthesis and print them.
+    Solve {
+        #[command(flatten)]
+        problem: ProblemArgs,
+        /// Print the routes as JSON instead of text, see the `output_file` module of synth-solver
+        /// for the schema.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Replay a route written in route notation, eg. `1.0@c3R90; 0.1@a1`, and print its goals.
+    Replay {
+        #[command(flatten)]
+        problem: ProblemArgs,
+        /// The moves of the route, separated by `;`.
+        #[arg(long)]
+        route: String,
+    },
+    /// Solve a directory of problem files, or a JSONL file with a problem on each line.
+    ///
+    /// A line with the result of each problem is appended to the output file. Problems that
+    /// already have a result there are skipped, so an interrupted batch can be continued by
+    /// running it again.
+    Batch {
+        /// A directory of TOML or JSON problem files, or a JSONL file.
+        input: PathBuf,
+        /// The JSONL file to append the results to.
+        #[arg(long)]
+        output: PathBuf,
+        /// The amount of problems to solve at the same time.
+        #[arg(long, default_value_t = 1)]
+        threads: usize,
+    },
+    /// Write the problem to a problem file without solving it.
+    Save {
+        #[command(flatten)]
+        problem: ProblemArgs,
+        /// The file to write. Files ending in `.json` are written as JSON, others as TOML.
+        output: PathBuf,
+    },
+}
+
+#[derive(Args)]
+struct ProblemArgs {
+    /// Read the problem from a TOML or JSON problem file instead of the other arguments.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "item",
+            "kettle",
+            "size",
+            "bonus_level",
+            "groups",
+            "max_overlaps",
+            "max_materials_per_overlap",
+            "optimize_quality",
+            "coverage_tie_rule",
+        ]
+    )]
+    problem: Option<PathBuf>,
+    /// The tag of the item to craft, eg. ITEM_MIX_UNI_BAG.
+    #[arg(long, required_unless_present = "problem")]
+    item: Option<ItemTag>,
+    /// The tag of the kettle to use.
+    #[arg(long, default_value_t = DEFAULT_KETTLE)]
+    kettle: ItemTag,
+    /// The size of the cauldron.
+    #[arg(long, default_value_t = 4)]
+    size: usize,
+    /// The bonus level of the board, from 0 to 2.
+    #[arg(long, default_value_t = 0)]
+    bonus_level: usize,
+    /// The materials for an ingredient, in the order of the recipe. Each material is written as
+    /// ITEM_TAG:EFFECT_VALUE:SHAPE_SIZE[:COLOR], separated by commas.
+    #[arg(long = "group", value_name = "MATERIALS")]
+    groups: Vec<MaterialGroupArg>,
+    /// The max amount of placements that may overlap other materials.
+    #[arg(long, default_value_t = 0)]
+    max_overlaps: usize,
+    /// The max amount of materials that a single placement may overlap.
+    #[arg(long, default_value_t = 1)]
+    max_materials_per_overlap: usize,
+    /// Optimize the quality of the item alongside the goals.
+    #[arg(long)]
+    optimize_quality: bool,
+    /// Which colors receive the coverage bonus when they are tied for the highest coverage.
+    #[arg(long, value_enum, default_value_t = TieRuleArg::AllTied)]
+    coverage_tie_rule: TieRuleArg,
+}
+
+/// The names of the [`CoverageTieRule`]s on the command line.
+#[derive(Clone, Copy, ValueEnum)]
+enum TieRuleArg {
+    AllTied,
+    None,
+    FirstByColorIndex,
+    CauldronColor,
+}
+
+impl From<TieRuleArg> for CoverageTieRule {
+    fn from(tie_rule: TieRuleArg) -> Self {
+        match tie_rule {
+            TieRuleArg::AllTied => Self::AllTied,
+            TieRuleArg::None => Self::None,
+            TieRuleArg::FirstByColorIndex => Self::FirstByColorIndex,
+            TieRuleArg::CauldronColor => Self::CauldronColor,
+        }
+    }
+}
+
+impl ProblemArgs {
+    /// Read the problem file, or build the problem from the arguments.
+    fn load(&self) -> Result<ProblemFile, String> {
+        if let Some(path) = &self.problem {
+            return ProblemFile::read(path).map_err(|e| format!("{}: {e}", path.display()));
+        }
+
+        let problem = self.build()?;
+        Ok(ProblemFile::new(&problem.to_solver_problem())
+            .with_tags(problem.item_tag.as_str(), problem.kettle_tag.as_str()))
+    }
+
+    fn build(&self) -> Result<SynthProblem, String> {
+        let item = self.item.ok_or("No item to craft was given")?;
+        let settings = SolverSettings {
+            overlap_limits: OverlapLimits {
+                max_overlaps: self.max_overlaps,
+                max_materials_per_overlap: self.max_materials_per_overlap,
+            },
+            quality_model: self.optimize_quality.then(QualityModel::default),
+            coverage_tie_rule: self.coverage_tie_rule.into(),
+            ..Default::default()
+        };
+
+        self.groups
+            .iter()
+            .fold(
+                SynthProblem::builder(item)
+                    .kettle(self.kettle)
+                    .size(self.size)
+                    .bonus_level(self.bonus_level)
+                    .settings(settings),
+                |builder, group| builder.material_group(group.0.clone()),
+            )
+            .build()
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match input::expand_args_files(std::env::args()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let cli = Cli::parse_from(args);
+
+    if let Some(result) = GameData::load_from_env() {
+        match result {
+            Ok(data) => GameData::set_current(data.leak()),
+            Err(e) => {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match cli.command {
+        Command::Solve { problem, json } => solve(&problem, json),
+        Command::Replay { problem, route } => replay(&problem, &route),
+        Command::Batch {
+            input,
+            output,
+            threads,
+        } => batch(&input, &output, threads),
+        Command::Save { problem, output } => save(&problem, &output),
+    }
+}
+
+fn solve(args: &ProblemArgs, json: bool) -> ExitCode {
+    let (problem, item_tag) = match args.load().and_then(|file| to_problem(&file)) {
+        Ok(problem) => problem,
+        Err(e) => {
+            eprintln!("Invalid problem: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let routes = match problem.solve(Some(progress_reporter())) {
+        Ok(routes) => routes,
+        Err(e) => {
+            eprintln!("Invalid problem: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!();
+
+    if json {
+        println!("{}", OutputFile::new(&routes).to_json());
+        return ExitCode::SUCCESS;
+    }
+
+    println!("Found {} routes", routes.len());
+    for (i, route) in routes.iter().enumerate() {
+        println!();
+        println!(
+            "Route {}: {}",
+            i + 1,
+            output::format_route(&problem, item_tag, route)
+        );
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn replay(args: &ProblemArgs, route: &str) -> ExitCode {
+    let (problem, item_tag) = match args.load().and_then(|file| to_problem(&file)) {
+        Ok(problem) => problem,
+        Err(e) => {
+            eprintln!("Invalid problem: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let route = synth_solver::solver::parse_route(route, problem.cauldron.size)
+        .map_err(|e| e.to_string())
+        .and_then(|moves| problem.score_route(&moves).map_err(|e| e.to_string()));
+    match route {
+        Ok(route) => {
+            println!("{}", output::format_route(&problem, item_tag, &route));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Invalid route: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn batch(input: &Path, output: &Path, threads: usize) -> ExitCode {
+    let batch = match Batch::read(input) {
+        Ok(batch) => batch.threads(threads),
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let total = batch.problems().len();
+    let mut finished = 0;
+    let summary = batch.solve(output, |result| {
+        finished += 1;
+        match (&result.output, &result.error) {
+            (Some(output), _) => eprintln!(
+                "[{finished}] {}: {} routes in {:.2}s",
+                result.id,
+                output.routes.len(),
+                result.duration_secs
+            ),
+            (None, Some(error)) => eprintln!("[{finished}] {}: {error}", result.id),
+            (None, None) => {}
+        }
+    });
+
+    match summary {
+        Ok(summary) => {
+            eprintln!(
+                "Solved {} of {total} problems in {:.2}s, {} failed, {} already solved",
+                summary.solved,
+                summary.duration.as_secs_f64(),
+                summary.failed,
+                summary.skipped
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn save(args: &ProblemArgs, output: &PathBuf) -> ExitCode {
+    let result = args.load().and_then(|file| {
+        // check the file before writing it, so only valid problems are saved
+        to_problem(&file)?;
+        file.write(output).map_err(|e| e.to_string())
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Failed to save problem: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Convert a problem file to a problem, along with the item that it crafts if it names one.
+fn to_problem(file: &ProblemFile) -> Result<(SolverProblem, Option<ItemTag>), String> {
+    let problem = file.to_problem().map_err(|e| e.to_string())?;
+    let item_tag = (file.item.as_deref())
+        .map(str::parse::<ItemTag>)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    Ok((problem, item_tag))
+}
+
+/// Print the progress to stderr whenever it changes by at least a tenth of a percent.
+fn progress_reporter<T>() -> synth_solver::solver::ProgressReporter<T> {
+    let last_permille = AtomicU32::new(u32::MAX);
+    Box::new(move |progress, _| {
+        let permille = (progress * 1000.) as u32;
+        if last_permille.swap(permille, Ordering::Relaxed) != permille {
+            eprint!("\rProgress: {:.1}%", progress * 100.);
+        }
+        ControlFlow::Continue(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn problem_file_conflicts_with_problem_args() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(
+                ["sophie-solver", "solve", "--problem", "uni_bag.toml"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+        assert!(parse(&[]).is_ok());
+        for arg in [
+            "--item=ITEM_MIX_UNI_BAG",
+            "--kettle=ITEM_MIX_TIMEWORN_ALCHEMY_KETTLE",
+            "--size=5",
+            "--bonus-level=1",
+            "--max-overlaps=1",
+            "--max-materials-per-overlap=2",
+            "--optimize-quality",
+            "--coverage-tie-rule=none",
+        ] {
+            assert_eq!(
+                parse(&[arg]).err().map(|e| e.kind()),
+                Some(ErrorKind::ArgumentConflict),
+                "{arg} should conflict with --problem"
+            );
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Printing of the routes that were found.
+
+use std::fmt::Write as _;
+
+use atelier_sophie_data::ItemTag;
+use synth_solver::{
+    solver::{self, Move, SolverProblem, SolverRoute},
+    Cauldron,
+};
+
+/// Describe a route with its goals, scores, route notation, move list and the board after all
+/// moves.
+///
+/// The achieved effects are named if the item that is crafted is known, otherwise only their tiers
+/// are shown.
+pub fn format_route(
+    problem: &SolverProblem,
+    item_tag: Option<ItemTag>,
+    route: &SolverRoute,
+) -> String {
+    let mut cauldron = problem.cauldron.clone();
+    let materials = &problem.materials;
+    let mut out = String::new();
+
+    let rules = problem.settings.rules();
+    let scores = match cauldron.place_all_with_rules(
+        &rules,
+        materials,
+        &route.moves,
+        problem.settings.overlap_limits,
+    ) {
+        Ok(scores) => cauldron.calculate_final_score_with_rules(&rules, materials, &scores),
+        Err(e) => return format!("Failed to replay route: {e}\n"),
+    };
+
+    let achieved_goals = &route.goal_result.achieved_goals;
+    let goals = match item_tag {
+        Some(item_tag) => atelier_sophie_data::achieved_effects(item_tag, achieved_goals)
+            .iter()
+            .enumerate()
+            .map(|(goal_index, effect)| match effect {
+                Some(effect) => format!(
+                    "goal {}: {} (tier {})",
+                    goal_index + 1,
+                    effect.effect.name,
+                    effect.tier
+                ),
+                None => format!("goal {}: -", goal_index + 1),
+            })
+            .collect::<Vec<_>>(),
+        None => (achieved_goals.iter())
+            .enumerate()
+            .map(|(goal_index, tier)| match tier {
+                0 => format!("goal {}: -", goal_index + 1),
+                tier => format!("goal {}: tier {tier}", goal_index + 1),
+            })
+            .collect(),
+    }
+    .join(", ");
+
+    writeln!(out, "{goals}").unwrap();
+    write!(out, "scores: {:?}, overlaps: {}", scores, route.overlaps).unwrap();
+    if let Some(quality) = route.goal_result.quality {
+        write!(out, ", quality: {quality}").unwrap();
+    }
+    writeln!(out).unwrap();
+
+    writeln!(
+        out,
+        "route: {}",
+        solver::format_route(&route.moves, cauldron.size)
+    )
+    .unwrap();
+    writeln!(out, "moves:").unwrap();
+    out.push_str(&format_moves(&cauldron, &route.moves));
+    writeln!(out, "board:").unwrap();
+    for line in cauldron.to_string().lines() {
+        writeln!(out, "  {line}").unwrap();
+    }
+    out
+}
+
+fn format_moves(cauldron: &Cauldron, moves: &[Move]) -> String {
+    let mut out = String::new();
+    for move_ in moves {
+        let (x, y) = cauldron.get_position(move_.placement.index);
+        let (group, index) = move_.material_index;
+        write!(out, "  - material {group}-{index} at {x},{y}").unwrap();
+        if let Some(transformation) = move_.placement.transformation {
+            write!(out, " with {transformation:?}").unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use synth_solver::{output_file::OutputFile, utils::test_data::problems};
+
+    use super::*;
+
+    #[test]
+    fn format_uni_bag_route() {
+        let problem = problems::uni_bag();
+        let moves = solver::parse_route("0.0@a1; 0.1@b1; 1.0@b4; 2.0@c2", 5).unwrap();
+        let route = problem.score_route(&moves).unwrap();
+
+        let text = format_route(&problem, None, &route);
+        assert_eq!(
+            text,
+            "\
+goal 1: tier 1, goal 2: tier 1, goal 3: tier 1
+scores: [57, 34, 30], overlaps: 0
+route: 0.0@a1; 0.1@b1; 1.0@b4; 2.0@c2
+moves:
+  - material 0-0 at 0,0
+  - material 0-1 at 1,0
+  - material 1-0 at 1,3
+  - material 2-0 at 2,1
+board:
+  color: white
+  bonus_scores: flat 3 5 7
+  B0:0.0 G0:0.1 Y2     Y1     W0
+  W0:0.0 Y0:0.1 Y0:2.0 Y1     Y1
+  R0:0.0 Y0:0.1 R0:2.0 R1     Y0
+  R3     Y0:1.0 R0:2.0 R2     Y1
+  W1     Y0:1.0 Y0:1.0 Y2     Y0
+"
+        );
+
+        // the routes that are printed in JSON mode replay to the same route
+        let json = OutputFile::new(&vec![route]).to_json();
+        let routes = OutputFile::from_json(&json).unwrap().routes;
+        assert_eq!(routes.len(), 1);
+        assert_eq!(format_route(&problem, None, &routes[0]), text);
+    }
+
+    #[test]
+    fn format_failed_replay() {
+        let problem = problems::uni_bag();
+        let moves = solver::parse_route("0.0@a1; 0.1@a1", 5).unwrap();
+        let route = SolverRoute {
+            moves: moves.into_iter().collect(),
+            ..problem.score_route(&[]).unwrap()
+        };
+        assert!(format_route(&problem, None, &route).starts_with("Failed to replay route: "));
+    }
+}
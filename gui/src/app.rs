@@ -1,137 +1,209 @@
-use std::sync::{
-    atomic::{AtomicBool, AtomicU32},
-    Arc, RwLock,
-};
+use std::sync::{Arc, RwLock};
 
+use atelier_sophie_data::{problem::SynthProblem, AchievedEffect, ItemTag};
 use egui::RichText;
 
 use synth_solver::{
-    solver::{GoalResult, Move, SolverResult},
+    solver::{Move, SolverProblem, SolverResult, SolverRoute},
     Cauldron, Material,
 };
 
 use crate::{
+    background::BackgroundSolve,
     sections::CauldronInputSection,
-    util::{create_goals, create_materials, create_solver_settings, synth_color_to_egui_color},
+    util::{create_problem, synth_color_to_egui_color},
 };
 use crate::{sections::*, util::create_synth_cauldron};
 
-struct AtomicF32(AtomicU32);
-
-impl AtomicF32 {
-    fn new(val: f32) -> Self {
-        Self(AtomicU32::new(val.to_bits()))
-    }
-
-    fn get(&self) -> f32 {
-        f32::from_bits(self.0.load(std::sync::atomic::Ordering::Relaxed))
-    }
-
-    fn set(&self, val: f32) {
-        self.0
-            .store(val.to_bits(), std::sync::atomic::Ordering::Relaxed);
-    }
-}
-
-struct PendingSearch {
-    results_receiver: oneshot::Receiver<SolverResult>,
-    cancelled: Arc<AtomicBool>,
-    current_progress: Arc<AtomicF32>,
-}
-
 pub struct App {
     // inputs
+    data_input: GameDataInputSection,
     cauldron_input: CauldronInputSection,
     item_input: TargetItemInputSection,
     materials_input: MaterialsInputSection,
+    settings_input: SolverSettingsInputSection,
+    problem_file_input: ProblemFileInputSection,
 
     results: Arc<RwLock<Option<SolverResult>>>,
-    pending_search: Option<PendingSearch>,
+    /// The problem that the results are for, along with the item that it crafts if that is known.
+    /// The results are shown with this, since the inputs may have changed since.
+    solved_problem: Option<(SolverProblem, Option<ItemTag>)>,
+    pending_search: Option<BackgroundSolve>,
+    /// The error that stopped the last search from starting or finishing.
+    solver_error: Option<String>,
+    /// A route in route notation that is pasted to be added to the results.
+    route_input: String,
+    route_input_error: Option<String>,
 }
 
 impl App {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         cc.egui_ctx.set_pixels_per_point(1.5);
         Self {
+            data_input: GameDataInputSection::from_env(),
             cauldron_input: CauldronInputSection::default(),
             item_input: TargetItemInputSection::default(),
             materials_input: MaterialsInputSection::default(),
+            settings_input: SolverSettingsInputSection::default(),
+            problem_file_input: ProblemFileInputSection::default(),
 
             results: Arc::new(RwLock::new(None)),
+            solved_problem: None,
             pending_search: None,
+            solver_error: None,
+            route_input: String::new(),
+            route_input_error: None,
         }
     }
 
+    fn create_problem(&self) -> Result<SynthProblem, String> {
+        create_problem(
+            &self.cauldron_input,
+            &self.item_input,
+            &self.materials_input,
+            &self.settings_input,
+        )
+    }
+
+    /// The problem to solve, which is the loaded problem file if there is one, along with the item
+    /// that it crafts if that is known. Fails if the problem is invalid.
+    fn solver_problem(&self) -> Result<(SolverProblem, Option<ItemTag>), String> {
+        let (problem, item_tag) = match &self.problem_file_input.loaded {
+            Some(loaded) => loaded.clone(),
+            None => self
+                .create_problem()
+                .map(|problem| (problem.to_solver_problem(), Some(problem.item_tag)))?,
+        };
+        problem.validate().map_err(|e| e.to_string())?;
+        Ok((problem, item_tag))
+    }
+
     fn run_solver(&mut self, ctx: egui::Context) {
-        let allow_overlaps = true; // TODO: make this configurable
-
-        let cauldron = create_synth_cauldron(&self.cauldron_input, &self.item_input);
-        let materials = create_materials(&self.materials_input);
-        let goals = create_goals(&self.item_input);
-        let settings = create_solver_settings(&self.cauldron_input, allow_overlaps);
-
-        let (results_send, results_recv) = oneshot::channel();
-        let cancelled = Arc::new(AtomicBool::new(false));
-        let progress_val = Arc::new(AtomicF32::new(0.));
-        let results = self.results.clone();
-
-        self.pending_search = Some(PendingSearch {
-            results_receiver: results_recv,
-            cancelled: cancelled.clone(),
-            current_progress: progress_val.clone(),
-        });
+        // the solver can only be started when the inputs are valid, otherwise their error is shown
+        let Ok((problem, item_tag)) = self.solver_problem() else {
+            return;
+        };
+        self.solved_problem = Some((problem.clone(), item_tag));
 
-        std::thread::spawn(move || {
-            let found_routes = synth_solver::solver::find_optimal_routes(
-                &cauldron,
-                &materials,
-                &goals,
-                &settings,
-                Some(Box::new(move |progress, temp_results| {
-                    progress_val.set(progress);
-                    *results.write().unwrap() = Some(temp_results);
-
-                    if cancelled.load(std::sync::atomic::Ordering::Relaxed) {
-                        return std::ops::ControlFlow::Break(());
-                    }
+        match BackgroundSolve::start(problem, self.results.clone(), ctx) {
+            Ok(search) => {
+                self.pending_search = Some(search);
+                self.solver_error = None;
+            }
+            Err(e) => self.solver_error = Some(e),
+        }
+    }
 
-                    // for now, don't stop the search
-                    std::ops::ControlFlow::Continue(())
-                })),
-            );
-            println!("Found {} routes", found_routes.len());
-            results_send.send(found_routes).unwrap();
-            ctx.request_repaint();
+    /// Score the route in the route input and add it to the results.
+    fn add_route_from_input(&mut self) {
+        // routes are added to the results of the solved problem, if there are any
+        let results_available = self.results.read().unwrap().is_some();
+        let solved_problem = match &self.solved_problem {
+            Some(solved_problem) if results_available => Ok(solved_problem.clone()),
+            _ => self.solver_problem(),
+        };
+        let route = solved_problem.and_then(|(problem, item_tag)| {
+            let moves = synth_solver::solver::parse_route(&self.route_input, problem.cauldron.size)
+                .map_err(|e| e.to_string())?;
+            let route = problem.score_route(&moves).map_err(|e| e.to_string())?;
+            Ok((route, (problem, item_tag)))
         });
+
+        match route {
+            Ok((route, solved_problem)) => {
+                self.results
+                    .write()
+                    .unwrap()
+                    .get_or_insert_with(Vec::new)
+                    .push(route);
+                self.solved_problem = Some(solved_problem);
+                self.route_input.clear();
+                self.route_input_error = None;
+            }
+            Err(e) => self.route_input_error = Some(e),
+        }
     }
 
-    fn render_route(&self, ui: &mut egui::Ui, goal_result: &GoalResult, route: &[Move]) {
+    fn render_route(
+        &self,
+        ui: &mut egui::Ui,
+        (problem, item_tag): &(SolverProblem, Option<ItemTag>),
+        route: &SolverRoute,
+    ) {
         // calculate the playfield after these moves
-        let allow_overlaps = true; // TODO derive from UI?
+        let SolverProblem {
+            cauldron,
+            materials,
+            settings,
+            ..
+        } = problem;
+        let mut cauldron = cauldron.clone();
 
-        let mut cauldron = create_synth_cauldron(&self.cauldron_input, &self.item_input);
-        let materials = create_materials(&self.materials_input);
+        // replay with the rules the route was found with, not the ones selected now
+        let rules = settings.rules();
 
-        let res = cauldron.place_all(&materials, route, allow_overlaps);
+        let res =
+            cauldron.place_all_with_rules(&rules, materials, &route.moves, settings.overlap_limits);
 
         let scores = match res {
-            Ok(scores) => cauldron.calculate_final_score(&materials, &scores),
+            Ok(scores) => cauldron.calculate_final_score_with_rules(&rules, materials, &scores),
             Err(e) => {
                 ui.label(format!("Error: {e:?}"));
                 return;
             }
         };
 
+        let quality =
+            cauldron.calculate_final_quality(materials, settings.quality_model.unwrap_or_default());
+
+        let achieved_goals = &route.goal_result.achieved_goals;
+        let goals = match item_tag {
+            Some(item_tag) => atelier_sophie_data::achieved_effects(*item_tag, achieved_goals)
+                .iter()
+                .enumerate()
+                .map(|(goal_index, effect)| match effect {
+                    Some(effect) => format!(
+                        "Goal {}: {} (tier {}{})",
+                        goal_index + 1,
+                        effect.effect.localized_name(self.data_input.language),
+                        effect.tier,
+                        format_effect_strength(
+                            effect,
+                            scores.get(goal_index).copied().unwrap_or(0)
+                        ),
+                    ),
+                    None => format!("Goal {}: -", goal_index + 1),
+                })
+                .collect::<Vec<_>>(),
+            // the effects of a loaded problem without a known item can't be named
+            None => (achieved_goals.iter())
+                .enumerate()
+                .map(|(goal_index, tier)| match tier {
+                    0 => format!("Goal {}: -", goal_index + 1),
+                    tier => format!("Goal {}: tier {tier}", goal_index + 1),
+                })
+                .collect(),
+        }
+        .join(", ");
+
         egui::CollapsingHeader::new(format!(
-            "Goals: {:?}, score: {:?}",
-            goal_result.achieved_goals, scores
+            "{goals}, score: {:?}, quality: {quality}, overlaps: {}",
+            scores, route.overlaps
         ))
         .show(ui, |ui| {
+            let notation = synth_solver::solver::format_route(&route.moves, cauldron.size);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&notation).monospace());
+                if ui.button("Copy").clicked() {
+                    ui.output_mut(|output| output.copied_text = notation);
+                }
+            });
+
             // render move list
-            render_move_list(ui, &cauldron, route);
+            render_move_list(ui, &cauldron, &route.moves);
 
             // render playfield
-            render_playfield(ui, &cauldron, &materials);
+            render_playfield(ui, &cauldron, materials);
         });
     }
 }
@@ -139,10 +211,17 @@ impl App {
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         if let Some(pending_search) = &self.pending_search {
-            if let Ok(results) = pending_search.results_receiver.try_recv() {
-                *self.results.write().unwrap() = Some(results);
-                self.pending_search = None;
-            };
+            match pending_search.try_finish() {
+                Some(Ok(results)) => {
+                    *self.results.write().unwrap() = Some(results);
+                    self.pending_search = None;
+                }
+                Some(Err(e)) => {
+                    self.solver_error = Some(e);
+                    self.pending_search = None;
+                }
+                None => {}
+            }
         }
 
         let results_pending = self.pending_search.is_some();
@@ -165,18 +244,36 @@ impl eframe::App for App {
             ui.add_space(8.);
 
             ui.add_enabled_ui(can_edit_input, |ui| {
-                self.item_input.render(ui);
+                if self.data_input.render(ui) {
+                    // regenerate the material groups from the new recipes
+                    self.materials_input.target_item = None;
+                }
                 ui.add_space(16.);
-                self.materials_input
-                    .render(ui, self.item_input.target_item_tag);
+                self.item_input.render(ui, self.data_input.language);
+                ui.add_space(16.);
+                self.materials_input.render(
+                    ui,
+                    self.item_input.target_item_tag,
+                    self.data_input.language,
+                );
+                ui.add_space(16.);
+                self.settings_input.render(ui);
+                ui.add_space(16.);
+
+                let problem = self.create_problem();
+                self.problem_file_input.render(ui, problem.as_ref());
                 ui.add_space(16.);
             });
 
-            if let Err(err) = self
-                .item_input
-                .validate()
-                .and_then(|()| self.materials_input.validate())
-            {
+            let validation = if self.problem_file_input.loaded.is_some() {
+                self.solver_problem().map(|_| ())
+            } else {
+                self.item_input
+                    .validate()
+                    .and_then(|()| self.materials_input.validate())
+                    .and_then(|()| self.solver_problem().map(|_| ()))
+            };
+            if let Err(err) = validation {
                 ui.label("Input error");
                 ui.label(err);
             } else {
@@ -185,6 +282,9 @@ impl eframe::App for App {
                         self.run_solver(ctx.clone());
                     }
                 });
+                if let Some(error) = &self.solver_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
 
                 ui.add_enabled_ui(results_available, |ui| {
                     if ui.button("Clear results").clicked() {
@@ -194,12 +294,10 @@ impl eframe::App for App {
 
                 if let Some(pending_search) = &self.pending_search {
                     if ui.button("Cancel").clicked() {
-                        pending_search
-                            .cancelled
-                            .store(true, std::sync::atomic::Ordering::Relaxed);
+                        pending_search.cancel();
                     }
 
-                    let progress = pending_search.current_progress.get();
+                    let progress = pending_search.progress();
                     ui.add(
                         egui::widgets::ProgressBar::new(progress)
                             .animate(true)
@@ -217,25 +315,67 @@ impl eframe::App for App {
 
         egui::SidePanel::right("right panel").show(ctx, |ui| {
             ui.heading("Results");
-            if let Some(routes) = self.results.read().unwrap().as_ref() {
-                for (goal_result, route) in routes {
-                    self.render_route(ui, goal_result, route);
+            ui.add_enabled_ui(!results_pending, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Route:");
+                    ui.text_edit_singleline(&mut self.route_input)
+                        .on_hover_text("A route in route notation, eg. 1.0@c3R90; 0.1@a1");
+                    if ui.button("Add").clicked() {
+                        self.add_route_from_input();
+                    }
+                });
+            });
+            if let Some(error) = &self.route_input_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            if let (Some(routes), Some(solved_problem)) =
+                (self.results.read().unwrap().as_ref(), &self.solved_problem)
+            {
+                for route in routes {
+                    self.render_route(ui, solved_problem, route);
                 }
             }
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.add_enabled_ui(can_edit_input, |ui| {
-                self.cauldron_input.render(ui);
+                self.cauldron_input.render(ui, self.data_input.language);
                 ui.separator();
 
-                let cauldron = create_synth_cauldron(&self.cauldron_input, &self.item_input);
-                CauldronPreview { cauldron }.render(ui);
+                if let Some(cauldron) =
+                    create_synth_cauldron(&self.cauldron_input, &self.item_input)
+                {
+                    CauldronPreview { cauldron }.render(ui);
+                }
             });
         });
     }
 }
 
+/// Format the estimated strength of the actions of an effect, eg. `, AtkDamage Fire 15`.
+fn format_effect_strength(effect: &AchievedEffect, effect_value: u32) -> String {
+    effect
+        .effect
+        .actions
+        .iter()
+        .zip(effect.strength(effect_value))
+        .filter_map(|(action, strength)| {
+            let kind = action.act_tag?;
+            let params = strength
+                .iter()
+                .flatten()
+                .map(|value| format!("{value:.0}"))
+                .collect::<Vec<_>>()
+                .join("/");
+
+            Some(match action.attribute_tag {
+                Some(attribute) => format!(", {kind:?} {attribute:?} {params}"),
+                None => format!(", {kind:?} {params}"),
+            })
+        })
+        .collect()
+}
+
 fn render_move_list(ui: &mut egui::Ui, cauldron: &Cauldron, route: &[Move]) {
     for move_ in route {
         let (x, y) = cauldron.get_position(move_.placement.index);
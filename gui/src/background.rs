@@ -0,0 +1,275 @@
+//! Running the solver without blocking the UI: on a thread on native platforms, and in a web worker
+//! in the browser, where the page can't block on a thread.
+
+use std::sync::{Arc, RwLock};
+
+use synth_solver::solver::{SolverProblem, SolverResult};
+
+#[cfg(not(target_arch = "wasm32"))]
+use native::Solve;
+#[cfg(target_arch = "wasm32")]
+use web::Solve;
+
+/// A search for routes that runs in the background. The routes found so far are written to
+/// `results` while it runs.
+pub struct BackgroundSolve {
+    solve: Solve,
+}
+
+impl BackgroundSolve {
+    /// Start the search. Fails if it can't be started, eg. when the web worker can't be created.
+    pub fn start(
+        problem: SolverProblem,
+        results: Arc<RwLock<Option<SolverResult>>>,
+        ctx: egui::Context,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            solve: Solve::start(problem, results, ctx)?,
+        })
+    }
+
+    /// The progress from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.solve.progress()
+    }
+
+    /// Stop the search. It still finishes with the routes found so far.
+    pub fn cancel(&self) {
+        self.solve.cancel();
+    }
+
+    /// The final routes, once the search has finished, or the error that stopped it.
+    pub fn try_finish(&self) -> Option<Result<SolverResult, String>> {
+        self.solve.try_finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{
+        ops::ControlFlow,
+        sync::{
+            atomic::{AtomicBool, AtomicU32, Ordering},
+            Arc, RwLock,
+        },
+    };
+
+    use synth_solver::solver::{find_optimal_routes_with_rules, SolverProblem, SolverResult};
+
+    struct AtomicF32(AtomicU32);
+
+    impl AtomicF32 {
+        fn new(val: f32) -> Self {
+            Self(AtomicU32::new(val.to_bits()))
+        }
+
+        fn get(&self) -> f32 {
+            f32::from_bits(self.0.load(Ordering::Relaxed))
+        }
+
+        fn set(&self, val: f32) {
+            self.0.store(val.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    pub struct Solve {
+        results_receiver: oneshot::Receiver<SolverResult>,
+        cancelled: Arc<AtomicBool>,
+        current_progress: Arc<AtomicF32>,
+    }
+
+    impl Solve {
+        pub fn start(
+            problem: SolverProblem,
+            results: Arc<RwLock<Option<SolverResult>>>,
+            ctx: egui::Context,
+        ) -> Result<Self, String> {
+            let SolverProblem {
+                cauldron,
+                materials,
+                goals,
+                settings,
+            } = problem;
+
+            let (results_send, results_recv) = oneshot::channel();
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let progress_val = Arc::new(AtomicF32::new(0.));
+
+            let solve = Self {
+                results_receiver: results_recv,
+                cancelled: cancelled.clone(),
+                current_progress: progress_val.clone(),
+            };
+
+            std::thread::spawn(move || {
+                let found_routes = find_optimal_routes_with_rules(
+                    &settings.rules(),
+                    &cauldron,
+                    &materials,
+                    &goals,
+                    &settings,
+                    Some(Box::new(move |progress, temp_results| {
+                        progress_val.set(progress);
+                        *results.write().unwrap() = Some(temp_results);
+
+                        if cancelled.load(Ordering::Relaxed) {
+                            return ControlFlow::Break(());
+                        }
+
+                        // for now, don't stop the search
+                        ControlFlow::Continue(())
+                    })),
+                );
+                println!("Found {} routes", found_routes.len());
+                results_send.send(found_routes).unwrap();
+                ctx.request_repaint();
+            });
+
+            Ok(solve)
+        }
+
+        pub fn progress(&self) -> f32 {
+            self.current_progress.get()
+        }
+
+        pub fn cancel(&self) {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+
+        pub fn try_finish(&self) -> Option<Result<SolverResult, String>> {
+            self.results_receiver.try_recv().ok().map(Ok)
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{Arc, RwLock},
+    };
+
+    use synth_solver::{
+        problem_file::ProblemFile,
+        solver::{SolverProblem, SolverResult},
+    };
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+    use web_sys::{ErrorEvent, MessageEvent, Worker};
+
+    use crate::worker_protocol::{SolveRequest, SolveUpdate};
+
+    /// The script of the worker, which is built from `src/bin/worker.rs` next to the page.
+    const WORKER_SCRIPT: &str = "./worker.js";
+
+    #[derive(Default)]
+    struct State {
+        progress: f32,
+        /// The routes found so far.
+        routes: SolverResult,
+        finished: bool,
+        /// The error that stopped the worker, eg. when its script could not be loaded.
+        error: Option<String>,
+    }
+
+    pub struct Solve {
+        worker: Worker,
+        state: Rc<RefCell<State>>,
+        // kept alive for as long as the worker can send messages
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+        _on_error: Closure<dyn FnMut(ErrorEvent)>,
+    }
+
+    impl Solve {
+        pub fn start(
+            problem: SolverProblem,
+            results: Arc<RwLock<Option<SolverResult>>>,
+            ctx: egui::Context,
+        ) -> Result<Self, String> {
+            let worker = Worker::new(WORKER_SCRIPT)
+                .map_err(|e| format!("Failed to start the solver worker: {}", js_error(&e)))?;
+            let state = Rc::new(RefCell::new(State::default()));
+
+            let message_state = state.clone();
+            let message_ctx = ctx.clone();
+            let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                let Some(update) = event
+                    .data()
+                    .as_string()
+                    .and_then(|data| serde_json::from_str::<SolveUpdate>(&data).ok())
+                else {
+                    return;
+                };
+
+                let mut state = message_state.borrow_mut();
+                if state.finished {
+                    return;
+                }
+                state.progress = update.progress;
+                state.routes = update.output.routes;
+                state.finished = update.finished;
+                *results.write().unwrap() = Some(state.routes.clone());
+                message_ctx.request_repaint();
+            });
+            worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+            let error_state = state.clone();
+            let on_error = Closure::<dyn FnMut(ErrorEvent)>::new(move |event: ErrorEvent| {
+                let mut state = error_state.borrow_mut();
+                if state.finished {
+                    return;
+                }
+                state.finished = true;
+                state.error = Some(format!("The solver worker failed: {}", event.message()));
+                ctx.request_repaint();
+            });
+            worker.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+            let request = SolveRequest {
+                problem: ProblemFile::new(&problem),
+            };
+            let request = serde_json::to_string(&request).expect("requests can be serialized");
+            if let Err(e) = worker.post_message(&JsValue::from_str(&request)) {
+                worker.terminate();
+                return Err(format!(
+                    "Failed to send the problem to the solver worker: {}",
+                    js_error(&e)
+                ));
+            }
+
+            Ok(Self {
+                worker,
+                state,
+                _on_message: on_message,
+                _on_error: on_error,
+            })
+        }
+
+        pub fn progress(&self) -> f32 {
+            self.state.borrow().progress
+        }
+
+        /// The worker can't be interrupted while the solver runs, so it is stopped and the routes
+        /// from its last update are kept.
+        pub fn cancel(&self) {
+            self.worker.terminate();
+            self.state.borrow_mut().finished = true;
+        }
+
+        pub fn try_finish(&self) -> Option<Result<SolverResult, String>> {
+            let state = self.state.borrow();
+            state.finished.then(|| match &state.error {
+                Some(error) => Err(error.clone()),
+                None => Ok(state.routes.clone()),
+            })
+        }
+    }
+
+    /// The message of an error thrown by a browser API.
+    fn js_error(error: &JsValue) -> String {
+        (error.dyn_ref::<js_sys::Error>())
+            .map(|error| String::from(error.message()))
+            .or_else(|| error.as_string())
+            .unwrap_or_else(|| format!("{error:?}"))
+    }
+}
@@ -0,0 +1,88 @@
+//! The web worker that runs the solver when the GUI runs in a browser, so solving doesn't block the
+//! page. It receives a `SolveRequest` and answers with `SolveUpdate`s while it solves, see
+//! `worker_protocol.rs`.
+//!
+//! Trunk builds it next to the GUI as `worker.js`, see `index.html`. It does nothing on other
+//! platforms, where the GUI solves on a thread instead.
+
+#[cfg(target_arch = "wasm32")]
+#[path = "../worker_protocol.rs"]
+mod worker_protocol;
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use std::{cell::Cell, ops::ControlFlow};
+
+    use synth_solver::{
+        output_file::OutputFile,
+        solver::{find_optimal_routes_with_rules, SolverResult},
+    };
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+    use web_sys::{DedicatedWorkerGlobalScope, MessageEvent};
+    use worker_protocol::{SolveRequest, SolveUpdate};
+
+    /// The least change in progress that is sent to the GUI, since each update has all routes.
+    const PROGRESS_STEP: f32 = 0.01;
+
+    fn post(
+        scope: &DedicatedWorkerGlobalScope,
+        progress: f32,
+        routes: &SolverResult,
+        finished: bool,
+    ) {
+        let update = SolveUpdate {
+            progress,
+            output: OutputFile::new(routes),
+            finished,
+        };
+        let update = serde_json::to_string(&update).expect("updates can be serialized");
+        _ = scope.post_message(&JsValue::from_str(&update));
+    }
+
+    let scope = js_sys::global().unchecked_into::<DedicatedWorkerGlobalScope>();
+    let message_scope = scope.clone();
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+        let scope = &message_scope;
+        let Some(request) = event
+            .data()
+            .as_string()
+            .and_then(|data| serde_json::from_str::<SolveRequest>(&data).ok())
+        else {
+            return;
+        };
+        // the GUI only sends valid problems, so an invalid one just finishes without routes
+        let Ok(problem) = request.problem.to_problem() else {
+            post(scope, 1., &vec![], true);
+            return;
+        };
+        let rules = problem.settings.rules();
+
+        let reporter_scope = scope.clone();
+        let last_progress = Cell::new(0.);
+        let routes = find_optimal_routes_with_rules(
+            &rules,
+            &problem.cauldron,
+            &problem.materials,
+            &problem.goals,
+            &problem.settings,
+            Some(Box::new(move |progress, routes| {
+                if progress - last_progress.get() >= PROGRESS_STEP {
+                    last_progress.set(progress);
+                    post(&reporter_scope, progress, &routes, false);
+                }
+                ControlFlow::Continue(())
+            })),
+        );
+        post(scope, 1., &routes, true);
+    });
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // the worker handles messages until it is terminated
+    on_message.forget();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    eprintln!(
+        "The solver worker only runs in a browser, see the README for building the web version."
+    );
+}
@@ -9,10 +9,14 @@
 #![allow(clippy::wildcard_imports)]
 
 mod app;
+mod background;
 mod components;
 pub mod sections;
 mod util;
+#[cfg(target_arch = "wasm32")]
+mod worker_protocol;
 
+#[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -26,3 +30,19 @@ fn main() -> eframe::Result<()> {
         Box::new(|cc| Box::new(app::App::new(cc))),
     )
 }
+
+/// In the browser, the app is drawn on the canvas with the id `the_canvas_id` in `index.html`.
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    let web_options = eframe::WebOptions::default();
+    wasm_bindgen_futures::spawn_local(async {
+        eframe::WebRunner::new()
+            .start(
+                "the_canvas_id",
+                web_options,
+                Box::new(|cc| Box::new(app::App::new(cc))),
+            )
+            .await
+            .expect("failed to start eframe");
+    });
+}
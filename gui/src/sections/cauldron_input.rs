@@ -1,5 +1,7 @@
+use atelier_sophie_data::{problem::DEFAULT_KETTLE, ItemTag, Kettle, UseType};
+
 pub struct CauldronInputSection {
-    cauldron_item_tag: &'static str,
+    pub kettle_tag: ItemTag,
     pub size: usize,
     pub cauldron_effects: Vec<String>,
 }
@@ -7,7 +9,7 @@ pub struct CauldronInputSection {
 impl Default for CauldronInputSection {
     fn default() -> Self {
         Self {
-            cauldron_item_tag: "ITEM_MIX_TIMEWORN_ALCHEMY_KETTLE",
+            kettle_tag: DEFAULT_KETTLE,
             size: 4,
             cauldron_effects: vec![],
         }
@@ -15,54 +17,64 @@ impl Default for CauldronInputSection {
 }
 
 impl CauldronInputSection {
-    pub fn render(&mut self, ui: &mut egui::Ui) {
+    pub fn render(&mut self, ui: &mut egui::Ui, language: &str) {
         ui.heading("Cauldron");
 
         // Cauldron selection
-        ui.add_enabled_ui(false, |ui| {
-            egui::ComboBox::from_id_source("cauldron")
-                .selected_text(item_tag_to_name(self.cauldron_item_tag).expect("get cauldron name"))
-                .show_ui(ui, |ui| {
-                    for item in atelier_sophie_data::ITEMS.iter() {
-                        if item.use_type == "ITEM_USE_KETTLE" {
-                            ui.selectable_value(
-                                &mut self.cauldron_item_tag,
-                                item.tag.as_str(),
-                                item.name.as_str(),
-                            );
-                        }
-                    }
-                });
-        });
+        egui::ComboBox::from_id_source("cauldron")
+            .selected_text(
+                self.kettle_tag
+                    .item()
+                    .map_or(self.kettle_tag.as_str(), |item| {
+                        item.localized_name(language)
+                    }),
+            )
+            .show_ui(ui, |ui| {
+                for item in UseType::Kettle.items() {
+                    ui.add_enabled_ui(item.tag.kettle().is_some(), |ui| {
+                        ui.selectable_value(
+                            &mut self.kettle_tag,
+                            item.tag,
+                            item.localized_name(language),
+                        );
+                    });
+                }
+            });
+
+        let kettle = self.kettle();
+        if !kettle.sizes.contains(&self.size) {
+            self.size = kettle.sizes[0];
+        }
 
         // Size up/down
+        let size_index = kettle.sizes.iter().position(|&s| s == self.size).unwrap();
         ui.horizontal(|ui| {
             ui.label("Size:");
 
             if ui
-                .add_enabled_ui(self.size > 4, |ui| ui.button("<"))
+                .add_enabled_ui(size_index > 0, |ui| ui.button("<"))
                 .inner
                 .clicked()
             {
-                self.size -= 1;
+                self.size = kettle.sizes[size_index - 1];
             }
             ui.label(format!("{}", self.size));
             if ui
-                .add_enabled_ui(self.size < 6, |ui| ui.button(">"))
+                .add_enabled_ui(size_index + 1 < kettle.sizes.len(), |ui| ui.button(">"))
                 .inner
                 .clicked()
             {
-                self.size += 1;
+                self.size = kettle.sizes[size_index + 1];
             }
         });
 
+        ui.label(format!("Bonus scores: {:?}", kettle.bonus_scores));
+        ui.label(format!("Transformations: {:?}", kettle.transformations));
+
         // TODO: effects
     }
-}
 
-fn item_tag_to_name(tag: &str) -> Option<&'static str> {
-    atelier_sophie_data::ITEMS
-        .iter()
-        .find(|item| item.tag == tag)
-        .map(|item| item.name.as_str())
+    pub fn kettle(&self) -> &'static Kettle {
+        self.kettle_tag.kettle().expect("get kettle data")
+    }
 }
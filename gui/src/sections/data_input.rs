@@ -0,0 +1,96 @@
+use atelier_sophie_data::{GameData, LoadError, DEFAULT_LANGUAGE};
+
+/// Allows loading game data from a data directory instead of using the built-in data, and choosing
+/// the language that names are shown in.
+pub struct GameDataInputSection {
+    pub data_dir: String,
+    /// The result of the last attempt to load game data, shown below the input.
+    pub status: Option<Result<String, String>>,
+    pub language: &'static str,
+}
+
+impl Default for GameDataInputSection {
+    fn default() -> Self {
+        Self {
+            data_dir: String::new(),
+            status: None,
+            language: DEFAULT_LANGUAGE,
+        }
+    }
+}
+
+impl GameDataInputSection {
+    /// Load game data from the directory in the environment variable, if it is set.
+    pub fn from_env() -> Self {
+        let mut section = Self::default();
+        if let Some(dir) = std::env::var_os(atelier_sophie_data::DATA_DIR_ENV_VAR) {
+            section.data_dir = dir.to_string_lossy().into_owned();
+            section.load();
+        }
+        section
+    }
+
+    /// Render the section, returning `true` if different game data was made current.
+    pub fn render(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+
+        ui.heading("Game data");
+        ui.horizontal(|ui| {
+            ui.label("Data directory:");
+            ui.text_edit_singleline(&mut self.data_dir);
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled_ui(!self.data_dir.is_empty(), |ui| ui.button("Load"))
+                .inner
+                .clicked()
+            {
+                changed = self.load();
+            }
+            if ui.button("Use built-in").clicked() {
+                GameData::set_current(GameData::builtin());
+                self.status = Some(Ok("Using built-in game data".to_string()));
+                changed = true;
+            }
+        });
+
+        if changed && !GameData::current().languages().any(|l| l == self.language) {
+            self.language = DEFAULT_LANGUAGE;
+        }
+        egui::ComboBox::from_label("Language")
+            .selected_text(self.language)
+            .show_ui(ui, |ui| {
+                for language in GameData::current().languages() {
+                    ui.selectable_value(&mut self.language, language, language);
+                }
+            });
+
+        match &self.status {
+            Some(Ok(message)) => _ = ui.label(message),
+            Some(Err(message)) => _ = ui.colored_label(ui.visuals().error_fg_color, message),
+            None => {}
+        }
+
+        changed
+    }
+
+    fn load(&mut self) -> bool {
+        match GameData::load_from_dir(&self.data_dir) {
+            Ok(data) => {
+                let data = data.leak();
+                GameData::set_current(data);
+                self.status = Some(Ok(format!(
+                    "Loaded {} items from {}",
+                    data.items().len(),
+                    self.data_dir
+                )));
+                true
+            }
+            Err(LoadError(errors)) => {
+                let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+                self.status = Some(Err(errors.join("\n")));
+                false
+            }
+        }
+    }
+}
@@ -1,49 +1,57 @@
+use atelier_sophie_data::{GameData, ItemTag};
 use egui::ComboBox;
 
 pub struct TargetItemInputSection {
-    pub target_item_tag: &'static str,
+    pub target_item_tag: ItemTag,
+    /// Filters the items by their name in any language.
+    pub search: String,
 }
 
 impl Default for TargetItemInputSection {
     fn default() -> Self {
         Self {
-            target_item_tag: "ITEM_MIX_UNI_BAG",
+            target_item_tag: ItemTag::MixUniBag,
+            search: String::new(),
         }
     }
 }
 
 impl TargetItemInputSection {
-    pub fn render(&mut self, ui: &mut egui::Ui) {
+    pub fn render(&mut self, ui: &mut egui::Ui, language: &str) {
         ui.heading("Item to craft");
 
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search);
+        });
+
         ComboBox::from_id_source("item")
-            .selected_text(item_tag_to_name(self.target_item_tag).expect("get target item name"))
+            .selected_text(
+                self.target_item_tag
+                    .item()
+                    .map_or(self.target_item_tag.as_str(), |item| {
+                        item.localized_name(language)
+                    }),
+            )
             .show_ui(ui, |ui| {
-                for item in atelier_sophie_data::ITEMS
-                    .iter()
-                    .filter(|item| atelier_sophie_data::ITEM_BOARDS.contains_key(&item.tag))
+                for item in GameData::current()
+                    .search_items(&self.search)
+                    .filter(|item| item.tag.board().is_some())
                 {
                     ui.selectable_value(
                         &mut self.target_item_tag,
-                        item.tag.as_str(),
-                        item.name.as_str(),
+                        item.tag,
+                        item.localized_name(language),
                     );
                 }
             });
     }
 
     pub fn validate(&self) -> Result<(), String> {
-        if self.target_item_tag.is_empty() {
-            return Err("Target item is empty".into());
+        if self.target_item_tag.board().is_none() {
+            return Err(format!("Target item {} has no board", self.target_item_tag));
         }
 
         Ok(())
     }
 }
-
-fn item_tag_to_name(tag: &str) -> Option<&'static str> {
-    atelier_sophie_data::ITEMS
-        .iter()
-        .find(|item| item.tag == tag)
-        .map(|item| item.name.as_str())
-}
@@ -76,5 +76,6 @@ fn default_material() -> Material {
         color: Color::Red,
         effect_value: 10,
         shape: synth_solver::Shape::from_binary([0b110, 0b100, 0b000]),
+        quality: 50,
     }
 }
@@ -1,40 +1,35 @@
+use atelier_sophie_data::{GameData, IngredientTag, ItemTag};
 use egui::RichText;
 
 use crate::components::color_button_group;
 
 #[derive(Default)]
 pub struct MaterialsInputSection {
-    pub target_item: &'static str,
+    pub target_item: Option<ItemTag>,
     pub item_groups: Vec<IngredientGroup>,
 }
 
 impl MaterialsInputSection {
-    pub fn render(&mut self, ui: &mut egui::Ui, target_item: &'static str) {
-        if target_item.is_empty() {
-            eprintln!("target item in materials input is empty!");
-            return;
-        }
-        if self.target_item != target_item {
-            self.target_item = target_item;
+    pub fn render(&mut self, ui: &mut egui::Ui, target_item: ItemTag, language: &str) {
+        if self.target_item != Some(target_item) {
+            self.target_item = Some(target_item);
 
             // reset item groups
-            self.item_groups = atelier_sophie_data::INGREDIENTS
-                .get(target_item)
-                .expect("find target item")
+            self.item_groups = target_item
+                .ingredients()
+                .unwrap_or_default()
                 .iter()
                 .map(|ingredient| IngredientGroup {
-                    ingredient_tag: ingredient.ingredient.as_str(),
+                    ingredient_tag: ingredient.ingredient,
                     materials: vec![IndividualMaterialState::default(); ingredient.count],
                 })
                 .collect();
         }
-        debug_assert_ne!(self.target_item, "");
-
         ui.heading("Materials");
         for (group_idx, group) in self.item_groups.iter_mut().enumerate() {
             ui.push_id(group_idx, |ui| {
                 ui.group(|ui| {
-                    ui.label(RichText::new(group.ingredient_tag).monospace());
+                    ui.label(RichText::new(group.ingredient_tag.to_string()).monospace());
 
                     for (material_idx, material) in group.materials.iter_mut().enumerate() {
                         if material_idx > 0 {
@@ -43,28 +38,22 @@ impl MaterialsInputSection {
 
                         ui.push_id(material_idx, |ui| {
                             // item selection. pre-fill with item if it's not a category
-                            if !group.ingredient_tag.starts_with("ITEM_CAT") {
-                                material.item_tag = Some(group.ingredient_tag);
+                            if let IngredientTag::Item(item_tag) = group.ingredient_tag {
+                                material.item_tag = Some(item_tag);
                             }
                             egui::ComboBox::from_id_source("item")
                                 .selected_text(
                                     material
                                         .item_tag
-                                        .and_then(item_tag_to_name)
-                                        .unwrap_or("<None>"),
+                                        .and_then(ItemTag::item)
+                                        .map_or("<None>", |item| item.localized_name(language)),
                                 )
                                 .show_ui(ui, |ui| {
-                                    for item in atelier_sophie_data::ITEMS.iter().filter(|item| {
-                                        item.tag == group.ingredient_tag
-                                            || item
-                                                .categories
-                                                .iter()
-                                                .any(|c| c == group.ingredient_tag)
-                                    }) {
+                                    for item in group.ingredient_tag.candidates() {
                                         ui.selectable_value(
                                             &mut material.item_tag,
-                                            Some(item.tag.as_str()),
-                                            item.name.as_str(),
+                                            Some(item.tag),
+                                            item.localized_name(language),
                                         );
                                     }
                                 });
@@ -78,10 +67,16 @@ impl MaterialsInputSection {
                                 )
                             });
 
-                            if let Some(item) = atelier_sophie_data::ITEMS
-                                .iter()
-                                .find(|item| Some(item.tag.as_str()) == material.item_tag)
-                            {
+                            // quality
+                            ui.horizontal(|ui| {
+                                ui.label("Quality:");
+                                ui.add(
+                                    egui::DragValue::new(&mut material.quality)
+                                        .clamp_range(0..=999),
+                                )
+                            });
+
+                            if let Some(item) = material.item_tag.and_then(ItemTag::item) {
                                 // color override
                                 ui.horizontal(|ui| {
                                     ui.label("Color override:");
@@ -102,7 +97,7 @@ impl MaterialsInputSection {
                                 // shape
                                 render_input_shape_index(
                                     ui,
-                                    &item.shape_type,
+                                    item.shape_type,
                                     &mut material.shape_size,
                                 );
                             }
@@ -151,9 +146,10 @@ fn render_input_shape_index(ui: &mut egui::Ui, shape_tag: &str, size: &mut usize
         }
     });
 
-    let shape = atelier_sophie_data::SHAPES
-        .get(shape_tag)
-        .expect("find shape");
+    let Some(shape) = GameData::current().shape(shape_tag) else {
+        ui.label(format!("Unknown shape {shape_tag}"));
+        return;
+    };
     let shape = synth_solver::Shape::from_indices(shape.iter().take(*size).copied()).normalize();
     let shape = shape.to_matrix();
 
@@ -178,16 +174,17 @@ fn render_input_shape_index(ui: &mut egui::Ui, shape_tag: &str, size: &mut usize
 
 pub struct IngredientGroup {
     /// Either a category tag or an item tag
-    pub ingredient_tag: &'static str,
+    pub ingredient_tag: IngredientTag,
     pub materials: Vec<IndividualMaterialState>,
 }
 
 #[derive(Clone)]
 pub struct IndividualMaterialState {
-    pub item_tag: Option<&'static str>,
+    pub item_tag: Option<ItemTag>,
     pub shape_size: usize,
     pub color_override: Option<synth_solver::Color>,
     pub effect_value: u32,
+    pub quality: u32,
 }
 
 impl Default for IndividualMaterialState {
@@ -197,13 +194,7 @@ impl Default for IndividualMaterialState {
             shape_size: 3,
             color_override: None,
             effect_value: 10,
+            quality: 50,
         }
     }
 }
-
-fn item_tag_to_name(tag: &str) -> Option<&'static str> {
-    atelier_sophie_data::ITEMS
-        .iter()
-        .find(|item| item.tag == tag)
-        .map(|item| item.name.as_str())
-}
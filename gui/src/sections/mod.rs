@@ -1,9 +1,15 @@
 mod cauldron_input;
 mod cauldron_preview;
+mod data_input;
 mod item_input;
 mod materials_input;
+mod problem_file_input;
+mod settings_input;
 
 pub use cauldron_input::CauldronInputSection;
 pub use cauldron_preview::CauldronPreview;
+pub use data_input::GameDataInputSection;
 pub use item_input::TargetItemInputSection;
 pub use materials_input::MaterialsInputSection;
+pub use problem_file_input::ProblemFileInputSection;
+pub use settings_input::SolverSettingsInputSection;
@@ -0,0 +1,85 @@
+use atelier_sophie_data::{problem::SynthProblem, ItemTag};
+use synth_solver::{problem_file::ProblemFile, solver::SolverProblem};
+
+/// Allows saving the problem from the inputs to a problem file, and loading a problem file to solve
+/// instead of the inputs.
+#[derive(Default)]
+pub struct ProblemFileInputSection {
+    pub path: String,
+    /// The problem that was loaded, along with the item it crafts if the file names one.
+    pub loaded: Option<(SolverProblem, Option<ItemTag>)>,
+    /// The result of the last save or load, shown below the input.
+    pub status: Option<Result<String, String>>,
+}
+
+impl ProblemFileInputSection {
+    /// Render the section. `problem` is the problem created from the other inputs, which is saved.
+    pub fn render(&mut self, ui: &mut egui::Ui, problem: Result<&SynthProblem, &String>) {
+        ui.heading("Problem file");
+        ui.horizontal(|ui| {
+            ui.label("Path:");
+            ui.text_edit_singleline(&mut self.path);
+        });
+        ui.horizontal(|ui| {
+            let has_path = !self.path.is_empty();
+            if ui
+                .add_enabled_ui(has_path && problem.is_ok(), |ui| ui.button("Save"))
+                .inner
+                .clicked()
+            {
+                if let Ok(problem) = problem {
+                    self.save(problem);
+                }
+            }
+            if ui
+                .add_enabled_ui(has_path, |ui| ui.button("Load"))
+                .inner
+                .clicked()
+            {
+                self.load();
+            }
+            if ui
+                .add_enabled_ui(self.loaded.is_some(), |ui| ui.button("Clear"))
+                .inner
+                .clicked()
+            {
+                self.loaded = None;
+                self.status = None;
+            }
+        });
+
+        if self.loaded.is_some() {
+            ui.label("The loaded problem is solved instead of the inputs.");
+        }
+        match &self.status {
+            Some(Ok(message)) => _ = ui.label(message),
+            Some(Err(message)) => _ = ui.colored_label(ui.visuals().error_fg_color, message),
+            None => {}
+        }
+    }
+
+    fn save(&mut self, problem: &SynthProblem) {
+        let file = ProblemFile::new(&problem.to_solver_problem())
+            .with_tags(problem.item_tag.as_str(), problem.kettle_tag.as_str());
+        self.status = Some(match file.write(&self.path) {
+            Ok(()) => Ok(format!("Saved problem to {}", self.path)),
+            Err(e) => Err(e.to_string()),
+        });
+    }
+
+    fn load(&mut self) {
+        let result = ProblemFile::read(&self.path).and_then(|file| {
+            let problem = file.to_problem()?;
+            // an unknown item only means the effects can't be named
+            let item_tag = file.item.and_then(|tag| tag.parse().ok());
+            Ok((problem, item_tag))
+        });
+        match result {
+            Ok(loaded) => {
+                self.loaded = Some(loaded);
+                self.status = Some(Ok(format!("Loaded problem from {}", self.path)));
+            }
+            Err(e) => self.status = Some(Err(e.to_string())),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use synth_solver::{CoverageTieRule, OverlapLimits, QualityModel};
+
+pub struct SolverSettingsInputSection {
+    pub allow_overlaps: bool,
+    pub limit_overlaps: bool,
+    pub max_overlaps: usize,
+    pub max_materials_per_overlap: usize,
+    pub coverage_tie_rule: CoverageTieRule,
+    pub optimize_quality: bool,
+}
+
+impl Default for SolverSettingsInputSection {
+    fn default() -> Self {
+        Self {
+            allow_overlaps: true,
+            limit_overlaps: false,
+            max_overlaps: 1,
+            max_materials_per_overlap: 1,
+            coverage_tie_rule: CoverageTieRule::default(),
+            optimize_quality: false,
+        }
+    }
+}
+
+impl SolverSettingsInputSection {
+    pub fn render(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Solver settings");
+
+        ui.checkbox(&mut self.allow_overlaps, "Allow overlaps");
+        ui.add_enabled_ui(self.allow_overlaps, |ui| {
+            ui.checkbox(&mut self.limit_overlaps, "Limit overlaps");
+            ui.add_enabled_ui(self.limit_overlaps, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Max overlaps:");
+                    ui.add(egui::DragValue::new(&mut self.max_overlaps).clamp_range(1..=20));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Max materials per overlap:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.max_materials_per_overlap)
+                            .clamp_range(1..=9),
+                    );
+                });
+            });
+        });
+
+        egui::ComboBox::from_label("Coverage tie rule")
+            .selected_text(format!("{:?}", self.coverage_tie_rule))
+            .show_ui(ui, |ui| {
+                for tie_rule in [
+                    CoverageTieRule::AllTied,
+                    CoverageTieRule::None,
+                    CoverageTieRule::FirstByColorIndex,
+                    CoverageTieRule::CauldronColor,
+                ] {
+                    ui.selectable_value(
+                        &mut self.coverage_tie_rule,
+                        tie_rule,
+                        format!("{tie_rule:?}"),
+                    );
+                }
+            });
+
+        ui.checkbox(&mut self.optimize_quality, "Optimize quality");
+    }
+
+    pub fn overlap_limits(&self) -> OverlapLimits {
+        match (self.allow_overlaps, self.limit_overlaps) {
+            (false, _) => OverlapLimits::NONE,
+            (true, false) => OverlapLimits::UNLIMITED,
+            (true, true) => OverlapLimits {
+                max_overlaps: self.max_overlaps,
+                max_materials_per_overlap: self.max_materials_per_overlap,
+            },
+        }
+    }
+
+    pub fn quality_model(&self) -> Option<QualityModel> {
+        self.optimize_quality.then(QualityModel::default)
+    }
+}
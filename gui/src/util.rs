@@ -1,6 +1,8 @@
-use synth_solver::tinyvec::ArrayVec;
+use atelier_sophie_data::problem::{self, MaterialChoice, SynthProblem};
 
-use crate::sections::{CauldronInputSection, MaterialsInputSection, TargetItemInputSection};
+use crate::sections::{
+    CauldronInputSection, MaterialsInputSection, SolverSettingsInputSection, TargetItemInputSection,
+};
 
 pub fn synth_color_to_egui_color(color: synth_solver::Color) -> egui::Color32 {
     use synth_solver::Color;
@@ -17,149 +19,62 @@ pub fn synth_color_to_egui_color(color: synth_solver::Color) -> egui::Color32 {
 pub fn create_synth_cauldron(
     cauldron_input: &CauldronInputSection,
     item_input: &TargetItemInputSection,
-) -> synth_solver::Cauldron {
-    // TODO: calculate properties from cauldron input
+) -> Option<synth_solver::Cauldron> {
+    // TODO: calculate bonus level from cauldron input
     let bonus_level = 0;
-    let properties = synth_solver::CauldronProperties::default();
-    let bonus_scores = (3, 5, 7);
 
-    synth_solver::Cauldron {
-        size: cauldron_input.size,
-        tiles: create_tiles(item_input.target_item_tag, cauldron_input.size, bonus_level),
-        bonus_scores,
-        color: synth_solver::Color::from_mat_color_tag(
-            &atelier_sophie_data::ITEMS
-                .iter()
-                .find(|item| item.tag == item_input.target_item_tag)
-                .unwrap()
-                .color,
-        ),
-        properties,
-    }
+    problem::create_cauldron(
+        item_input.target_item_tag,
+        cauldron_input.kettle_tag,
+        cauldron_input.size,
+        bonus_level,
+    )
+    .ok()
 }
 
-pub fn create_materials(
+pub fn create_problem(
+    cauldron_input: &CauldronInputSection,
+    item_input: &TargetItemInputSection,
     materials_input: &MaterialsInputSection,
-) -> Vec<Vec<synth_solver::Material>> {
-    materials_input
-        .item_groups
-        .iter()
-        .map(|group| {
-            group
-                .materials
-                .iter()
-                .map(|material| {
-                    let item = atelier_sophie_data::ITEMS
-                        .iter()
-                        .find(|item| item.tag == material.item_tag.unwrap())
-                        .unwrap();
+    settings_input: &SolverSettingsInputSection,
+) -> Result<SynthProblem, String> {
+    let mut builder = SynthProblem::builder(item_input.target_item_tag)
+        .size(cauldron_input.size)
+        .kettle(cauldron_input.kettle_tag)
+        .settings(create_solver_settings(settings_input));
+
+    for group in &materials_input.item_groups {
+        let materials = group
+            .materials
+            .iter()
+            .map(|material| {
+                let item_tag = material.item_tag.ok_or_else(|| {
+                    format!("Material group {} has an empty item", group.ingredient_tag)
+                })?;
 
-                    synth_solver::Material {
-                        color: material.color_override.unwrap_or_else(|| {
-                            synth_solver::Color::from_mat_color_tag(&item.color)
-                        }),
-                        effect_value: material.effect_value,
-                        shape: create_shape(&item.shape_type, material.shape_size),
-                    }
+                Ok(MaterialChoice {
+                    item_tag,
+                    effect_value: material.effect_value,
+                    shape_size: material.shape_size,
+                    color_override: material.color_override,
+                    quality: material.quality,
                 })
-                .collect()
-        })
-        .collect()
-}
+            })
+            .collect::<Result<_, String>>()?;
+        builder = builder.material_group(materials);
+    }
 
-pub fn create_goals(item_input: &TargetItemInputSection) -> Vec<synth_solver::Goal> {
-    atelier_sophie_data::ITEM_EFFECT_THRESHOLDS
-        .get(item_input.target_item_tag)
-        .unwrap()
-        .iter()
-        .map(|thresholds_group| synth_solver::Goal {
-            effect_value_thresholds: thresholds_group
-                .iter()
-                .map(|threshold| threshold.threshold)
-                .collect(),
-        })
-        .collect()
+    builder.build().map_err(|e| e.to_string())
 }
 
+/// Create the solver settings. The transformations are taken from the kettle by the problem builder.
 pub fn create_solver_settings(
-    _cauldron_input: &CauldronInputSection,
-    allow_overlaps: bool,
+    settings_input: &SolverSettingsInputSection,
 ) -> synth_solver::solver::SolverSettings {
-    let transformations = synth_solver::TransformationType::Rotate; // TODO: derive from traits
-
     synth_solver::solver::SolverSettings {
-        transformations,
-        allow_overlaps,
+        overlap_limits: settings_input.overlap_limits(),
+        quality_model: settings_input.quality_model(),
+        coverage_tie_rule: settings_input.coverage_tie_rule,
+        ..Default::default()
     }
 }
-
-fn create_tiles(
-    item: &str,
-    size: usize,
-    bonus_level: usize,
-) -> ArrayVec<[Option<synth_solver::Tile>; 6 * 6]> {
-    let board = atelier_sophie_data::ITEM_BOARDS.get(item).unwrap();
-
-    // clippy lint for false positive, see https://github.com/rust-lang/rust-clippy/issues/11761
-    #[allow(clippy::iter_skip_zero)]
-    board
-        .colors
-        .iter()
-        .zip(board.bonus_levels[bonus_level].iter())
-        .enumerate()
-        .flat_map(|(y, (row_col, row_bonus))| match (size, y) {
-            // with size 4, take the middle 4x4 slots
-            (4, 1..=4) => Iterator::zip(
-                row_col.chars().skip(1).take(4),
-                row_bonus.chars().skip(1).take(4),
-            ),
-            // with size 5, take the 5x5 slots to the top left
-            (5, 0..=4) => Iterator::zip(
-                row_col.chars().skip(0).take(5),
-                row_bonus.chars().skip(0).take(5),
-            ),
-            // with size 6, take everything
-            (6, _) => Iterator::zip(
-                row_col.chars().skip(0).take(6),
-                row_bonus.chars().skip(0).take(6),
-            ),
-            // cases for size 4 or 5 where no items are taken
-            (4, 0 | 5) | (5, 5) => Iterator::zip(
-                row_col.chars().skip(0).take(0),
-                row_bonus.chars().skip(0).take(0),
-            ),
-            (..=3 | 6..=usize::MAX, _) => panic!("Invalid size {size} for item {item}"),
-            (_, _) => panic!("Invalid y {y} for item {item}"),
-        })
-        .map(|(color, bonus)| {
-            if color != ' ' {
-                Some(synth_solver::Tile {
-                    color: match color {
-                        'R' => synth_solver::Color::Red,
-                        'B' => synth_solver::Color::Blue,
-                        'G' => synth_solver::Color::Green,
-                        'Y' => synth_solver::Color::Yellow,
-                        'W' => synth_solver::Color::White,
-                        ' ' => unreachable!(),
-                        c => panic!("Invalid char {c}"),
-                    },
-                    level: match bonus {
-                        ' ' => 0,
-                        '1' => 1,
-                        '2' => 2,
-                        '3' => 3,
-                        c => panic!("Invalid char {c}"),
-                    },
-                    played_material_index: None,
-                })
-            } else {
-                None
-            }
-        })
-        .collect()
-}
-
-fn create_shape(shape_tag: &str, size: usize) -> synth_solver::Shape {
-    let shape = atelier_sophie_data::SHAPES.get(shape_tag).unwrap();
-    synth_solver::Shape::from_indices(shape.iter().take(size).copied())
-}
@@ -0,0 +1,21 @@
+//! The messages between the GUI and the solver worker in the browser, which are sent as JSON
+//! strings.
+
+use serde::{Deserialize, Serialize};
+use synth_solver::{output_file::OutputFile, problem_file::ProblemFile};
+
+/// Sent to the worker to start solving.
+#[derive(Serialize, Deserialize)]
+pub struct SolveRequest {
+    pub problem: ProblemFile,
+}
+
+/// Sent by the worker while it solves, and once more when it is done.
+#[derive(Serialize, Deserialize)]
+pub struct SolveUpdate {
+    /// The progress from 0 to 1.
+    pub progress: f32,
+    /// The routes found so far, or all routes once the worker is done.
+    pub output: OutputFile,
+    pub finished: bool,
+}
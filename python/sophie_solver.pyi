@@ -0,0 +1,89 @@
+"""Finds synthesis routes for Atelier Sophie."""
+
+from typing import Callable, List, Optional, Tuple
+
+# `((group, index), position, transformation)`, in the arguments of `Cauldron.place`
+Move = Tuple[Tuple[int, int], int, Optional[str]]
+
+class Shape:
+    """A shape in a 3x3 grid, eg. `Shape(["X..", "XX.", "..."])`."""
+
+    def __init__(self, rows: List[str]) -> None: ...
+    def rows(self) -> List[str]: ...
+
+class Material:
+    def __init__(self, color: str, effect_value: int, shape: Shape, quality: int = 0) -> None: ...
+    @property
+    def color(self) -> str: ...
+    @property
+    def effect_value(self) -> int: ...
+    @property
+    def shape(self) -> Shape: ...
+    @property
+    def quality(self) -> int: ...
+
+class Goal:
+    def __init__(self, thresholds: List[int]) -> None: ...
+    @property
+    def thresholds(self) -> List[int]: ...
+
+class Cauldron:
+    """A cauldron in the text format of cauldrons, with a line of tiles like `Y0 W1 --` per row."""
+
+    def __init__(self, text: str) -> None: ...
+    @property
+    def size(self) -> int: ...
+    @property
+    def color(self) -> str: ...
+    @property
+    def overlaps(self) -> int: ...
+    def tile(self, x: int, y: int) -> Optional[Tuple[str, int, Optional[Tuple[int, int]]]]: ...
+    def place(
+        self,
+        materials: List[List[Material]],
+        material_index: Tuple[int, int],
+        position: int,
+        transformation: Optional[str] = None,
+        max_overlaps: int = 0,
+        max_materials_per_overlap: int = 1,
+    ) -> None: ...
+    def calculate_final_score(self, materials: List[List[Material]]) -> List[int]: ...
+    def copy(self) -> "Cauldron": ...
+
+class Route:
+    @property
+    def achieved_goals(self) -> List[int]: ...
+    @property
+    def quality(self) -> Optional[int]: ...
+    @property
+    def overlaps(self) -> int: ...
+    @property
+    def moves(self) -> List[Move]: ...
+    def to_notation(self, size: int) -> str: ...
+
+class QualityModel:
+    def __init__(self, quality_bonus: int = 0, max_quality: int = 999) -> None: ...
+    @property
+    def quality_bonus(self) -> int: ...
+    @property
+    def max_quality(self) -> int: ...
+
+class CancelToken:
+    def __init__(self) -> None: ...
+    def cancel(self) -> None: ...
+    @property
+    def cancelled(self) -> bool: ...
+
+def find_optimal_routes(
+    cauldron: Cauldron,
+    materials: List[List[Material]],
+    goals: List[Goal],
+    *,
+    transformations: str = "none",
+    max_overlaps: int = 0,
+    max_materials_per_overlap: int = 1,
+    coverage_tie_rule: str = "all_tied",
+    quality_model: Optional[QualityModel] = None,
+    progress: Optional[Callable[[float, List[Route]], object]] = None,
+    cancel: Optional[CancelToken] = None,
+) -> List[Route]: ...
@@ -0,0 +1,166 @@
+//! The cauldron, which materials are placed in one by one.
+
+use pyo3::{
+    exceptions::{PyIndexError, PyValueError},
+    prelude::*,
+};
+use synth_solver::{errors::ParseCauldronError, Cauldron, ColorScoreSet, Placement, MAX_GOALS};
+
+use crate::{
+    materials::{to_material_groups, PyMaterial},
+    route::{overlap_limits, parse_transformation},
+};
+
+/// A tile as `(color, level, material)`, where `material` is the `(group, index)` of the material
+/// placed on it.
+type PyTile = (&'static str, u32, Option<(usize, usize)>);
+
+/// The playfield of a synthesis, created from the text format of cauldrons:
+///
+/// ```text
+/// color: white
+/// bonus_scores: flat 3 5 7
+/// B0 G0 Y0 Y0 W0
+/// W0 Y0 Y0 Y0 Y1
+/// ...
+/// ```
+///
+/// The scores of the materials that were placed are kept with the cauldron, so
+/// `calculate_final_score` can be called after placing them.
+#[pyclass(name = "Cauldron", module = "sophie_solver")]
+#[derive(Clone)]
+pub struct PyCauldron {
+    pub cauldron: Cauldron,
+    /// The scores of the placed materials for each material group.
+    scores: Vec<ColorScoreSet>,
+}
+
+#[pymethods]
+impl PyCauldron {
+    #[new]
+    fn new(text: &str) -> PyResult<Self> {
+        let cauldron = text
+            .parse()
+            .map_err(|e: ParseCauldronError| PyValueError::new_err(e.to_string()))?;
+        Ok(Self {
+            cauldron,
+            scores: vec![],
+        })
+    }
+
+    #[getter]
+    fn size(&self) -> usize {
+        self.cauldron.size
+    }
+
+    /// The name of the color of the liquid, eg. `white`.
+    #[getter]
+    fn color(&self) -> &'static str {
+        self.cauldron.color.name()
+    }
+
+    /// The amount of overlaps that happened so far.
+    #[getter]
+    fn overlaps(&self) -> usize {
+        self.cauldron.overlaps
+    }
+
+    /// The tile at a column and row as `(color, level, material)`, where `material` is the
+    /// `(group, index)` of the material placed on it, or `None` if the tile is a hole.
+    fn tile(&self, x: usize, y: usize) -> PyResult<Option<PyTile>> {
+        if x >= self.cauldron.size || y >= self.cauldron.size {
+            return Err(PyIndexError::new_err(format!(
+                "({x}, {y}) is outside of the {0}x{0} cauldron",
+                self.cauldron.size
+            )));
+        }
+        Ok(self
+            .cauldron
+            .get_tile((x, y))
+            .map(|tile| (tile.color.name(), tile.level, tile.played_material_index)))
+    }
+
+    /// Place a material at a position, which is the index of the top left tile of its shape
+    /// counted row by row.
+    #[pyo3(signature = (
+        materials,
+        material_index,
+        position,
+        transformation = None,
+        max_overlaps = 0,
+        max_materials_per_overlap = 1,
+    ))]
+    fn place(
+        &mut self,
+        materials: Vec<Vec<PyMaterial>>,
+        material_index: (usize, usize),
+        position: usize,
+        transformation: Option<&str>,
+        max_overlaps: usize,
+        max_materials_per_overlap: usize,
+    ) -> PyResult<()> {
+        let materials = to_material_groups(materials);
+        let (group, index) = material_index;
+        if materials.get(group).and_then(|g| g.get(index)).is_none() {
+            return Err(PyValueError::new_err(format!(
+                "there is no material {group}.{index}"
+            )));
+        }
+        self.check_groups(materials.len())?;
+
+        let placement = Placement::new(
+            position,
+            transformation.map(parse_transformation).transpose()?,
+        );
+        let overlap_limits = overlap_limits(max_overlaps, max_materials_per_overlap)?;
+        self.cauldron
+            .place(
+                &materials,
+                material_index,
+                placement,
+                overlap_limits,
+                &mut self.scores,
+            )
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// The final effect value for each material group, from the materials placed so far.
+    fn calculate_final_score(&mut self, materials: Vec<Vec<PyMaterial>>) -> PyResult<Vec<u32>> {
+        let materials = to_material_groups(materials);
+        self.check_groups(materials.len())?;
+        Ok(self
+            .cauldron
+            .calculate_final_score(&materials, &self.scores)
+            .to_vec())
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __str__(&self) -> String {
+        self.cauldron.to_string()
+    }
+}
+
+impl PyCauldron {
+    /// Check that the materials have as many groups as the ones that were placed before, and not
+    /// more than the solver supports.
+    fn check_groups(&mut self, groups: usize) -> PyResult<()> {
+        if groups > MAX_GOALS {
+            return Err(PyValueError::new_err(format!(
+                "there can be at most {MAX_GOALS} material groups, got {groups}"
+            )));
+        }
+        if self.scores.is_empty() {
+            self.scores = vec![ColorScoreSet::default(); groups];
+        }
+        if self.scores.len() != groups {
+            return Err(PyValueError::new_err(format!(
+                "expected {} material groups like in earlier placements, got {groups}",
+                self.scores.len()
+            )));
+        }
+        Ok(())
+    }
+}
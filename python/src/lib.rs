@@ -0,0 +1,302 @@
+//! Python bindings for the solver, built as the `sophie_solver` module with maturin. See
+//! `sophie_solver.pyi` for the Python API.
+
+#![warn(
+    clippy::cloned_instead_of_copied,
+    clippy::explicit_iter_loop,
+    clippy::return_self_not_must_use,
+    clippy::trivially_copy_pass_by_ref,
+    clippy::uninlined_format_args,
+    clippy::use_self
+)]
+#![allow(clippy::wildcard_imports)]
+
+mod cauldron;
+mod materials;
+mod route;
+
+use std::{
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use synth_solver::{
+    solver::{SolverProblem, SolverSettings},
+    QualityModel,
+};
+
+use crate::{
+    cauldron::PyCauldron,
+    materials::{to_material_groups, PyGoal, PyMaterial, PyShape},
+    route::{overlap_limits, parse_coverage_tie_rule, parse_transformation_type, PyRoute},
+};
+
+/// Stops a search that is running, eg. from a progress callback or another thread. The search
+/// still returns the routes it found so far.
+#[pyclass(name = "CancelToken", module = "sophie_solver", frozen)]
+#[derive(Default)]
+struct PyCancelToken(Arc<AtomicBool>);
+
+#[pymethods]
+impl PyCancelToken {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Predicts the quality of the item from the materials that are left in the cauldron, eg.
+/// `QualityModel(quality_bonus=10)`.
+#[pyclass(name = "QualityModel", module = "sophie_solver", frozen)]
+#[derive(Clone, Copy)]
+struct PyQualityModel(QualityModel);
+
+// pyo3 methods take `self` by reference
+#[allow(clippy::trivially_copy_pass_by_ref)]
+#[pymethods]
+impl PyQualityModel {
+    #[new]
+    #[pyo3(signature = (quality_bonus = 0, max_quality = 999))]
+    fn new(quality_bonus: u32, max_quality: u32) -> Self {
+        Self(QualityModel {
+            quality_bonus,
+            max_quality,
+        })
+    }
+
+    #[getter]
+    fn quality_bonus(&self) -> u32 {
+        self.0.quality_bonus
+    }
+
+    #[getter]
+    fn max_quality(&self) -> u32 {
+        self.0.max_quality
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "QualityModel(quality_bonus={}, max_quality={})",
+            self.0.quality_bonus, self.0.max_quality
+        )
+    }
+}
+
+/// Find the optimal routes for placing the materials in the cauldron.
+///
+/// With a `quality_model`, the quality of the item is optimized alongside the goals and each route
+/// has a `quality`.
+///
+/// `progress` is called with the progress from 0 to 1 and the routes found so far. The search runs
+/// without holding the GIL, so other Python threads keep running and can cancel it with `cancel`.
+/// An exception raised by `progress`, or a `KeyboardInterrupt`, stops the search and is raised
+/// again. Raises `ValueError` if the problem is invalid or over the limits of the solver.
+#[pyfunction]
+#[pyo3(signature = (
+    cauldron,
+    materials,
+    goals,
+    *,
+    transformations = "none",
+    max_overlaps = 0,
+    max_materials_per_overlap = 1,
+    coverage_tie_rule = "all_tied",
+    quality_model = None,
+    progress = None,
+    cancel = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn find_optimal_routes(
+    py: Python<'_>,
+    cauldron: &PyCauldron,
+    materials: Vec<Vec<PyMaterial>>,
+    goals: Vec<PyGoal>,
+    transformations: &str,
+    max_overlaps: usize,
+    max_materials_per_overlap: usize,
+    coverage_tie_rule: &str,
+    quality_model: Option<PyQualityModel>,
+    progress: Option<PyObject>,
+    cancel: Option<&PyCancelToken>,
+) -> PyResult<Vec<PyRoute>> {
+    let problem = SolverProblem {
+        cauldron: cauldron.cauldron.clone(),
+        materials: to_material_groups(materials),
+        goals: goals.into_iter().map(|goal| goal.0).collect(),
+        settings: SolverSettings {
+            transformations: parse_transformation_type(transformations)?,
+            overlap_limits: overlap_limits(max_overlaps, max_materials_per_overlap)?,
+            quality_model: quality_model.map(|quality_model| quality_model.0),
+            coverage_tie_rule: parse_coverage_tie_rule(coverage_tie_rule)?,
+        },
+    };
+
+    let cancelled = cancel.map_or_else(Default::default, |cancel| cancel.0.clone());
+    let error = Arc::new(Mutex::new(None::<PyErr>));
+    let reporter_error = error.clone();
+    let routes = py.allow_threads(move || {
+        problem.solve(Some(Box::new(move |fraction, routes| {
+            let result = Python::with_gil(|py| {
+                py.check_signals()?;
+                if let Some(progress) = &progress {
+                    let routes = routes.into_iter().map(PyRoute).collect::<Vec<_>>();
+                    progress.call1(py, (fraction, routes))?;
+                }
+                Ok(())
+            });
+            if let Err(e) = result {
+                *reporter_error.lock().unwrap() = Some(e);
+                return ControlFlow::Break(());
+            }
+
+            if cancelled.load(Ordering::Relaxed) {
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
+        })))
+    });
+
+    if let Some(e) = error.lock().unwrap().take() {
+        return Err(e);
+    }
+    let routes = routes.map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(routes.into_iter().map(PyRoute).collect())
+}
+
+#[pymodule]
+fn sophie_solver(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyShape>()?;
+    m.add_class::<PyMaterial>()?;
+    m.add_class::<PyGoal>()?;
+    m.add_class::<PyCauldron>()?;
+    m.add_class::<PyRoute>()?;
+    m.add_class::<PyCancelToken>()?;
+    m.add_class::<PyQualityModel>()?;
+    m.add_function(wrap_pyfunction!(find_optimal_routes, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::IntoPyDict;
+
+    use super::*;
+
+    /// Run Python code with the module imported as `s`.
+    fn run(code: &str) {
+        Python::with_gil(|py| {
+            let module = PyModule::new_bound(py, "sophie_solver").unwrap();
+            sophie_solver(&module).unwrap();
+            let globals = [("s", module)].into_py_dict_bound(py);
+            if let Err(e) = py.run_bound(code, Some(&globals), None) {
+                e.print(py);
+                panic!("Python code failed");
+            }
+        });
+    }
+
+    const UNI_BAG: &str = r#"
+cauldron = s.Cauldron("""
+color: white
+bonus_scores: flat 3 5 7
+B0 G0 Y0 Y0 W0
+W0 Y0 Y0 Y0 Y1
+R0 Y0 R1 R0 Y0
+R0 Y0 R0 R0 Y1
+W0 Y2 Y0 Y0 Y0
+""")
+line = s.Shape(["X..", "X..", "X.."])
+uni = s.Material("yellow", 15, line, quality=40)
+beehive = s.Material("yellow", 10, s.Shape(["X..", "XX.", "..."]), quality=25)
+broken_stone = s.Material("white", 15, line, quality=60)
+materials = [[uni, uni], [beehive], [broken_stone]]
+goals = [s.Goal([50, 100]), s.Goal([30, 50]), s.Goal([30, 55])]
+"#;
+
+    #[test]
+    fn solve_and_place() {
+        run(&format!(
+            "{UNI_BAG}
+calls = []
+routes = s.find_optimal_routes(
+    cauldron, materials, goals, progress=lambda p, routes: calls.append(p)
+)
+assert len(routes) == 2, routes
+assert calls and calls[-1] <= 1
+
+best = routes[0]
+placed = cauldron.copy()
+for material_index, position, transformation in best.moves:
+    placed.place(materials, material_index, position, transformation)
+scores = placed.calculate_final_score(materials)
+achieved = [sum(score >= t for t in goal.thresholds) for score, goal in zip(scores, goals)]
+assert achieved == best.achieved_goals, (achieved, best)
+assert placed.overlaps == best.overlaps
+assert best.quality is None
+
+routes = s.find_optimal_routes(
+    cauldron, materials, goals, max_overlaps=1, quality_model=s.QualityModel(quality_bonus=5)
+)
+assert all(route.overlaps <= 1 for route in routes), routes
+assert all(route.quality is not None for route in routes), routes
+"
+        ));
+    }
+
+    #[test]
+    fn cancel_and_errors() {
+        run(&format!(
+            "{UNI_BAG}
+token = s.CancelToken()
+routes = s.find_optimal_routes(
+    cauldron, materials, goals, progress=lambda p, routes: token.cancel(), cancel=token
+)
+assert token.cancelled
+
+class Stop(Exception):
+    pass
+
+def stop(p, routes):
+    raise Stop()
+
+try:
+    s.find_optimal_routes(cauldron, materials, goals, progress=stop)
+    assert False
+except Stop:
+    pass
+
+for bad in [
+    lambda: s.Cauldron('Q0'),
+    lambda: s.Shape(['X..']),
+    lambda: s.Material('purple', 1, line),
+    lambda: s.find_optimal_routes(cauldron, materials, goals[:1]),
+    lambda: s.find_optimal_routes(cauldron, materials, goals, coverage_tie_rule='liquid'),
+    lambda: s.find_optimal_routes(cauldron, materials * 2, goals * 2),
+    lambda: s.find_optimal_routes(s.Cauldron(str(cauldron).replace('Y0', 'Y0:9.9', 1)), materials, goals),
+    lambda: cauldron.copy().calculate_final_score(materials * 2),
+    lambda: s.find_optimal_routes(cauldron, materials, goals, max_overlaps=1, max_materials_per_overlap=0),
+    lambda: cauldron.copy().place(materials, (0, 0), 0, max_overlaps=1, max_materials_per_overlap=0),
+    lambda: cauldron.copy().place(materials, (5, 0), 0),
+]:
+    try:
+        bad()
+        assert False
+    except ValueError:
+        pass
+"
+        ));
+    }
+}
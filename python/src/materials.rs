@@ -0,0 +1,142 @@
+//! Materials and goals, which make up the problem along with the cauldron.
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use synth_solver::{Color, Goal, Material, Shape};
+
+/// A shape in a 3x3 grid, written as three rows where `X` is a filled tile and `.` an empty one,
+/// eg. `Shape(["X..", "XX.", "..."])`.
+#[pyclass(name = "Shape", module = "sophie_solver", frozen)]
+#[derive(Clone, Copy)]
+pub struct PyShape(pub Shape);
+
+// pyo3 methods take `self` by reference
+#[allow(clippy::trivially_copy_pass_by_ref)]
+#[pymethods]
+impl PyShape {
+    #[new]
+    fn new(rows: Vec<String>) -> PyResult<Self> {
+        let error = || PyValueError::new_err(format!("expected 3 rows like \"XX.\", got {rows:?}"));
+
+        let [a, b, c] = &rows[..] else {
+            return Err(error());
+        };
+        let mut matrix = [[false; 3]; 3];
+        for (row, matrix_row) in [a, b, c].into_iter().zip(&mut matrix) {
+            let tiles = row.chars().collect::<Vec<_>>();
+            if tiles.len() != 3 {
+                return Err(error());
+            }
+            for (tile, filled) in tiles.into_iter().zip(matrix_row) {
+                *filled = match tile {
+                    'X' | 'x' => true,
+                    '.' => false,
+                    _ => return Err(error()),
+                };
+            }
+        }
+        Ok(Self(Shape::from_matrix(matrix)))
+    }
+
+    fn rows(&self) -> Vec<String> {
+        self.0
+            .to_matrix()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&filled| if filled { 'X' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Shape({:?})", self.rows())
+    }
+}
+
+/// A material that can be placed in the cauldron, eg. `Material("yellow", 15, shape, quality=40)`.
+#[pyclass(name = "Material", module = "sophie_solver", frozen)]
+#[derive(Clone, Copy)]
+pub struct PyMaterial(pub Material);
+
+#[pymethods]
+impl PyMaterial {
+    #[new]
+    #[pyo3(signature = (color, effect_value, shape, quality = 0))]
+    fn new(color: &str, effect_value: u32, shape: PyShape, quality: u32) -> PyResult<Self> {
+        Ok(Self(
+            Material::new(parse_color(color)?, effect_value, shape.0).with_quality(quality),
+        ))
+    }
+
+    /// The name of the color, eg. `yellow`.
+    #[getter]
+    fn color(&self) -> &'static str {
+        self.0.color.name()
+    }
+
+    #[getter]
+    fn effect_value(&self) -> u32 {
+        self.0.effect_value
+    }
+
+    #[getter]
+    fn shape(&self) -> PyShape {
+        PyShape(self.0.shape)
+    }
+
+    #[getter]
+    fn quality(&self) -> u32 {
+        self.0.quality
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Material({:?}, {}, {}, quality={})",
+            self.color(),
+            self.0.effect_value,
+            self.shape().__repr__(),
+            self.0.quality
+        )
+    }
+}
+
+/// An item effect, reached by getting an effect value of at least each threshold, eg.
+/// `Goal([50, 100])`.
+#[pyclass(name = "Goal", module = "sophie_solver", frozen)]
+#[derive(Clone)]
+pub struct PyGoal(pub Goal);
+
+#[pymethods]
+impl PyGoal {
+    #[new]
+    fn new(thresholds: Vec<u32>) -> Self {
+        Self(Goal {
+            effect_value_thresholds: thresholds,
+        })
+    }
+
+    #[getter]
+    fn thresholds(&self) -> Vec<u32> {
+        self.0.effect_value_thresholds.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Goal({:?})", self.0.effect_value_thresholds)
+    }
+}
+
+pub fn parse_color(name: &str) -> PyResult<Color> {
+    Color::from_name(name).ok_or_else(|| PyValueError::new_err(format!("unknown color {name:?}")))
+}
+
+pub fn to_material_groups(materials: Vec<Vec<PyMaterial>>) -> Vec<Vec<Material>> {
+    materials
+        .into_iter()
+        .map(|group| group.into_iter().map(|material| material.0).collect())
+        .collect()
+}
@@ -0,0 +1,135 @@
+//! Routes found by the solver, and the transformations of their placements.
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use synth_solver::{
+    solver::SolverRoute, CoverageTieRule, OverlapLimits, Transformation, TransformationType,
+};
+
+/// A route found by the solver.
+#[pyclass(name = "Route", module = "sophie_solver", frozen)]
+#[derive(Clone)]
+pub struct PyRoute(pub SolverRoute);
+
+#[pymethods]
+impl PyRoute {
+    /// The amount of thresholds that are met for each goal.
+    #[getter]
+    fn achieved_goals(&self) -> Vec<usize> {
+        self.0.goal_result.achieved_goals.to_vec()
+    }
+
+    /// The predicted quality of the item, if quality was optimized.
+    #[getter]
+    fn quality(&self) -> Option<u32> {
+        self.0.goal_result.quality
+    }
+
+    #[getter]
+    fn overlaps(&self) -> usize {
+        self.0.overlaps
+    }
+
+    /// The moves as `((group, index), position, transformation)` tuples, in the arguments of
+    /// `Cauldron.place`.
+    #[getter]
+    fn moves(&self) -> Vec<((usize, usize), usize, Option<&'static str>)> {
+        self.0
+            .moves
+            .iter()
+            .map(|move_| {
+                (
+                    move_.material_index,
+                    move_.placement.index,
+                    move_.placement.transformation.map(transformation_name),
+                )
+            })
+            .collect()
+    }
+
+    /// The route in route notation for a cauldron of the given size, eg. `0.0@a1; 1.0@c3R90`.
+    fn to_notation(&self, size: usize) -> String {
+        synth_solver::solver::format_route(&self.0.moves, size)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Route(achieved_goals={:?}, quality={:?}, overlaps={})",
+            self.achieved_goals(),
+            self.0.goal_result.quality,
+            self.0.overlaps
+        )
+    }
+}
+
+const TRANSFORMATIONS: [Transformation; 5] = [
+    Transformation::FlipHorizontal,
+    Transformation::FlipVertical,
+    Transformation::Rotate90,
+    Transformation::Rotate180,
+    Transformation::Rotate270,
+];
+
+fn transformation_name(transformation: Transformation) -> &'static str {
+    match transformation {
+        Transformation::FlipHorizontal => "flip_horizontal",
+        Transformation::FlipVertical => "flip_vertical",
+        Transformation::Rotate90 => "rotate90",
+        Transformation::Rotate180 => "rotate180",
+        Transformation::Rotate270 => "rotate270",
+    }
+}
+
+pub fn parse_transformation(name: &str) -> PyResult<Transformation> {
+    TRANSFORMATIONS
+        .into_iter()
+        .find(|&transformation| transformation_name(transformation) == name)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown transformation {name:?}")))
+}
+
+/// Parse the transformations the solver may use: `none`, `flip_horizontal`, `flip_vertical` or
+/// `rotate`.
+pub fn parse_transformation_type(name: &str) -> PyResult<TransformationType> {
+    match name {
+        "none" => Ok(TransformationType::None),
+        "flip_horizontal" => Ok(TransformationType::FlipHorizontal),
+        "flip_vertical" => Ok(TransformationType::FlipVertical),
+        "rotate" => Ok(TransformationType::Rotate),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown transformations {name:?}, expected none, flip_horizontal, flip_vertical or \
+             rotate"
+        ))),
+    }
+}
+
+/// Parse the rule for colors that are tied for the highest coverage: `all_tied`, `none`,
+/// `first_by_color_index` or `cauldron_color`.
+pub fn parse_coverage_tie_rule(name: &str) -> PyResult<CoverageTieRule> {
+    match name {
+        "all_tied" => Ok(CoverageTieRule::AllTied),
+        "none" => Ok(CoverageTieRule::None),
+        "first_by_color_index" => Ok(CoverageTieRule::FirstByColorIndex),
+        "cauldron_color" => Ok(CoverageTieRule::CauldronColor),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown coverage tie rule {name:?}, expected all_tied, none, first_by_color_index or \
+             cauldron_color"
+        ))),
+    }
+}
+
+/// The overlap limits for a search or placement. Fails if overlaps are allowed but no material may
+/// be overlapped, which would silently disallow them.
+pub fn overlap_limits(
+    max_overlaps: usize,
+    max_materials_per_overlap: usize,
+) -> PyResult<OverlapLimits> {
+    if max_overlaps > 0 && max_materials_per_overlap == 0 {
+        return Err(PyValueError::new_err(format!(
+            "max_overlaps is {max_overlaps}, but max_materials_per_overlap is 0, so no overlaps \
+             are possible"
+        )));
+    }
+    Ok(OverlapLimits {
+        max_overlaps,
+        max_materials_per_overlap,
+    })
+}
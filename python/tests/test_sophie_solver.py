@@ -0,0 +1,146 @@
+import threading
+
+import pytest
+
+import sophie_solver as s
+
+CAULDRON = """
+color: white
+bonus_scores: flat 3 5 7
+B0 G0 Y0 Y0 W0
+W0 Y0 Y0 Y0 Y1
+R0 Y0 R1 R0 Y0
+R0 Y0 R0 R0 Y1
+W0 Y2 Y0 Y0 Y0
+"""
+
+
+@pytest.fixture
+def uni_bag():
+    line = s.Shape(["X..", "X..", "X.."])
+    uni = s.Material("yellow", 15, line, quality=40)
+    beehive = s.Material("yellow", 10, s.Shape(["X..", "XX.", "..."]), quality=25)
+    broken_stone = s.Material("white", 15, line, quality=60)
+    materials = [[uni, uni], [beehive], [broken_stone]]
+    goals = [s.Goal([50, 100]), s.Goal([30, 50]), s.Goal([30, 55])]
+    return s.Cauldron(CAULDRON), materials, goals
+
+
+def test_shape_and_material():
+    shape = s.Shape(["X..", "XX.", "..."])
+    assert shape.rows() == ["X..", "XX.", "..."]
+    assert shape == s.Shape(["x..", "xx.", "..."])
+
+    material = s.Material("yellow", 10, shape)
+    assert (material.color, material.effect_value, material.quality) == ("yellow", 10, 0)
+    assert material.shape == shape
+
+    with pytest.raises(ValueError):
+        s.Shape(["X.."])
+    with pytest.raises(ValueError):
+        s.Material("purple", 10, shape)
+
+
+def test_cauldron_text():
+    cauldron = s.Cauldron(CAULDRON)
+    assert (cauldron.size, cauldron.color, cauldron.overlaps) == (5, "white", 0)
+    assert cauldron.tile(1, 4) == ("yellow", 2, None)
+    assert "Y2" in str(cauldron)
+
+    with pytest.raises(IndexError):
+        cauldron.tile(5, 0)
+    with pytest.raises(ValueError):
+        s.Cauldron("Q0")
+
+
+def test_find_and_replay_routes(uni_bag):
+    cauldron, materials, goals = uni_bag
+    progress = []
+    routes = s.find_optimal_routes(
+        cauldron, materials, goals, progress=lambda p, routes: progress.append(p)
+    )
+    assert len(routes) == 2
+    assert progress == sorted(progress)
+
+    best = routes[0]
+    placed = cauldron.copy()
+    for material_index, position, transformation in best.moves:
+        placed.place(materials, material_index, position, transformation)
+    scores = placed.calculate_final_score(materials)
+    achieved = [sum(score >= t for t in goal.thresholds) for score, goal in zip(scores, goals)]
+    assert achieved == best.achieved_goals
+    assert best.to_notation(cauldron.size).count("@") == len(best.moves)
+
+    # the original cauldron is unchanged
+    assert cauldron.tile(0, 0) == ("blue", 0, None)
+
+
+def test_overlaps_and_quality(uni_bag):
+    cauldron, materials, goals = uni_bag
+    assert s.find_optimal_routes(cauldron, materials, goals)[0].quality is None
+
+    routes = s.find_optimal_routes(
+        cauldron, materials, goals, max_overlaps=1, quality_model=s.QualityModel(quality_bonus=5)
+    )
+    assert all(route.overlaps <= 1 for route in routes)
+    assert all(route.quality is not None for route in routes)
+
+    # overlaps are allowed, but no material may be overlapped
+    with pytest.raises(ValueError):
+        s.find_optimal_routes(cauldron, materials, goals, max_overlaps=1, max_materials_per_overlap=0)
+
+
+def test_place_errors(uni_bag):
+    cauldron, materials, _ = uni_bag
+    with pytest.raises(ValueError):
+        cauldron.place(materials, (5, 0), 0)
+    with pytest.raises(ValueError):
+        cauldron.place(materials, (0, 0), 0, "rotate45")
+    with pytest.raises(ValueError):
+        cauldron.place(materials, (0, 0), 0, max_overlaps=1, max_materials_per_overlap=0)
+
+    cauldron.place(materials, (0, 0), 0)
+    # the same material groups have to be used for every placement
+    with pytest.raises(ValueError):
+        cauldron.place(materials[:2], (1, 0), 0)
+
+
+def test_cancel_from_callback(uni_bag):
+    cauldron, materials, goals = uni_bag
+    token = s.CancelToken()
+    s.find_optimal_routes(
+        cauldron, materials, goals, progress=lambda p, routes: token.cancel(), cancel=token
+    )
+    assert token.cancelled
+
+
+def test_cancel_from_thread(uni_bag):
+    cauldron, materials, goals = uni_bag
+    token = s.CancelToken()
+    started = threading.Event()
+
+    def solve():
+        s.find_optimal_routes(
+            cauldron, materials, goals, progress=lambda p, routes: started.set(), cancel=token
+        )
+
+    # the search releases the GIL, so this thread can cancel it while it runs
+    thread = threading.Thread(target=solve)
+    thread.start()
+    assert started.wait(timeout=60)
+    token.cancel()
+    thread.join(timeout=60)
+    assert not thread.is_alive()
+
+
+def test_callback_exception(uni_bag):
+    cauldron, materials, goals = uni_bag
+
+    class Stop(Exception):
+        pass
+
+    def stop(p, routes):
+        raise Stop()
+
+    with pytest.raises(Stop):
+        s.find_optimal_routes(cauldron, materials, goals, progress=stop)
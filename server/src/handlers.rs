@@ -0,0 +1,233 @@
+//! The endpoints of the server, which take and return JSON.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use synth_solver::{problem_file::ProblemFile, solver};
+
+use crate::jobs::Jobs;
+
+/// A response with an HTTP status code and a JSON body.
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ScoreRouteRequest {
+    problem: ProblemFile,
+    /// The route in route notation, eg. `1.0@c3R90; 0.1@a1`.
+    route: String,
+}
+
+/// Handle a request to the given method and path with a JSON body.
+pub fn handle(jobs: &Jobs, method: &str, path: &str, body: &str) -> Response {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    match (method, &segments[..]) {
+        ("POST", ["solve"]) => solve(jobs, body),
+        ("POST", ["score_route"]) => score_route(body),
+        ("POST", ["validate_problem"]) => validate_problem(body),
+        ("GET", ["jobs", job_id]) => match job_id.parse().ok().and_then(|id| jobs.state(id)) {
+            Some(state) => Response::ok(serde_json::to_value(state).unwrap()),
+            None => Response::error(404, format!("Unknown job {job_id}")),
+        },
+        ("DELETE", ["jobs", job_id]) => match job_id.parse() {
+            Ok(id) if jobs.cancel(id) => Response::ok(json!({ "job_id": id })),
+            _ => Response::error(404, format!("Unknown job {job_id}")),
+        },
+        _ => Response::error(404, format!("Unknown endpoint {method} {path}")),
+    }
+}
+
+/// Start solving a problem, returning the id of the job to poll.
+fn solve(jobs: &Jobs, body: &str) -> Response {
+    let problem = match parse_problem(body) {
+        Ok(problem) => problem,
+        Err(response) => return response,
+    };
+
+    match jobs.start(problem) {
+        Ok(job_id) => Response {
+            status: 202,
+            body: json!({ "job_id": job_id }),
+        },
+        Err(e) => Response::error(400, e.to_string()),
+    }
+}
+
+/// Replay a route and return the goals it achieves.
+fn score_route(body: &str) -> Response {
+    let request = match serde_json::from_str::<ScoreRouteRequest>(body) {
+        Ok(request) => request,
+        Err(e) => return Response::error(400, format!("Invalid request: {e}")),
+    };
+    let problem = match request.problem.to_problem() {
+        Ok(problem) => problem,
+        Err(e) => return Response::error(400, e.to_string()),
+    };
+
+    let route = solver::parse_route(&request.route, problem.cauldron.size)
+        .map_err(|e| e.to_string())
+        .and_then(|moves| problem.score_route(&moves).map_err(|e| e.to_string()));
+    match route {
+        Ok(route) => Response::ok(serde_json::to_value(route).unwrap()),
+        Err(e) => Response::error(400, e),
+    }
+}
+
+/// Check whether a problem is valid without solving it.
+fn validate_problem(body: &str) -> Response {
+    match parse_problem(body) {
+        Ok(_) => Response::ok(json!({ "valid": true })),
+        Err(Response { body, .. }) => Response::ok(json!({
+            "valid": false,
+            "error": body["error"],
+        })),
+    }
+}
+
+fn parse_problem(body: &str) -> Result<solver::SolverProblem, Response> {
+    ProblemFile::from_json(body)
+        .and_then(|file| file.to_problem())
+        .map_err(|e| Response::error(400, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use synth_solver::{utils::test_data::*, OverlapLimits, TransformationType};
+
+    use super::*;
+
+    fn problem_json() -> String {
+        ProblemFile::new(&problems::uni_bag()).to_json().unwrap()
+    }
+
+    #[test]
+    fn solve_and_poll() {
+        let jobs = Jobs::default();
+        let response = handle(&jobs, "POST", "/solve", &problem_json());
+        assert_eq!(response.status, 202);
+        let job_id = response.body["job_id"].as_u64().unwrap();
+
+        let start = Instant::now();
+        let state = loop {
+            let response = handle(&jobs, "GET", &format!("/jobs/{job_id}"), "");
+            assert_eq!(response.status, 200);
+            if response.body["status"] != "running" {
+                break response.body;
+            }
+            assert!(start.elapsed() < Duration::from_secs(60));
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(state["status"], "done");
+        assert_eq!(state["progress"], 1.0);
+        assert_eq!(state["output"]["routes"].as_array().unwrap().len(), 2);
+
+        // deleting a finished job forgets it
+        assert_eq!(
+            handle(&jobs, "DELETE", &format!("/jobs/{job_id}"), "").status,
+            200
+        );
+        assert_eq!(
+            handle(&jobs, "GET", &format!("/jobs/{job_id}"), "").status,
+            404
+        );
+    }
+
+    #[test]
+    fn cancel_running_job() {
+        // with this many materials and all transformations the search runs for a long time
+        let mut problem = problems::uni_bag();
+        problem.materials[0] = vec![material::uni(); 5];
+        problem.settings.transformations = TransformationType::Rotate;
+        problem.settings.overlap_limits = OverlapLimits::UNLIMITED;
+        let problem_json = ProblemFile::new(&problem).to_json().unwrap();
+
+        let jobs = Jobs::default();
+        let response = handle(&jobs, "POST", "/solve", &problem_json);
+        let job_id = response.body["job_id"].as_u64().unwrap();
+        let response = handle(&jobs, "GET", &format!("/jobs/{job_id}"), "");
+        assert_eq!(response.body["status"], "running");
+
+        assert_eq!(
+            handle(&jobs, "DELETE", &format!("/jobs/{job_id}"), "").status,
+            200
+        );
+        let start = Instant::now();
+        let state = loop {
+            let response = handle(&jobs, "GET", &format!("/jobs/{job_id}"), "");
+            if response.body["status"] != "running" {
+                break response.body;
+            }
+            assert!(start.elapsed() < Duration::from_secs(60));
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(state["status"], "cancelled");
+        assert!(state["progress"].as_f64().unwrap() < 1.);
+    }
+
+    #[test]
+    fn score_and_validate() {
+        let jobs = Jobs::default();
+        let problem = serde_json::from_str::<Value>(&problem_json()).unwrap();
+
+        let request = json!({ "problem": problem, "route": "0.0@a1; 0.1@b1; 1.0@b4; 2.0@c2" });
+        let response = handle(&jobs, "POST", "/score_route", &request.to_string());
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.body["goal_result"]["achieved_goals"],
+            json!([1, 1, 1])
+        );
+
+        let request = json!({ "problem": problem, "route": "0.0@z1" });
+        let response = handle(&jobs, "POST", "/score_route", &request.to_string());
+        assert_eq!(response.status, 400);
+
+        let mut unknown_material = problem.clone();
+        unknown_material["cauldron"]["tiles"][0] = json!("B0 G0 Y0:9.9 Y0 W0");
+        let request = json!({ "problem": unknown_material, "route": "" });
+        let response = handle(&jobs, "POST", "/score_route", &request.to_string());
+        assert_eq!(response.status, 400);
+        let response = handle(&jobs, "POST", "/solve", &unknown_material.to_string());
+        assert_eq!(response.status, 400);
+
+        // problems over the limits of the solver are rejected before a job is started
+        let mut too_many_goals = problem.clone();
+        for key in ["goals", "material_groups"] {
+            let groups = too_many_goals[key].as_array_mut().unwrap();
+            groups.extend([groups[0].clone(), groups[0].clone()]);
+        }
+        let response = handle(&jobs, "POST", "/solve", &too_many_goals.to_string());
+        assert_eq!(response.status, 400);
+        assert!(jobs.state(1).is_none());
+
+        let response = handle(&jobs, "POST", "/validate_problem", &problem_json());
+        assert_eq!(response.body, json!({ "valid": true }));
+        let response = handle(&jobs, "POST", "/validate_problem", "{}");
+        assert_eq!(response.body["valid"], false);
+
+        assert_eq!(handle(&jobs, "GET", "/solve", "").status, 404);
+        assert_eq!(handle(&jobs, "DELETE", "/jobs/12", "").status, 404);
+    }
+}
@@ -0,0 +1,246 @@
+//! Solver jobs that run in the background, so a client can poll their progress and cancel them.
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    ops::ControlFlow,
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use synth_solver::{
+    errors::SynthError,
+    output_file::OutputFile,
+    solver::{ProgressReporter, SolverProblem, SolverResult},
+};
+
+/// How long a finished job is kept for clients to fetch its routes.
+pub const FINISHED_JOB_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Done,
+    Cancelled,
+    /// The solver panicked.
+    Failed,
+}
+
+/// The state of a job as it is returned to clients.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobState {
+    pub job_id: u64,
+    pub status: JobStatus,
+    /// The progress from 0 to 1.
+    pub progress: f32,
+    /// The routes found so far, or all routes once the job is done.
+    pub output: OutputFile,
+    /// Why the job failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct Job {
+    cancelled: AtomicBool,
+    progress: Mutex<(f32, SolverResult)>,
+    error: OnceLock<String>,
+    /// When the job finished.
+    finished: OnceLock<Instant>,
+}
+
+/// The jobs that were started, by their id. Finished jobs are forgotten after a while.
+#[derive(Clone)]
+pub struct Jobs {
+    next_id: Arc<AtomicU64>,
+    jobs: Arc<Mutex<HashMap<u64, Arc<Job>>>>,
+    finished_job_ttl: Duration,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self {
+            next_id: Default::default(),
+            jobs: Default::default(),
+            finished_job_ttl: FINISHED_JOB_TTL,
+        }
+    }
+}
+
+impl Jobs {
+    /// Set how long finished jobs are kept. Defaults to [`FINISHED_JOB_TTL`].
+    #[must_use]
+    pub fn finished_job_ttl(self, finished_job_ttl: Duration) -> Self {
+        Self {
+            finished_job_ttl,
+            ..self
+        }
+    }
+
+    /// Start solving a problem on a new thread, returning the id of the job. Fails if the problem
+    /// is invalid.
+    pub fn start(&self, problem: SolverProblem) -> Result<u64, SynthError> {
+        problem.validate()?;
+        Ok(self.spawn(move |reporter| {
+            problem
+                .solve(Some(reporter))
+                // the problem was validated before the job was started
+                .unwrap_or_default()
+        }))
+    }
+
+    /// Run `solve` on a new thread with a reporter that updates the progress of a new job.
+    fn spawn(
+        &self,
+        solve: impl FnOnce(ProgressReporter<SolverResult>) -> SolverResult + Send + 'static,
+    ) -> u64 {
+        let job_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Arc::new(Job {
+            cancelled: AtomicBool::new(false),
+            progress: Mutex::new((0., vec![])),
+            error: OnceLock::new(),
+            finished: OnceLock::new(),
+        });
+        let mut jobs = self.jobs.lock().unwrap();
+        self.remove_expired(&mut jobs);
+        jobs.insert(job_id, job.clone());
+        drop(jobs);
+
+        std::thread::spawn(move || {
+            let reporter_job = job.clone();
+            let reporter: ProgressReporter<SolverResult> = Box::new(move |progress, routes| {
+                *reporter_job.progress.lock().unwrap() = (progress, routes);
+
+                if reporter_job.cancelled.load(Ordering::Relaxed) {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            });
+            // a bug in the solver fails the job instead of leaving it running forever
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| solve(reporter)));
+
+            let mut progress = job.progress.lock().unwrap();
+            match result {
+                Ok(routes) => {
+                    // a cancelled search returns the routes it found so far
+                    if !job.cancelled.load(Ordering::Relaxed) {
+                        progress.0 = 1.;
+                    }
+                    progress.1 = routes;
+                }
+                Err(payload) => {
+                    let message = format!("The solver panicked: {}", panic_message(&*payload));
+                    _ = job.error.set(message);
+                }
+            }
+            _ = job.finished.set(Instant::now());
+        });
+
+        job_id
+    }
+
+    pub fn state(&self, job_id: u64) -> Option<JobState> {
+        let mut jobs = self.jobs.lock().unwrap();
+        self.remove_expired(&mut jobs);
+        let job = jobs.get(&job_id)?.clone();
+        drop(jobs);
+
+        // the job is marked as finished while its final routes are locked, so they are read after
+        let status = match (
+            job.finished.get().is_some(),
+            job.cancelled.load(Ordering::Relaxed),
+        ) {
+            (false, _) => JobStatus::Running,
+            _ if job.error.get().is_some() => JobStatus::Failed,
+            (true, false) => JobStatus::Done,
+            (true, true) => JobStatus::Cancelled,
+        };
+        let (progress, routes) = job.progress.lock().unwrap().clone();
+
+        Some(JobState {
+            job_id,
+            status,
+            progress,
+            output: OutputFile::new(&routes),
+            error: job.error.get().cloned(),
+        })
+    }
+
+    /// Cancel a running job, or forget a job that has finished. Returns `false` if there is no job
+    /// with the id.
+    pub fn cancel(&self, job_id: u64) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.get(&job_id) else {
+            return false;
+        };
+
+        if job.finished.get().is_some() {
+            jobs.remove(&job_id);
+        } else {
+            job.cancelled.store(true, Ordering::Relaxed);
+        }
+        true
+    }
+
+    fn remove_expired(&self, jobs: &mut HashMap<u64, Arc<Job>>) {
+        jobs.retain(|_, job| {
+            (job.finished.get()).is_none_or(|finished| finished.elapsed() < self.finished_job_ttl)
+        });
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wait_until_finished(jobs: &Jobs, job_id: u64) -> Option<JobState> {
+        let start = Instant::now();
+        loop {
+            match jobs.state(job_id) {
+                Some(state) if state.status == JobStatus::Running => {}
+                state => return state,
+            }
+            assert!(start.elapsed() < Duration::from_secs(60));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn panicking_job_fails() {
+        let jobs = Jobs::default();
+        let job_id = jobs.spawn(|_| panic!("index {} out of bounds", 4));
+
+        let state = wait_until_finished(&jobs, job_id).unwrap();
+        assert_eq!(state.status, JobStatus::Failed);
+        assert_eq!(
+            state.error.as_deref(),
+            Some("The solver panicked: index 4 out of bounds")
+        );
+    }
+
+    #[test]
+    fn finished_jobs_expire() {
+        let jobs = Jobs::default().finished_job_ttl(Duration::ZERO);
+        let job_id = jobs.spawn(|_| vec![]);
+        assert!(wait_until_finished(&jobs, job_id).is_none());
+
+        let jobs = Jobs::default();
+        let job_id = jobs.spawn(|_| vec![]);
+        let state = wait_until_finished(&jobs, job_id).unwrap();
+        assert_eq!(state.status, JobStatus::Done);
+        assert!(jobs.state(job_id).is_some());
+    }
+}
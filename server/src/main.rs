@@ -0,0 +1,87 @@
+#![warn(
+    clippy::cloned_instead_of_copied,
+    clippy::explicit_iter_loop,
+    clippy::return_self_not_must_use,
+    clippy::trivially_copy_pass_by_ref,
+    clippy::uninlined_format_args,
+    clippy::use_self
+)]
+#![allow(clippy::wildcard_imports)]
+
+mod handlers;
+mod jobs;
+
+use std::{panic::AssertUnwindSafe, process::ExitCode, time::Duration};
+
+use clap::Parser;
+use tiny_http::{Header, Server};
+
+use crate::jobs::Jobs;
+
+/// Serves the solver over HTTP with JSON bodies, for other tools running on this machine.
+///
+/// Endpoints:
+///
+/// - `POST /solve` with a problem file in JSON starts a job and returns its `job_id`
+///
+/// - `GET /jobs/{id}` returns the status, progress and routes found so far of a job. Finished jobs
+///   are forgotten after `--job-ttl` seconds.
+///
+/// - `DELETE /jobs/{id}` cancels a running job, or forgets a finished one
+///
+/// - `POST /score_route` with `{"problem": ..., "route": "1.0@c3R90; 0.1@a1"}` returns the goals
+///   the route achieves
+///
+/// - `POST /validate_problem` with a problem file in JSON returns whether it is valid
+#[derive(Parser)]
+#[command(version, verbatim_doc_comment)]
+struct Cli {
+    /// The port to listen on. Only connections from this machine are accepted.
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+    /// How long finished jobs are kept, in seconds.
+    #[arg(long, default_value_t = jobs::FINISHED_JOB_TTL.as_secs())]
+    job_ttl: u64,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let server = match Server::http(("127.0.0.1", cli.port)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to listen on port {}: {e}", cli.port);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("Listening on http://127.0.0.1:{}", cli.port);
+
+    let jobs = Jobs::default().finished_job_ttl(Duration::from_secs(cli.job_ttl));
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            // a bug in a handler fails the request instead of stopping the server
+            Ok(_) => std::panic::catch_unwind(AssertUnwindSafe(|| {
+                handlers::handle(&jobs, request.method().as_str(), request.url(), &body)
+            }))
+            .unwrap_or_else(|_| handlers::Response {
+                status: 500,
+                body: serde_json::json!({ "error": "Internal error while handling the request" }),
+            }),
+            Err(e) => handlers::Response {
+                status: 400,
+                body: serde_json::json!({ "error": format!("Failed to read request: {e}") }),
+            },
+        };
+
+        let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let http_response = tiny_http::Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(content_type);
+        if let Err(e) = request.respond(http_response) {
+            eprintln!("Failed to send response: {e}");
+        }
+    }
+
+    ExitCode::SUCCESS
+}
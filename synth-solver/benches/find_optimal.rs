@@ -21,7 +21,13 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 
             for (item, placement) in placements {
                 cauldron
-                    .place(&materials, item, placement, false, &mut scores)
+                    .place(
+                        &materials,
+                        item,
+                        placement,
+                        OverlapLimits::NONE,
+                        &mut scores,
+                    )
                     .unwrap();
             }
         });
@@ -58,7 +64,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             let cauldron = cauldron::uni_bag_5x5_bonus1();
             let goals = goals::uni_bag();
             let properties = SolverSettings {
-                allow_overlaps: true,
+                overlap_limits: OverlapLimits::UNLIMITED,
                 ..Default::default()
             };
             b.iter(|| {
@@ -78,6 +84,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             color: Color::White,
             effect_value: 1000,
             shape: Shape::from_binary([0b100, 0b100, 0b100]),
+            quality: 50,
         };
         let materials = vec![
             vec![perfect_material, material::uni()],
@@ -0,0 +1,397 @@
+//! Solving many problems at once, eg. every combination of materials for an item.
+//!
+//! Problems are read from a directory of problem files, or from a JSONL file with a problem file in
+//! JSON on each line and an optional `"id"` field. Each result is appended as a line to a JSONL
+//! output file as soon as it is found:
+//!
+//! ```json
+//! {"id": "uni_bag.toml", "output": { ... }, "error": null, "duration_secs": 0.25}
+//! ```
+//!
+//! `output` has the routes in the format of the [`output_file`](crate::output_file) module, or is
+//! `null` if the problem could not be read or solved, in which case `error` says why. Problems that
+//! already have a result in the output file are skipped, so an interrupted batch continues where it
+//! left off when it is run again.
+//!
+//! Batches need threads and a clock, so this module isn't available on `wasm32`.
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    panic::UnwindSafe,
+    path::Path,
+    sync::{mpsc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{output_file::OutputFile, problem_file::ProblemFile};
+
+/// A problem in a batch, or the reason it could not be read.
+#[derive(Debug, Clone)]
+pub struct BatchProblem {
+    /// The name of the problem file, or the id or line number in a JSONL file.
+    pub id: String,
+    pub problem: Result<ProblemFile, String>,
+}
+
+/// The result of solving a problem in a batch, as it is written to the output file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub id: String,
+    pub output: Option<OutputFile>,
+    pub error: Option<String>,
+    /// The time it took to solve the problem.
+    pub duration_secs: f64,
+}
+
+/// The amount of problems that were solved, failed or skipped in a batch.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BatchSummary {
+    pub solved: usize,
+    pub failed: usize,
+    /// Problems that already had a result in the output file.
+    pub skipped: usize,
+    /// The time it took to solve the whole batch.
+    pub duration: Duration,
+}
+
+/// A batch of problems to solve.
+pub struct Batch {
+    problems: Vec<BatchProblem>,
+    threads: usize,
+}
+
+impl Batch {
+    pub fn new(problems: Vec<BatchProblem>) -> Self {
+        Self {
+            problems,
+            threads: 1,
+        }
+    }
+
+    /// Read the problems from a directory of problem files or a JSONL file.
+    pub fn read(input: impl AsRef<Path>) -> Result<Self, BatchError> {
+        let input = input.as_ref();
+        let problems = if input.is_dir() {
+            read_dir(input)?
+        } else {
+            read_jsonl(input)?
+        };
+        Ok(Self::new(problems))
+    }
+
+    /// Set the amount of problems that are solved at the same time. Defaults to 1.
+    #[must_use]
+    pub fn threads(self, threads: usize) -> Self {
+        Self {
+            threads: threads.max(1),
+            ..self
+        }
+    }
+
+    pub fn problems(&self) -> &[BatchProblem] {
+        &self.problems
+    }
+
+    /// Solve the problems that have no result in the output file yet, appending their results to
+    /// it. `on_result` is called with each result after it was written.
+    pub fn solve(
+        self,
+        output: impl AsRef<Path>,
+        mut on_result: impl FnMut(&BatchResult),
+    ) -> Result<BatchSummary, BatchError> {
+        let start = Instant::now();
+        let output = output.as_ref();
+        let done = read_done_ids(output)?;
+
+        let (todo, skipped): (Vec<_>, Vec<_>) = self
+            .problems
+            .into_iter()
+            .partition(|problem| !done.contains(&problem.id));
+        let mut summary = BatchSummary {
+            skipped: skipped.len(),
+            ..Default::default()
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(output)
+            .map_err(BatchError::Io)?;
+
+        let queue = Mutex::new(todo.into_iter());
+        let (result_send, result_recv) = mpsc::channel();
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads {
+                let result_send = result_send.clone();
+                let queue = &queue;
+                scope.spawn(move || loop {
+                    let Some(problem) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    if result_send.send(solve_problem(problem)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(result_send);
+
+            for result in result_recv {
+                let line = serde_json::to_string(&result).expect("results can be serialized");
+                writeln!(file, "{line}").map_err(BatchError::Io)?;
+                file.flush().map_err(BatchError::Io)?;
+
+                match result.error {
+                    None => summary.solved += 1,
+                    Some(_) => summary.failed += 1,
+                }
+                on_result(&result);
+            }
+            Ok(())
+        })?;
+
+        summary.duration = start.elapsed();
+        Ok(summary)
+    }
+}
+
+fn solve_problem(problem: BatchProblem) -> BatchResult {
+    let start = Instant::now();
+    let output = catch_panic(|| {
+        problem
+            .problem
+            .and_then(|file| file.to_problem().map_err(|e| e.to_string()))
+            .and_then(|problem| problem.solve(None).map_err(|e| e.to_string()))
+            .map(|routes| OutputFile::new(&routes))
+    });
+
+    let (output, error) = match output {
+        Ok(output) => (Some(output), None),
+        Err(e) => (None, Some(e)),
+    };
+    BatchResult {
+        id: problem.id,
+        output,
+        error,
+        duration_secs: start.elapsed().as_secs_f64(),
+    }
+}
+
+/// Run `f`, turning a panic into an error so a bug in the solver fails one problem instead of the
+/// whole batch.
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, String> + UnwindSafe) -> Result<T, String> {
+    std::panic::catch_unwind(f).unwrap_or_else(|payload| {
+        let message = (payload.downcast_ref::<&str>().copied())
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown error");
+        Err(format!("The solver panicked: {message}"))
+    })
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<BatchProblem>, BatchError> {
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(BatchError::Io)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(BatchError::Io)?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("toml") || e.eq_ignore_ascii_case("json"))
+    });
+    paths.sort();
+
+    Ok(paths
+        .iter()
+        .map(|path| BatchProblem {
+            id: path.file_name().unwrap().to_string_lossy().into_owned(),
+            problem: ProblemFile::read(path).map_err(|e| e.to_string()),
+        })
+        .collect())
+}
+
+fn read_jsonl(path: &Path) -> Result<Vec<BatchProblem>, BatchError> {
+    #[derive(Deserialize)]
+    struct Line {
+        id: Option<String>,
+        #[serde(flatten)]
+        problem: ProblemFile,
+    }
+
+    let file = File::open(path).map_err(BatchError::Io)?;
+    let mut problems = vec![];
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(BatchError::Io)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let id = format!("line {}", i + 1);
+        problems.push(match serde_json::from_str::<Line>(&line) {
+            Ok(line) => BatchProblem {
+                id: line.id.unwrap_or(id),
+                problem: Ok(line.problem),
+            },
+            Err(e) => BatchProblem {
+                id,
+                problem: Err(format!("Failed to parse problem: {e}")),
+            },
+        });
+    }
+    Ok(problems)
+}
+
+/// Read the ids of the problems that have a result in the output file. A line that was only partly
+/// written when the batch was interrupted is removed, so new results start on a new line.
+fn read_done_ids(output: &Path) -> Result<HashSet<String>, BatchError> {
+    #[derive(Deserialize)]
+    struct Id {
+        id: String,
+    }
+
+    let contents = match std::fs::read_to_string(output) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(e) => return Err(BatchError::Io(e)),
+    };
+
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        let complete_len = contents.rfind('\n').map_or(0, |i| i + 1);
+        let file = OpenOptions::new()
+            .write(true)
+            .open(output)
+            .map_err(BatchError::Io)?;
+        file.set_len(complete_len as u64).map_err(BatchError::Io)?;
+    }
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Id>(line).ok())
+        .map(|line| line.id)
+        .collect())
+}
+
+/// An error that stops a batch, as opposed to a problem that could not be solved.
+#[derive(Debug)]
+pub enum BatchError {
+    /// The problems could not be read or the results could not be written
+    Io(std::io::Error),
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to access batch files: {e}"),
+        }
+    }
+}
+
+impl Error for BatchError {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::utils::test_data::problems;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("batch_{name}_{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn solve_dir_and_resume() {
+        let dir = temp_dir("dir");
+        let problems_dir = dir.join("problems");
+        std::fs::create_dir(&problems_dir).unwrap();
+        let file = ProblemFile::new(&problems::uni_bag());
+        file.write(problems_dir.join("a.toml")).unwrap();
+        file.write(problems_dir.join("b.json")).unwrap();
+        std::fs::write(problems_dir.join("c.toml"), "goals = 3").unwrap();
+        std::fs::write(problems_dir.join("notes.txt"), "not a problem").unwrap();
+
+        let output = dir.join("results.jsonl");
+        let mut ids = vec![];
+        let summary = Batch::read(&problems_dir)
+            .unwrap()
+            .threads(2)
+            .solve(&output, |result| ids.push(result.id.clone()))
+            .unwrap();
+        ids.sort();
+        assert_eq!(ids, ["a.toml", "b.json", "c.toml"]);
+        assert_eq!((summary.solved, summary.failed, summary.skipped), (2, 1, 0));
+
+        let results = std::fs::read_to_string(&output).unwrap();
+        let results = results
+            .lines()
+            .map(|line| serde_json::from_str::<BatchResult>(line).unwrap())
+            .collect::<Vec<_>>();
+        let a = results.iter().find(|r| r.id == "a.toml").unwrap();
+        assert_eq!(a.output.as_ref().unwrap().routes.len(), 2);
+        assert!(results
+            .iter()
+            .any(|r| r.id == "c.toml" && r.error.is_some()));
+
+        // simulate an interrupted write of the last result
+        let mut contents = std::fs::read_to_string(&output).unwrap();
+        let last_start = contents.trim_end().rfind('\n').unwrap() + 1;
+        let last_id = serde_json::from_str::<BatchResult>(&contents[last_start..])
+            .unwrap()
+            .id;
+        contents.truncate(last_start + 10);
+        std::fs::write(&output, contents).unwrap();
+
+        let mut ids = vec![];
+        let summary = Batch::read(&problems_dir)
+            .unwrap()
+            .solve(&output, |result| ids.push(result.id.clone()))
+            .unwrap();
+        assert_eq!(ids, [last_id]);
+        assert_eq!(summary.skipped, 2);
+        assert_eq!(std::fs::read_to_string(&output).unwrap().lines().count(), 3);
+
+        _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_jsonl_ids() {
+        let dir = temp_dir("jsonl");
+        let json = serde_json::to_value(ProblemFile::new(&problems::uni_bag())).unwrap();
+        let mut with_id = json.clone();
+        with_id["id"] = "uni bag".into();
+        let input = dir.join("problems.jsonl");
+        std::fs::write(&input, format!("{json}\n\n{with_id}\n{{}}\n")).unwrap();
+
+        let batch = Batch::read(&input).unwrap();
+        let ids = batch
+            .problems()
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["line 1", "uni bag", "line 4"]);
+        assert!(batch.problems()[1].problem.is_ok());
+        assert!(batch.problems()[2].problem.is_err());
+
+        _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn panics_fail_the_problem() {
+        assert_eq!(catch_panic(|| Ok(1)), Ok(1));
+        assert_eq!(
+            catch_panic::<()>(|| panic!("index {} out of bounds", 4)),
+            Err("The solver panicked: index 4 out of bounds".to_string())
+        );
+        assert_eq!(
+            catch_panic::<()>(|| panic!("static message")),
+            Err("The solver panicked: static message".to_string())
+        );
+    }
+}
@@ -3,6 +3,8 @@ use std::{
     fmt::{self, Display, Formatter},
 };
 
+use crate::{MAX_GOALS, MAX_ITEMS_IN_GROUP};
+
 #[derive(Debug)]
 pub enum SynthError {
     /// A material was placed out of bounds
@@ -10,6 +12,22 @@ pub enum SynthError {
     /// A material was placed on top of another material while it is disallowed
     DisallowedOverlap,
     UnavailableTile,
+    /// A material overlapped other materials while the overlap limits were already reached
+    OverlapLimitReached,
+    /// A move placed a material that is not in the problem
+    UnknownMaterial,
+    /// A move placed a material that was already placed
+    DuplicateMaterial,
+    /// The problem has more than [`MAX_GOALS`] goals
+    TooManyGoals,
+    /// The problem doesn't have a material group for each goal
+    MismatchedMaterialGroups,
+    /// A material group has more than [`MAX_ITEMS_IN_GROUP`] materials
+    TooManyMaterials,
+    /// A material has a shape without any filled cells
+    EmptyShape,
+    /// The cauldron is not between 1x1 and 6x6, or doesn't have a tile for each position
+    InvalidCauldronSize,
 }
 
 impl Display for SynthError {
@@ -24,8 +42,47 @@ impl Display for SynthError {
                 f,
                 "A material was placed on an unavailable tile (ie. a hole)"
             ),
+            Self::OverlapLimitReached => write!(
+                f,
+                "A material overlapped other materials while the overlap limits were reached"
+            ),
+            Self::UnknownMaterial => write!(f, "A material that is not in the problem was placed"),
+            Self::DuplicateMaterial => write!(f, "A material was placed more than once"),
+            Self::TooManyGoals => write!(f, "A problem can have at most {MAX_GOALS} goals"),
+            Self::MismatchedMaterialGroups => {
+                write!(f, "A problem needs a material group for each goal")
+            }
+            Self::TooManyMaterials => write!(
+                f,
+                "A material group can have at most {MAX_ITEMS_IN_GROUP} materials"
+            ),
+            Self::EmptyShape => write!(f, "A material has an empty shape"),
+            Self::InvalidCauldronSize => write!(
+                f,
+                "A cauldron must be between 1x1 and 6x6 and have a tile for each position"
+            ),
         }
     }
 }
 
 impl Error for SynthError {}
+
+/// An error that occurs when parsing a cauldron from text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCauldronError {
+    /// The line of the error, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseCauldronError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid cauldron on line {}: {}",
+            self.line, self.message
+        )
+    }
+}
+
+impl Error for ParseCauldronError {}
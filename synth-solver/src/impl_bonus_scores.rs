@@ -0,0 +1,41 @@
+use crate::*;
+
+impl BonusScores {
+    /// Get the bonus score for a tile of the given level, when a material with the given effect value
+    /// is placed on it.
+    pub fn get(self, level: u32, effect_value: u32) -> f32 {
+        let (Self::Flat(level_1, level_2, level_3) | Self::Percentage(level_1, level_2, level_3)) =
+            self;
+
+        let score = match level {
+            0 => 0,
+            1 => level_1,
+            2 => level_2,
+            3 => level_3,
+            n => unreachable!("invalid tile level: {n}"),
+        };
+
+        match self {
+            Self::Flat(..) => score as f32,
+            Self::Percentage(..) => (effect_value * score) as f32 / 100.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bonus_scores() {
+        let flat = BonusScores::Flat(3, 5, 7);
+        assert_eq!(flat.get(0, 40), 0.);
+        assert_eq!(flat.get(1, 40), 3.);
+        assert_eq!(flat.get(3, 40), 7.);
+
+        let percentage = BonusScores::Percentage(10, 20, 30);
+        assert_eq!(percentage.get(0, 40), 0.);
+        assert_eq!(percentage.get(1, 40), 4.);
+        assert_eq!(percentage.get(3, 40), 12.);
+    }
+}
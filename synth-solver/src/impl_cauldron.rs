@@ -1,6 +1,7 @@
 use itertools::Itertools;
 use tinyvec::ArrayVec;
 
+use crate::rules::{SophieRules, SynthRules};
 use crate::solver::Move;
 use crate::{errors::SynthError, *};
 
@@ -34,13 +35,22 @@ impl Cauldron {
         &self,
         material_groups: &[Vec<Material>],
         score_sets: &[ColorScoreSet],
+    ) -> ArrayVec<[u32; MAX_GOALS]> {
+        self.calculate_final_score_with_rules(&SophieRules::default(), material_groups, score_sets)
+    }
+
+    pub fn calculate_final_score_with_rules(
+        &self,
+        rules: &impl SynthRules,
+        material_groups: &[Vec<Material>],
+        score_sets: &[ColorScoreSet],
     ) -> ArrayVec<[u32; MAX_GOALS]> {
         let coverage = self.calculate_coverage(material_groups);
 
         let scores = score_sets
             .iter()
             .enumerate()
-            .map(|(i, s)| s.calculate_score(&material_groups[i], &coverage, self))
+            .map(|(i, s)| s.calculate_score(&material_groups[i], &coverage, self, rules))
             .collect::<ArrayVec<[_; MAX_GOALS]>>();
 
         scores
@@ -50,15 +60,31 @@ impl Cauldron {
         &mut self,
         material_groups: &[Vec<Material>],
         moves: &[Move],
-        allow_overlap: bool,
+        overlap_limits: OverlapLimits,
+    ) -> Result<Vec<ColorScoreSet>, SynthError> {
+        self.place_all_with_rules(
+            &SophieRules::default(),
+            material_groups,
+            moves,
+            overlap_limits,
+        )
+    }
+
+    pub fn place_all_with_rules(
+        &mut self,
+        rules: &impl SynthRules,
+        material_groups: &[Vec<Material>],
+        moves: &[Move],
+        overlap_limits: OverlapLimits,
     ) -> Result<Vec<ColorScoreSet>, SynthError> {
         let mut scores = vec![ColorScoreSet::default(); material_groups.len()];
         for move_ in moves {
-            self.place(
+            self.place_with_rules(
+                rules,
                 material_groups,
                 move_.material_index,
                 move_.placement,
-                allow_overlap,
+                overlap_limits,
                 &mut scores,
             )?;
         }
@@ -71,7 +97,26 @@ impl Cauldron {
         material_groups: &[Vec<Material>],
         material_index: (usize, usize),
         placement: Placement,
-        allow_overlap: bool,
+        overlap_limits: OverlapLimits,
+        scores: &mut [ColorScoreSet],
+    ) -> Result<(), SynthError> {
+        self.place_with_rules(
+            &SophieRules::default(),
+            material_groups,
+            material_index,
+            placement,
+            overlap_limits,
+            scores,
+        )
+    }
+
+    pub fn place_with_rules(
+        &mut self,
+        rules: &impl SynthRules,
+        material_groups: &[Vec<Material>],
+        material_index: (usize, usize),
+        placement: Placement,
+        overlap_limits: OverlapLimits,
         scores: &mut [ColorScoreSet],
     ) -> Result<(), SynthError> {
         debug_assert_eq!(material_groups.len(), scores.len());
@@ -89,57 +134,62 @@ impl Cauldron {
             return Err(SynthError::OutOfBounds);
         }
 
-        // apply the shape to the playfield and count score
+        rules.validate_placement(self, shape, (placement_x, placement_y))?;
+
+        // the tiles that the shape covers. holes that are allowed by the rules are skipped
+        let positions = (0..Shape::HEIGHT)
+            .cartesian_product(0..Shape::WIDTH)
+            .filter(|&(shape_y, shape_x)| shape.get(shape_x, shape_y))
+            .map(|(shape_y, shape_x)| (placement_x + shape_x, placement_y + shape_y))
+            .filter(|&position| self.get_tile(position).is_some())
+            .collect::<ArrayVec<[_; 9]>>();
+
+        // find the overlapped materials before changing any tiles, so a placement that is not
+        // allowed leaves the cauldron untouched
         let mut materials_to_remove = ArrayVec::<[_; 9]>::new();
-        let mut score = 0.;
-        for (shape_y, shape_x) in (0..Shape::HEIGHT).cartesian_product(0..Shape::WIDTH) {
-            if shape.get(shape_x, shape_y) {
-                // copy value to avoid borrow checker issues
-                let bonus_scores = self.bonus_scores;
-                let properties = self.properties;
-                let color = self.color;
-
-                let Some(tile) = self
-                    .get_tile_mut((placement_x + shape_x, placement_y + shape_y))
-                    .as_mut()
-                else {
-                    return Err(SynthError::UnavailableTile);
-                };
-
-                if tile.played_material_index.is_some() && !allow_overlap {
-                    return Err(SynthError::DisallowedOverlap);
-                }
+        for &position in &positions {
+            let tile = self.get_tile(position).expect("tile should exist");
+            let Some(material_index) = tile.played_material_index else {
+                continue;
+            };
 
-                let mut bonus_multiplier = 1.0;
+            if !overlap_limits.allows_overlaps() {
+                return Err(SynthError::DisallowedOverlap);
+            }
 
-                // matching colors give a 50% bonus to bonus score
-                if material.color == tile.color {
-                    bonus_multiplier *= 1.5;
+            // a material can cover multiple tiles of the same material
+            if !materials_to_remove.contains(&material_index) {
+                if self.overlaps >= overlap_limits.max_overlaps
+                    || materials_to_remove.len() >= overlap_limits.max_materials_per_overlap
+                {
+                    return Err(SynthError::OverlapLimitReached);
                 }
 
-                // the Synergy trait gives an additional 50% bonus when the tile color matches the cauldron color
-                if properties.contains(CauldronProperties::SYNERGY) && color == tile.color {
-                    bonus_multiplier *= 1.5;
-                }
+                materials_to_remove.push(material_index);
+            }
+        }
 
-                score += match tile.level {
-                    0 => 0.,
-                    1 => bonus_scores.0 as f32 * bonus_multiplier,
-                    2 => bonus_scores.1 as f32 * bonus_multiplier,
-                    3 => bonus_scores.2 as f32 * bonus_multiplier,
-                    n => unreachable!("invalid tile level: {n}"),
-                };
+        // apply the shape to the playfield and count score
+        let mut score = 0.;
+        for &position in &positions {
+            // copy value to avoid borrow checker issues
+            let bonus_scores = self.bonus_scores;
+            let tile = self.get_tile(position).expect("tile should exist");
+            let bonus_multiplier = rules.bonus_multiplier(self, material, &tile);
 
-                let material_index_before_placement = tile.played_material_index;
+            let tile = self
+                .get_tile_mut(position)
+                .as_mut()
+                .expect("tile should exist");
 
-                tile.played_material_index = Some(material_index);
-                tile.level = 0;
+            score += bonus_scores.get(tile.level, material.effect_value) * bonus_multiplier;
 
-                if let Some(material_index) = material_index_before_placement {
-                    debug_assert!(allow_overlap); // checked earlier
-                    materials_to_remove.push(material_index);
-                }
-            }
+            tile.played_material_index = Some(material_index);
+            tile.level = 0;
+        }
+
+        if !materials_to_remove.is_empty() {
+            self.overlaps += 1;
         }
         // score is truncated into an integer
         let score = score as u32;
@@ -160,15 +210,7 @@ impl Cauldron {
             let tile = self.get_tile_mut((position_x as usize, position_y as usize));
 
             if let Some(tile) = tile {
-                // tiles that are already played cannot be updated
-                if tile.played_material_index.is_some() {
-                    continue;
-                }
-
-                // tiles can only go up to level 3
-                if tile.level < 3 {
-                    tile.level += 1;
-                }
+                rules.level_up_neighbour(tile);
             }
         }
 
@@ -189,6 +231,38 @@ impl Cauldron {
         Ok(())
     }
 
+    /// Predict the quality of the synthesized item, based on the materials that are currently in
+    /// the cauldron.
+    pub fn calculate_final_quality(
+        &self,
+        material_groups: &[Vec<Material>],
+        quality_model: QualityModel,
+    ) -> u32 {
+        let mut placed_materials = ArrayVec::<[(usize, usize); MAX_ITEMS]>::new();
+        for material_index in self
+            .tiles
+            .iter()
+            .filter_map(|t| t.as_ref())
+            .filter_map(|t| t.played_material_index)
+        {
+            if !placed_materials.contains(&material_index) {
+                placed_materials.push(material_index);
+            }
+        }
+
+        if placed_materials.is_empty() {
+            return 0;
+        }
+
+        let total_quality = placed_materials
+            .iter()
+            .map(|&(i1, i2)| material_groups[i1][i2].quality)
+            .sum::<u32>();
+        let average_quality = total_quality / placed_materials.len() as u32;
+
+        (average_quality + quality_model.quality_bonus).min(quality_model.max_quality)
+    }
+
     pub fn calculate_coverage(&self, material_groups: &[Vec<Material>]) -> CoverageInfo {
         self.tiles
             .iter()
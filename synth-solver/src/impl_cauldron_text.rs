@@ -0,0 +1,246 @@
+//! A plain text format for cauldrons, so boards can be written in tests and bug reports and shown
+//! in text output.
+//!
+//! ```text
+//! color: white
+//! bonus_scores: flat 3 5 7
+//! properties: synergy
+//! B0 G0     Y0 --
+//! W0 Y0:0.1 Y1 Y0
+//! R0 Y0:0.1 R1 R0
+//! W0 Y2:0.1 Y0 Y0
+//! ```
+//!
+//! Each row of the board is a line of tiles separated by whitespace. A tile is its color (`R`,
+//! `B`, `G`, `Y` or `W`) followed by its bonus level, and optionally `:` and the material that was
+//! placed on it as `GROUP.INDEX`. A hole is written as `--`.
+//!
+//! The lines before the board are optional and default to a white cauldron without bonus scores,
+//! properties or overlaps. Bonus scores are written as `flat` or `percentage` followed by the
+//! values for each level, and `overlaps` is the amount of overlaps so far. Empty lines and lines
+//! starting with `#` are ignored.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use tinyvec::ArrayVec;
+
+use crate::{errors::ParseCauldronError, *};
+
+pub(crate) const PROPERTY_NAMES: [(CauldronProperties, &str); 4] = [
+    (CauldronProperties::COMBINED_EFFECT, "combined_effect"),
+    (CauldronProperties::SYNERGY, "synergy"),
+    (CauldronProperties::TUNING, "tuning"),
+    (CauldronProperties::COLOR_SURROUNDINGS, "color_surroundings"),
+];
+
+impl Display for Cauldron {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "color: {}", self.color.name())?;
+        match self.bonus_scores {
+            BonusScores::Flat(a, b, c) => writeln!(f, "bonus_scores: flat {a} {b} {c}")?,
+            BonusScores::Percentage(a, b, c) => {
+                writeln!(f, "bonus_scores: percentage {a} {b} {c}")?;
+            }
+        }
+        if !self.properties.is_empty() {
+            writeln!(
+                f,
+                "properties: {}",
+                property_names(self.properties).join(" ")
+            )?;
+        }
+        if self.overlaps != 0 {
+            writeln!(f, "overlaps: {}", self.overlaps)?;
+        }
+
+        // pad the tiles so the columns line up when materials were placed
+        let tiles = self
+            .tiles
+            .iter()
+            .copied()
+            .map(format_tile)
+            .collect::<Vec<_>>();
+        let width = tiles.iter().map(String::len).max().unwrap_or(0);
+        for row in tiles.chunks(self.size) {
+            let line = row
+                .iter()
+                .map(|tile| format!("{tile:width$}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cauldron {
+    type Err = ParseCauldronError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cauldron = Self {
+            size: 0,
+            tiles: ArrayVec::new(),
+            bonus_scores: BonusScores::Flat(0, 0, 0),
+            color: Color::White,
+            properties: CauldronProperties::empty(),
+            overlaps: 0,
+        };
+        let mut rows = vec![];
+
+        for (i, line) in s.lines().enumerate() {
+            let error = |message: String| ParseCauldronError {
+                line: i + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line
+                .split_once(':')
+                .map(|(key, value)| (key.trim(), value.trim()))
+            {
+                Some(("color", value)) => {
+                    cauldron.color = Color::from_name(value)
+                        .ok_or_else(|| error(format!("unknown color {value:?}")))?;
+                }
+                Some(("bonus_scores", value)) => {
+                    cauldron.bonus_scores = parse_bonus_scores(value).ok_or_else(|| {
+                        error(format!(
+                            "expected bonus scores like \"flat 3 5 7\", got {value:?}"
+                        ))
+                    })?;
+                }
+                Some(("properties", value)) => {
+                    cauldron.properties = value
+                        .split_whitespace()
+                        .map(|name| {
+                            parse_property(name)
+                                .ok_or_else(|| error(format!("unknown property {name:?}")))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                Some(("overlaps", value)) => {
+                    cauldron.overlaps = value
+                        .parse()
+                        .map_err(|_| error(format!("invalid overlaps {value:?}")))?;
+                }
+                _ => rows.push((i + 1, line)),
+            }
+        }
+
+        cauldron.size = rows.len();
+        if !(1..=6).contains(&cauldron.size) {
+            return Err(ParseCauldronError {
+                line: s.lines().count(),
+                message: format!("expected 1 to 6 rows of tiles, got {}", cauldron.size),
+            });
+        }
+        for (line, row) in rows {
+            let row = parse_row(row, cauldron.size)
+                .map_err(|message| ParseCauldronError { line, message })?;
+            cauldron.tiles.extend(row);
+        }
+
+        Ok(cauldron)
+    }
+}
+
+pub(crate) fn format_tile(tile: Option<Tile>) -> String {
+    match tile {
+        None => "--".to_string(),
+        Some(Tile {
+            color,
+            level,
+            played_material_index: None,
+        }) => format!("{}{level}", color.to_char()),
+        Some(Tile {
+            color,
+            level,
+            played_material_index: Some((group, index)),
+        }) => format!("{}{level}:{group}.{index}", color.to_char()),
+    }
+}
+
+/// Parse a row of `size` tiles separated by whitespace.
+pub(crate) fn parse_row(row: &str, size: usize) -> Result<Vec<Option<Tile>>, String> {
+    let tiles = row
+        .split_whitespace()
+        .map(|tile| parse_tile(tile).ok_or_else(|| format!("invalid tile {tile:?}")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if tiles.len() != size {
+        return Err(format!(
+            "expected {size} tiles in row {row:?}, got {}",
+            tiles.len()
+        ));
+    }
+    Ok(tiles)
+}
+
+fn parse_tile(s: &str) -> Option<Option<Tile>> {
+    if s == "--" {
+        return Some(None);
+    }
+
+    let (tile, material) = match s.split_once(':') {
+        Some((tile, material)) => (tile, Some(material)),
+        None => (s, None),
+    };
+
+    let mut chars = tile.chars();
+    let (Some(color), Some(level), None) = (chars.next(), chars.next(), chars.next()) else {
+        return None;
+    };
+    let played_material_index = match material {
+        Some(material) => {
+            let (group, index) = material.split_once('.')?;
+            Some((group.parse().ok()?, index.parse().ok()?))
+        }
+        None => None,
+    };
+
+    Some(Some(Tile {
+        color: Color::from_char(color)?,
+        level: level.to_digit(10).filter(|level| *level <= 3)?,
+        played_material_index,
+    }))
+}
+
+fn parse_bonus_scores(s: &str) -> Option<BonusScores> {
+    let parts = s.split_whitespace().collect::<Vec<_>>();
+    let [kind, a, b, c] = parts[..] else {
+        return None;
+    };
+    let (a, b, c) = (a.parse().ok()?, b.parse().ok()?, c.parse().ok()?);
+
+    match kind {
+        "flat" => Some(BonusScores::Flat(a, b, c)),
+        "percentage" => Some(BonusScores::Percentage(a, b, c)),
+        _ => None,
+    }
+}
+
+/// The names of the properties, using `combined_effect` when all of its properties are set.
+pub(crate) fn property_names(properties: CauldronProperties) -> Vec<&'static str> {
+    let mut remaining = properties;
+    let mut names = vec![];
+    for (property, name) in PROPERTY_NAMES {
+        if remaining.contains(property) {
+            remaining.remove(property);
+            names.push(name);
+        }
+    }
+    names
+}
+
+pub(crate) fn parse_property(name: &str) -> Option<CauldronProperties> {
+    PROPERTY_NAMES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|&(property, _)| property)
+}
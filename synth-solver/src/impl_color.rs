@@ -32,4 +32,43 @@ impl Color {
             _ => panic!("Invalid color tag {tag}"),
         }
     }
+
+    /// The letter of this color, as used in text boards, eg. `R` for red.
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Red => 'R',
+            Self::Blue => 'B',
+            Self::Green => 'G',
+            Self::Yellow => 'Y',
+            Self::White => 'W',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            'R' => Self::Red,
+            'B' => Self::Blue,
+            'G' => Self::Green,
+            'Y' => Self::Yellow,
+            'W' => Self::White,
+            _ => return None,
+        })
+    }
+
+    /// The name of this color in lowercase, eg. `red`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Red => "red",
+            Self::Blue => "blue",
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::White => "white",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        (0..5)
+            .map(Self::from_index)
+            .find(|color| color.name() == name)
+    }
 }
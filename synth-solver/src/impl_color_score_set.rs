@@ -1,4 +1,4 @@
-use crate::{Cauldron, Color, ColorScoreSet, CoverageInfo, Material};
+use crate::{rules::SynthRules, Cauldron, Color, ColorScoreSet, CoverageInfo, Material};
 
 impl ColorScoreSet {
     pub fn get(&self, color: Color) -> u32 {
@@ -24,6 +24,7 @@ impl ColorScoreSet {
         items: &[Material],
         coverage: &CoverageInfo,
         playfield: &Cauldron,
+        rules: &impl SynthRules,
     ) -> u32 {
         self.into_iter()
             .map(|(color, color_score)| {
@@ -32,8 +33,8 @@ impl ColorScoreSet {
                     .filter(|i| i.color == color)
                     .map(|i| i.effect_value)
                     .sum::<u32>();
-                let ratio = coverage.get_color_ratio_conditional(color, playfield);
-                (base + color_score) as f32 * (1. + ratio)
+                let multiplier = rules.coverage_multiplier(playfield, coverage, color);
+                (base + color_score) as f32 * multiplier
             })
             .map(|f| f as u32)
             .sum()
@@ -17,9 +17,34 @@ impl CoverageInfo {
     }
 
     pub fn get_color_ratio_conditional(&self, color: Color, playfield: &Cauldron) -> f32 {
-        let is_max = self.coverage.iter().max() == Some(&self.get_color(color));
+        self.get_color_ratio_with_tie_rule(color, playfield, CoverageTieRule::default())
+    }
+
+    /// Get the coverage ratio of a color if it has the highest coverage, using `tie_rule` to
+    /// decide what happens when multiple colors have the highest coverage.
+    pub fn get_color_ratio_with_tie_rule(
+        &self,
+        color: Color,
+        playfield: &Cauldron,
+        tie_rule: CoverageTieRule,
+    ) -> f32 {
+        let max = self.coverage.iter().max().copied().unwrap_or_default();
+        if self.get_color(color) != max {
+            return 0.;
+        }
+
+        let is_tied = self.coverage.iter().filter(|&&c| c == max).count() > 1;
+        let gets_bonus = !is_tied
+            || match tie_rule {
+                CoverageTieRule::AllTied => true,
+                CoverageTieRule::None => false,
+                CoverageTieRule::FirstByColorIndex => {
+                    self.coverage.iter().position(|&c| c == max) == Some(color.get_index())
+                }
+                CoverageTieRule::CauldronColor => playfield.color == color,
+            };
 
-        if is_max {
+        if gets_bonus {
             self.get_color_ratio(color, playfield)
         } else {
             0.
@@ -1,11 +1,18 @@
 use crate::*;
 
 impl Material {
-    pub fn new(color: Color, quality: u32, shape: Shape) -> Self {
+    pub fn new(color: Color, effect_value: u32, shape: Shape) -> Self {
         Self {
             color,
-            effect_value: quality,
+            effect_value,
             shape,
+            quality: 0,
         }
     }
+
+    #[must_use]
+    pub fn with_quality(mut self, quality: u32) -> Self {
+        self.quality = quality;
+        self
+    }
 }
@@ -0,0 +1,19 @@
+use crate::*;
+
+impl OverlapLimits {
+    /// Disallow all overlaps.
+    pub const NONE: Self = Self {
+        max_overlaps: 0,
+        max_materials_per_overlap: 0,
+    };
+
+    /// Allow any amount of overlaps.
+    pub const UNLIMITED: Self = Self {
+        max_overlaps: usize::MAX,
+        max_materials_per_overlap: usize::MAX,
+    };
+
+    pub fn allows_overlaps(self) -> bool {
+        self.max_overlaps > 0 && self.max_materials_per_overlap > 0
+    }
+}
@@ -0,0 +1,10 @@
+use crate::*;
+
+impl Default for QualityModel {
+    fn default() -> Self {
+        Self {
+            quality_bonus: 0,
+            max_quality: 999,
+        }
+    }
+}
@@ -70,6 +70,11 @@ impl Shape {
         self.0 & (1 << index) != 0
     }
 
+    /// Whether none of the cells are filled.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
     pub fn get_neighbours(self) -> ShapeNeighbours {
         NEIGHBOUR_CACHE.get_or_init(|| {
             // technically we're allocating more than we need, since we'll always work with
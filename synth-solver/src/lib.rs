@@ -10,15 +10,26 @@
 
 pub use tinyvec;
 
+#[cfg(all(feature = "serde", not(target_arch = "wasm32")))]
+pub mod batch;
 pub mod errors;
+mod impl_bonus_scores;
 mod impl_cauldron;
+mod impl_cauldron_text;
 mod impl_color;
 mod impl_color_score_set;
 mod impl_coverage;
 mod impl_material;
+mod impl_overlap_limits;
 mod impl_placement;
+mod impl_quality_model;
 mod impl_shape;
 mod impl_shape_neighbours;
+#[cfg(feature = "serde")]
+pub mod output_file;
+#[cfg(feature = "serde")]
+pub mod problem_file;
+pub mod rules;
 pub mod solver;
 pub mod utils;
 
@@ -33,6 +44,11 @@ pub const MAX_ITEMS: usize = MAX_GOALS * MAX_ITEMS_IN_GROUP;
 // TODO: what is the actual practical amount of items that can be in a game?
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Color {
     /// Fire
     Red,
@@ -48,6 +64,7 @@ pub enum Color {
 
 /// The placement of a material on the playfield.
 #[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Placement {
     /// The index in the playfield where the item is placed.
     pub index: usize,
@@ -62,17 +79,32 @@ pub struct Cauldron {
     /// The individual tiles of a cauldron, or None if the tile is a hole.
     pub tiles: tinyvec::ArrayVec<[Option<Tile>; 6 * 6]>, // TODO: maybe use const generic? size should be 4x4, 5x5 or 6x6
     /// The bonus scores for each level of a tile.
-    // TODO: some cauldrons have percentages instead of fixed values
-    pub bonus_scores: (u32, u32, u32),
+    pub bonus_scores: BonusScores,
     /// The color of the item being crafted. This is used when this cauldron has the [`CauldronProperties::SYNERGY`] property.
     pub color: Color,
     /// The properties of the cauldron.
     pub properties: CauldronProperties,
+    /// The amount of overlaps that happened in this cauldron so far.
+    pub overlaps: usize,
+}
+
+/// The bonus scores a cauldron gives for each level of a tile, starting at level 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum BonusScores {
+    /// A fixed amount of score for each level.
+    Flat(u32, u32, u32),
+    /// A percentage of the effect value of the placed material for each level.
+    Percentage(u32, u32, u32),
 }
 
 bitflags::bitflags! {
     /// Optional properties for the cauldron that change how score may be calculated.
-    #[derive(Copy, Clone, Default)]
+    #[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
     pub struct CauldronProperties: u32 {
         /// Obtaining bonuses that are the same color as the liquid in the cauldron increases effect by 50%.
         const SYNERGY = 0b001;
@@ -88,6 +120,11 @@ bitflags::bitflags! {
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum TransformationType {
     #[default]
     None,
@@ -96,8 +133,23 @@ pub enum TransformationType {
     Rotate,
 }
 
+/// Limits on how materials may overlap each other during a synthesis.
+///
+/// There is no limit on how many times a single material may be overlapped, because an overlapped
+/// material is removed from the cauldron and can't be overlapped again. Instead,
+/// `max_materials_per_overlap` limits how many materials a single placement may remove.
+///
+/// The default value does not allow any overlaps.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OverlapLimits {
+    /// The max amount of placements that may overlap other materials in a single synthesis.
+    pub max_overlaps: usize,
+    /// The max amount of already placed materials that a single placement may overlap.
+    pub max_materials_per_overlap: usize,
+}
+
 /// A tile in the cauldron's playfield.
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Tile {
     pub color: Color,
     /// The bonus level of the tile. Value between 0 and 3 inclusive.
@@ -117,6 +169,8 @@ pub struct Material {
     pub effect_value: u32,
     /// The shape of this item.
     pub shape: Shape,
+    /// The quality of this item, which is used to predict the quality of the synthesized item.
+    pub quality: u32,
 }
 
 /// A shape in a 3x3 grid.
@@ -127,6 +181,11 @@ pub struct Shape(u16);
 pub struct ShapeNeighbours(u32);
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Transformation {
     FlipHorizontal,
     FlipVertical,
@@ -135,6 +194,20 @@ pub enum Transformation {
     Rotate270,
 }
 
+/// A model to predict the quality of a synthesized item.
+///
+/// The predicted quality is the average quality of the materials that are still in the cauldron
+/// after all placements, so materials that were overlapped do not contribute to it. The bonus is
+/// added to that average and the result is capped at the max quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityModel {
+    /// A flat bonus that is added to the quality, eg. from the cauldron.
+    pub quality_bonus: u32,
+    /// The highest quality an item can have.
+    pub max_quality: u32,
+}
+
 /// An item effect that can be reached by getting certain item effect levels.
 #[derive(Debug, Clone)]
 pub struct Goal {
@@ -153,6 +226,30 @@ pub struct CoverageInfo {
     coverage: [u32; 5],
 }
 
+/// Decides which colors receive the coverage bonus when multiple colors are tied for the highest
+/// coverage.
+///
+/// A color that has the highest coverage on its own always receives the coverage bonus.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CoverageTieRule {
+    /// All tied colors receive the coverage bonus. This is the closest to the one synthesis with a
+    /// tie that was recorded in-game, but it doesn't reproduce it exactly, so none of the rules
+    /// are verified yet.
+    #[default]
+    AllTied,
+    /// None of the tied colors receive the coverage bonus.
+    None,
+    /// Only the tied color with the lowest color index receives the coverage bonus.
+    FirstByColorIndex,
+    /// Only the color of the liquid in the cauldron receives the coverage bonus, if it is tied.
+    CauldronColor,
+}
+
 /// The effect value score for each color of an item group/a single goal, before item bonus and
 /// coverage is applied.
 #[derive(Default, Clone, Copy, Debug)]
@@ -0,0 +1,169 @@
+//! A JSON format for the routes found by the solver, so they can be stored, compared between solver
+//! versions and read by other tools.
+//!
+//! # Schema
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "solver_version": "0.0.0",
+//!   "routes": [
+//!     {
+//!       "goal_result": { "achieved_goals": [1, 1, 1], "quality": null },
+//!       "moves": [
+//!         {
+//!           "material_index": [0, 1],
+//!           "placement": { "index": 7, "transformation": "rotate90" }
+//!         }
+//!       ],
+//!       "overlaps": 0
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! - `schema_version` is [`SCHEMA_VERSION`]. It is increased whenever a field is removed or its
+//!   meaning changes, and files with a different version are rejected.
+//! - `solver_version` is the version of this crate that wrote the file, for information only.
+//! - `achieved_goals` has the amount of thresholds that are met for each goal, and `quality` is
+//!   the predicted quality if quality was optimized, otherwise `null`.
+//! - `material_index` is the index of the material group and the index of the material within it.
+//! - `index` is the index of the tile in the cauldron, counted row by row from the top left.
+//! - `transformation` is `null`, `flip_horizontal`, `flip_vertical`, `rotate90`, `rotate180` or
+//!   `rotate270`.
+//! - `overlaps` is the amount of placements that overlapped other materials.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::solver::{SolverResult, SolverRoute};
+
+/// The version of the schema that is written, see the [module documentation](self).
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The routes found by the solver, as they are stored in a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputFile {
+    pub schema_version: u32,
+    pub solver_version: String,
+    pub routes: Vec<SolverRoute>,
+}
+
+impl OutputFile {
+    pub fn new(result: &SolverResult) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            solver_version: env!("CARGO_PKG_VERSION").to_string(),
+            routes: result.clone(),
+        }
+    }
+
+    /// Read routes from JSON, checking that they were written with the current schema.
+    pub fn from_json(json: &str) -> Result<Self, OutputFileError> {
+        #[derive(Deserialize)]
+        struct Version {
+            schema_version: u32,
+        }
+
+        // check the version first, since other versions may not parse as this one
+        let version = serde_json::from_str::<Version>(json)
+            .map_err(|e| OutputFileError::Parse(e.to_string()))?;
+        if version.schema_version != SCHEMA_VERSION {
+            return Err(OutputFileError::UnsupportedVersion(version.schema_version));
+        }
+
+        serde_json::from_str(json).map_err(|e| OutputFileError::Parse(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("routes can always be serialized")
+    }
+}
+
+/// An error that occurs when reading solver output.
+#[derive(Debug)]
+pub enum OutputFileError {
+    /// The output is not valid JSON or does not match the schema
+    Parse(String),
+    /// The output was written with a different schema version
+    UnsupportedVersion(u32),
+}
+
+impl Display for OutputFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "Failed to parse solver output: {e}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Solver output has schema version {version}, but only version {SCHEMA_VERSION} is supported"
+            ),
+        }
+    }
+}
+
+impl Error for OutputFileError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{solver::*, utils::test_data::problems, *};
+
+    fn route() -> SolverRoute {
+        SolverRoute {
+            goal_result: GoalResult {
+                achieved_goals: [1, 1, 1].into_iter().collect(),
+                quality: None,
+            },
+            moves: [Move {
+                material_index: (0, 1),
+                placement: Placement::new(7, Some(Transformation::Rotate90)),
+            }]
+            .into_iter()
+            .collect(),
+            overlaps: 0,
+        }
+    }
+
+    #[test]
+    fn schema() {
+        let json = serde_json::to_value(OutputFile::new(&vec![route()])).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "schema_version": 1,
+                "solver_version": env!("CARGO_PKG_VERSION"),
+                "routes": [{
+                    "goal_result": { "achieved_goals": [1, 1, 1], "quality": null },
+                    "moves": [{
+                        "material_index": [0, 1],
+                        "placement": { "index": 7, "transformation": "rotate90" },
+                    }],
+                    "overlaps": 0,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let routes = problems::uni_bag().solve(None).unwrap();
+        let json = OutputFile::new(&routes).to_json();
+        let parsed = OutputFile::from_json(&json).unwrap();
+
+        assert_eq!(parsed.routes.len(), routes.len());
+        for (parsed, route) in parsed.routes.iter().zip(&routes) {
+            assert_eq!(parsed.goal_result, route.goal_result);
+            assert_eq!(format!("{parsed:?}"), format!("{route:?}"));
+        }
+
+        let json = json.replace("\"schema_version\": 1", "\"schema_version\": 2");
+        assert!(matches!(
+            OutputFile::from_json(&json),
+            Err(OutputFileError::UnsupportedVersion(2))
+        ));
+    }
+}
@@ -0,0 +1,506 @@
+//! A human-editable file format for [`SolverProblem`], in TOML or JSON.
+//!
+//! Tiles and shapes are written as rows of text so a file looks like the board it describes:
+//!
+//! ```toml
+//! goals = [[50, 100], [30, 50]]
+//!
+//! [cauldron]
+//! size = 4
+//! tiles = [
+//!     "B0 G0 Y0 --",
+//!     "W0 Y0 Y1 Y0",
+//!     "R0 Y0 R1 R0",
+//!     "W0 Y2 Y0 Y0",
+//! ]
+//! bonus_scores = { flat = [3, 5, 7] }
+//! color = "white"
+//! properties = []
+//!
+//! [[material_groups]]
+//! [[material_groups.materials]]
+//! color = "yellow"
+//! effect_value = 15
+//! shape = ["X..", "X..", "X.."]
+//!
+//! [[material_groups]]
+//! [[material_groups.materials]]
+//! color = "white"
+//! effect_value = 10
+//! shape = ["XX.", "X..", "..."]
+//! quality = 40
+//!
+//! [settings]
+//! transformations = "rotate"
+//! max_overlaps = "unlimited"
+//! max_materials_per_overlap = 1
+//! coverage_tie_rule = "all_tied"
+//! ```
+//!
+//! A tile is its color (`R`, `B`, `G`, `Y` or `W`) followed by its bonus level, or `--` for a
+//! hole. A shape has an `X` for each filled cell.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{impl_cauldron_text::*, solver::*, *};
+
+/// A [`SolverProblem`] as it is stored in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProblemFile {
+    /// The tag of the item that is crafted, if the problem was created from game data. The solver
+    /// ignores this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+    /// The tag of the kettle that is used, if the problem was created from game data. The solver
+    /// ignores this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kettle: Option<String>,
+    /// The effect value thresholds of each goal.
+    pub goals: Vec<Vec<u32>>,
+    pub cauldron: CauldronFile,
+    /// The materials, grouped by the goal they contribute to.
+    pub material_groups: Vec<MaterialGroupFile>,
+    #[serde(default)]
+    pub settings: SettingsFile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CauldronFile {
+    pub size: usize,
+    /// A row of space-separated tiles for each row of the cauldron.
+    pub tiles: Vec<String>,
+    pub bonus_scores: BonusScores,
+    pub color: Color,
+    /// The names of the properties, such as `synergy`.
+    #[serde(default)]
+    pub properties: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaterialGroupFile {
+    pub materials: Vec<MaterialFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaterialFile {
+    pub color: Color,
+    pub effect_value: u32,
+    /// Three rows of three cells.
+    pub shape: [String; 3],
+    #[serde(default)]
+    pub quality: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsFile {
+    #[serde(default)]
+    pub transformations: TransformationType,
+    #[serde(default, with = "limit")]
+    pub max_overlaps: usize,
+    #[serde(default, with = "limit")]
+    pub max_materials_per_overlap: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_model: Option<QualityModel>,
+    #[serde(default)]
+    pub coverage_tie_rule: CoverageTieRule,
+}
+
+impl ProblemFile {
+    pub fn new(problem: &SolverProblem) -> Self {
+        let SolverProblem {
+            cauldron,
+            materials,
+            goals,
+            settings,
+        } = problem;
+
+        Self {
+            item: None,
+            kettle: None,
+            goals: goals
+                .iter()
+                .map(|goal| goal.effect_value_thresholds.clone())
+                .collect(),
+            cauldron: CauldronFile {
+                size: cauldron.size,
+                tiles: format_tiles(cauldron),
+                bonus_scores: cauldron.bonus_scores,
+                color: cauldron.color,
+                properties: property_names(cauldron.properties)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect(),
+            },
+            material_groups: materials
+                .iter()
+                .map(|group| MaterialGroupFile {
+                    materials: group
+                        .iter()
+                        .map(|material| MaterialFile {
+                            color: material.color,
+                            effect_value: material.effect_value,
+                            shape: format_shape(material.shape),
+                            quality: material.quality,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            settings: SettingsFile {
+                transformations: settings.transformations,
+                max_overlaps: settings.overlap_limits.max_overlaps,
+                max_materials_per_overlap: settings.overlap_limits.max_materials_per_overlap,
+                quality_model: settings.quality_model,
+                coverage_tie_rule: settings.coverage_tie_rule,
+            },
+        }
+    }
+
+    /// Set the tags of the item and kettle that the problem was created for.
+    #[must_use]
+    pub fn with_tags(self, item: impl Into<String>, kettle: impl Into<String>) -> Self {
+        Self {
+            item: Some(item.into()),
+            kettle: Some(kettle.into()),
+            ..self
+        }
+    }
+
+    /// Convert this file to a problem, checking that the tiles, shapes and properties are valid.
+    pub fn to_problem(&self) -> Result<SolverProblem, ProblemFileError> {
+        if self.goals.len() != self.material_groups.len() {
+            return Err(ProblemFileError::Invalid(format!(
+                "expected a material group for each of the {} goals, but got {}",
+                self.goals.len(),
+                self.material_groups.len()
+            )));
+        }
+
+        let problem = SolverProblem {
+            cauldron: Cauldron {
+                size: self.cauldron.size,
+                tiles: parse_tiles(self.cauldron.size, &self.cauldron.tiles)?,
+                bonus_scores: self.cauldron.bonus_scores,
+                color: self.cauldron.color,
+                properties: parse_properties(&self.cauldron.properties)?,
+                overlaps: 0,
+            },
+            materials: self
+                .material_groups
+                .iter()
+                .map(|group| {
+                    group
+                        .materials
+                        .iter()
+                        .map(|material| {
+                            Ok(Material {
+                                color: material.color,
+                                effect_value: material.effect_value,
+                                shape: parse_shape(&material.shape)?,
+                                quality: material.quality,
+                            })
+                        })
+                        .collect()
+                })
+                .collect::<Result<_, _>>()?,
+            goals: self
+                .goals
+                .iter()
+                .map(|thresholds| Goal {
+                    effect_value_thresholds: thresholds.clone(),
+                })
+                .collect(),
+            settings: SolverSettings {
+                transformations: self.settings.transformations,
+                overlap_limits: OverlapLimits {
+                    max_overlaps: self.settings.max_overlaps,
+                    max_materials_per_overlap: self.settings.max_materials_per_overlap,
+                },
+                quality_model: self.settings.quality_model,
+                coverage_tie_rule: self.settings.coverage_tie_rule,
+            },
+        };
+        problem
+            .validate()
+            .map_err(|e| ProblemFileError::Invalid(e.to_string()))?;
+        Ok(problem)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, ProblemFileError> {
+        toml::from_str(toml).map_err(|e| ProblemFileError::Parse(e.to_string()))
+    }
+
+    pub fn to_toml(&self) -> Result<String, ProblemFileError> {
+        toml::to_string(self).map_err(|e| ProblemFileError::Serialize(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ProblemFileError> {
+        serde_json::from_str(json).map_err(|e| ProblemFileError::Parse(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> Result<String, ProblemFileError> {
+        serde_json::to_string_pretty(self).map_err(|e| ProblemFileError::Serialize(e.to_string()))
+    }
+
+    /// Read a problem file. Files with a `.json` extension are read as JSON, others as TOML.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ProblemFileError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(ProblemFileError::Io)?;
+        if is_json(path) {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        }
+    }
+
+    /// Write a problem file. Files with a `.json` extension are written as JSON, others as TOML.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ProblemFileError> {
+        let path = path.as_ref();
+        let contents = if is_json(path) {
+            self.to_json()?
+        } else {
+            self.to_toml()?
+        };
+        std::fs::write(path, contents).map_err(ProblemFileError::Io)
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+fn format_tiles(cauldron: &Cauldron) -> Vec<String> {
+    cauldron
+        .tiles
+        .chunks(cauldron.size)
+        .map(|row| {
+            row.iter()
+                .copied()
+                .map(format_tile)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+fn parse_tiles(
+    size: usize,
+    rows: &[String],
+) -> Result<tinyvec::ArrayVec<[Option<Tile>; 6 * 6]>, ProblemFileError> {
+    if size * size > 6 * 6 {
+        return Err(ProblemFileError::Invalid(format!(
+            "cauldron size {size} is too large"
+        )));
+    }
+    if rows.len() != size {
+        return Err(ProblemFileError::Invalid(format!(
+            "expected {size} rows of tiles, got {}",
+            rows.len()
+        )));
+    }
+
+    let mut tiles = tinyvec::ArrayVec::new();
+    for row in rows {
+        tiles.extend(parse_row(row, size).map_err(ProblemFileError::Invalid)?);
+    }
+    Ok(tiles)
+}
+
+fn format_shape(shape: Shape) -> [String; 3] {
+    shape.to_matrix().map(|row| {
+        row.iter()
+            .map(|&filled| if filled { 'X' } else { '.' })
+            .collect()
+    })
+}
+
+fn parse_shape(rows: &[String; 3]) -> Result<Shape, ProblemFileError> {
+    let mut matrix = [[false; 3]; 3];
+    for (row, matrix_row) in rows.iter().zip(&mut matrix) {
+        if row.chars().count() != 3 {
+            return Err(ProblemFileError::Invalid(format!(
+                "expected 3 cells in shape row {row:?}"
+            )));
+        }
+        for (c, cell) in row.chars().zip(matrix_row) {
+            *cell = match c {
+                'X' | 'x' => true,
+                '.' => false,
+                _ => {
+                    return Err(ProblemFileError::Invalid(format!(
+                        "invalid shape cell {c:?} in row {row:?}"
+                    )))
+                }
+            };
+        }
+    }
+    Ok(Shape::from_matrix(matrix))
+}
+
+fn parse_properties(names: &[String]) -> Result<CauldronProperties, ProblemFileError> {
+    names
+        .iter()
+        .map(|name| {
+            parse_property(name).ok_or_else(|| {
+                ProblemFileError::Invalid(format!("unknown cauldron property {name:?}"))
+            })
+        })
+        .collect()
+}
+
+/// Writes `usize::MAX` as `"unlimited"`, since it doesn't fit in a TOML integer.
+mod limit {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    const UNLIMITED: &str = "unlimited";
+
+    // serde passes the field by reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(value: &usize, serializer: S) -> Result<S::Ok, S::Error> {
+        match *value {
+            usize::MAX => serializer.serialize_str(UNLIMITED),
+            value => serializer.serialize_u64(value as u64),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Limit {
+            Count(usize),
+            Name(String),
+        }
+
+        match Limit::deserialize(deserializer)? {
+            Limit::Count(count) => Ok(count),
+            Limit::Name(name) if name == UNLIMITED => Ok(usize::MAX),
+            Limit::Name(name) => Err(D::Error::custom(format!(
+                "expected a number or {UNLIMITED:?}, got {name:?}"
+            ))),
+        }
+    }
+}
+
+/// An error that occurs when reading or writing a problem file.
+#[derive(Debug)]
+pub enum ProblemFileError {
+    /// The file could not be read or written
+    Io(std::io::Error),
+    /// The file is not valid TOML or JSON, or does not match the format
+    Parse(String),
+    /// The problem could not be serialized
+    Serialize(String),
+    /// The file has invalid tiles, shapes or properties
+    Invalid(String),
+}
+
+impl Display for ProblemFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to access problem file: {e}"),
+            Self::Parse(e) => write!(f, "Failed to parse problem file: {e}"),
+            Self::Serialize(e) => write!(f, "Failed to serialize problem: {e}"),
+            Self::Invalid(e) => write!(f, "Invalid problem: {e}"),
+        }
+    }
+}
+
+impl Error for ProblemFileError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_data::*;
+
+    #[test]
+    fn round_trip() {
+        let mut problem = problems::uni_bag();
+        problem.settings.coverage_tie_rule = CoverageTieRule::CauldronColor;
+        let file = ProblemFile::new(&problem);
+
+        let toml = file.to_toml().unwrap();
+        assert_eq!(ProblemFile::from_toml(&toml).unwrap(), file);
+        let json = file.to_json().unwrap();
+        assert_eq!(ProblemFile::from_json(&json).unwrap(), file);
+
+        let parsed = file.to_problem().unwrap();
+        assert_eq!(
+            parsed.settings.coverage_tie_rule,
+            CoverageTieRule::CauldronColor
+        );
+        assert_eq!(ProblemFile::new(&parsed), file);
+    }
+
+    #[test]
+    fn invalid_files() {
+        let file = ProblemFile::new(&problems::uni_bag());
+        let with = |f: fn(&mut ProblemFile)| {
+            let mut file = file.clone();
+            f(&mut file);
+            file.to_problem().err().unwrap().to_string()
+        };
+
+        assert_eq!(
+            with(|f| f.cauldron.tiles[0] = "B0 G0 Y0 Y0 P0".to_string()),
+            "Invalid problem: invalid tile \"P0\""
+        );
+        assert_eq!(
+            with(|f| f.cauldron.tiles[1] = "W0 Y0".to_string()),
+            "Invalid problem: expected 5 tiles in row \"W0 Y0\", got 2"
+        );
+        assert_eq!(
+            with(|f| f.material_groups[0].materials[0].shape[0] = "X?.".to_string()),
+            "Invalid problem: invalid shape cell '?' in row \"X?.\""
+        );
+        assert_eq!(
+            with(|f| f.cauldron.properties = vec!["time_limit".to_string()]),
+            "Invalid problem: unknown cauldron property \"time_limit\""
+        );
+        assert_eq!(
+            with(|f| _ = f.goals.pop()),
+            "Invalid problem: expected a material group for each of the 2 goals, but got 3"
+        );
+        assert_eq!(
+            with(|f| f.cauldron.tiles[0] = "B0 G0 Y0:9.9 Y0 W0".to_string()),
+            "Invalid problem: A material that is not in the problem was placed"
+        );
+        assert_eq!(
+            with(|f| {
+                f.goals.extend([vec![10], vec![10]]);
+                let group = f.material_groups[0].clone();
+                f.material_groups.extend([group.clone(), group]);
+            }),
+            "Invalid problem: A problem can have at most 4 goals"
+        );
+        assert_eq!(
+            with(|f| {
+                let material = f.material_groups[0].materials[0].clone();
+                f.material_groups[0].materials = vec![material; 6];
+            }),
+            "Invalid problem: A material group can have at most 5 materials"
+        );
+        assert_eq!(
+            with(|f| f.material_groups[1].materials[0].shape = ["..."; 3].map(str::to_string)),
+            "Invalid problem: A material has an empty shape"
+        );
+        assert_eq!(
+            with(|f| {
+                f.cauldron.size = 0;
+                f.cauldron.tiles.clear();
+            }),
+            "Invalid problem: A cauldron must be between 1x1 and 6x6 and have a tile for each position"
+        );
+
+        let toml = file.to_toml().unwrap().replace("\"white\"", "\"purple\"");
+        assert!(matches!(
+            ProblemFile::from_toml(&toml),
+            Err(ProblemFileError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! The rules that decide how placements are validated and scored.
+//!
+//! The rules of Atelier Sophie are implemented by [`SophieRules`]. Other rule sets can be used to
+//! experiment with variations of the synthesis system by implementing [`SynthRules`] and
+//! overriding the relevant methods.
+
+use itertools::Itertools;
+
+use crate::{errors::SynthError, *};
+
+/// A set of rules for the synthesis system.
+///
+/// Every method has a default implementation that matches the behaviour of Atelier Sophie.
+pub trait SynthRules {
+    /// Checks whether a shape can be placed with its top left corner at the given position.
+    ///
+    /// This is called after checking that the shape is inside the bounds of the cauldron. Tiles
+    /// that are not rejected here but are holes will be skipped when placing the shape.
+    fn validate_placement(
+        &self,
+        cauldron: &Cauldron,
+        shape: Shape,
+        position: (usize, usize),
+    ) -> Result<(), SynthError> {
+        let (placement_x, placement_y) = position;
+        for (shape_y, shape_x) in (0..Shape::HEIGHT).cartesian_product(0..Shape::WIDTH) {
+            if shape.get(shape_x, shape_y)
+                && cauldron
+                    .get_tile((placement_x + shape_x, placement_y + shape_y))
+                    .is_none()
+            {
+                return Err(SynthError::UnavailableTile);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The multiplier that is applied to the bonus score of a tile when a material is placed on it.
+    fn bonus_multiplier(&self, cauldron: &Cauldron, material: Material, tile: &Tile) -> f32 {
+        let mut bonus_multiplier = 1.0;
+
+        // matching colors give a 50% bonus to bonus score
+        if material.color == tile.color {
+            bonus_multiplier *= 1.5;
+        }
+
+        // the Synergy trait gives an additional 50% bonus when the tile color matches the cauldron color
+        if cauldron.properties.contains(CauldronProperties::SYNERGY) && cauldron.color == tile.color
+        {
+            bonus_multiplier *= 1.5;
+        }
+
+        bonus_multiplier
+    }
+
+    /// Levels up a tile that neighbours a material that was just placed.
+    fn level_up_neighbour(&self, tile: &mut Tile) {
+        // tiles that are already played cannot be updated
+        if tile.played_material_index.is_some() {
+            return;
+        }
+
+        // tiles can only go up to level 3
+        if tile.level < 3 {
+            tile.level += 1;
+        }
+    }
+
+    /// The rule that decides which colors receive the coverage bonus when multiple colors are tied
+    /// for the highest coverage.
+    fn coverage_tie_rule(&self) -> CoverageTieRule {
+        CoverageTieRule::AllTied
+    }
+
+    /// The multiplier that is applied to the total score of a color, based on the coverage of the
+    /// cauldron.
+    fn coverage_multiplier(
+        &self,
+        cauldron: &Cauldron,
+        coverage: &CoverageInfo,
+        color: Color,
+    ) -> f32 {
+        1. + coverage.get_color_ratio_with_tie_rule(color, cauldron, self.coverage_tie_rule())
+    }
+}
+
+/// The synthesis rules of Atelier Sophie.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SophieRules {
+    /// The rule to use for colors that are tied for the highest coverage.
+    pub coverage_tie_rule: CoverageTieRule,
+}
+
+impl SynthRules for SophieRules {
+    fn coverage_tie_rule(&self) -> CoverageTieRule {
+        self.coverage_tie_rule
+    }
+}
@@ -3,9 +3,12 @@ use tinyvec::ArrayVec;
 use crate::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GoalResult {
     /// The amount of thresholds that are met for each goal.
     pub achieved_goals: ArrayVec<[usize; MAX_GOALS]>,
+    /// The predicted quality of the item, if quality is one of the objectives of the solver.
+    pub quality: Option<u32>,
 }
 
 impl GoalResult {
@@ -17,15 +20,23 @@ impl GoalResult {
                 .zip(goals.iter())
                 .map(|(s, g)| g.effect_value_thresholds.iter().filter(|t| s >= t).count())
                 .collect(),
+            quality: None,
         }
     }
 
+    #[must_use]
+    pub fn with_quality(mut self, quality: u32) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
     pub fn is_strictly_better(&self, other: &Self) -> bool {
         debug_assert_eq!(self.achieved_goals.len(), other.achieved_goals.len());
         self.achieved_goals
             .iter()
             .zip(other.achieved_goals.iter())
             .all(|(a, b)| a >= b)
+            && self.quality >= other.quality
     }
 }
 
@@ -38,12 +49,12 @@ mod tests {
     macro_rules! is_strictly_better {
         (better: $(($a:expr, $b:expr),)*) => {
             $(
-                assert!(GoalResult { achieved_goals: $a }.is_strictly_better(&GoalResult { achieved_goals: $b }));
+                assert!(GoalResult { achieved_goals: $a, quality: None }.is_strictly_better(&GoalResult { achieved_goals: $b, quality: None }));
             )*
         };
         (not better: $(($a:expr, $b:expr),)*) => {
             $(
-                assert!(!GoalResult { achieved_goals: $a }.is_strictly_better(&GoalResult { achieved_goals: $b }));
+                assert!(!GoalResult { achieved_goals: $a, quality: None }.is_strictly_better(&GoalResult { achieved_goals: $b, quality: None }));
             )*
         };
     }
@@ -64,4 +75,17 @@ mod tests {
             (array_vec![0, 0, 1], array_vec![2, 0, 0]),
         ];
     }
+
+    #[test]
+    fn test_strictly_better_with_quality() {
+        let result = |achieved_goals, quality| GoalResult {
+            achieved_goals,
+            quality: Some(quality),
+        };
+
+        assert!(result(array_vec![1, 1], 50).is_strictly_better(&result(array_vec![1, 1], 40)));
+        assert!(result(array_vec![1, 1], 50).is_strictly_better(&result(array_vec![1, 0], 50)));
+        assert!(!result(array_vec![1, 1], 40).is_strictly_better(&result(array_vec![1, 0], 50)));
+        assert!(!result(array_vec![1, 0], 50).is_strictly_better(&result(array_vec![1, 1], 40)));
+    }
 }
@@ -1,37 +1,195 @@
 mod goal_result;
+mod notation;
 mod progress_tracker;
 
 use std::ops::ControlFlow;
 
 use tinyvec::ArrayVec;
 
-use crate::*;
+use crate::{
+    errors::SynthError,
+    rules::{SophieRules, SynthRules},
+    *,
+};
 pub use goal_result::*;
+pub use notation::*;
 pub use progress_tracker::ProgressReporter;
 use progress_tracker::ProgressTracker;
 
-pub type SolverResult = Vec<(GoalResult, ArrayVec<[Move; MAX_ITEMS]>)>;
+pub type SolverResult = Vec<SolverRoute>;
 
 #[derive(Default, Clone)]
 pub struct SolverSettings {
     /// The allowed transformations
     pub transformations: TransformationType,
-    /// Whether to allow overlapping placements
-    pub allow_overlaps: bool,
+    /// The limits for overlapping placements
+    pub overlap_limits: OverlapLimits,
+    /// The model used to predict item quality. If set, item quality is optimized alongside the
+    /// goals.
+    pub quality_model: Option<QualityModel>,
+    /// The rule that decides which colors receive the coverage bonus when they are tied.
+    pub coverage_tie_rule: CoverageTieRule,
+}
+
+impl SolverSettings {
+    /// The rules of Atelier Sophie with the coverage tie rule of these settings.
+    pub fn rules(&self) -> SophieRules {
+        SophieRules {
+            coverage_tie_rule: self.coverage_tie_rule,
+        }
+    }
+}
+
+/// Everything the solver needs to find routes for a synthesis.
+#[derive(Clone)]
+pub struct SolverProblem {
+    pub cauldron: Cauldron,
+    /// The materials, grouped by the goal they contribute to.
+    pub materials: Vec<Vec<Material>>,
+    pub goals: Vec<Goal>,
+    pub settings: SolverSettings,
+}
+
+impl SolverProblem {
+    /// Find the optimal routes for this problem with the [rules of its
+    /// settings](SolverSettings::rules). Fails if the problem is not [valid](Self::validate).
+    pub fn solve(
+        &self,
+        progress_reporter: Option<ProgressReporter<SolverResult>>,
+    ) -> Result<SolverResult, SynthError> {
+        self.validate()?;
+        Ok(find_optimal_routes(
+            &self.cauldron,
+            &self.materials,
+            &self.goals,
+            &self.settings,
+            progress_reporter,
+        ))
+    }
+
+    /// Check that the problem is within the limits of the solver, and that the materials that are
+    /// already placed in the cauldron are in the problem.
+    pub fn validate(&self) -> Result<(), SynthError> {
+        if self.goals.len() > MAX_GOALS {
+            return Err(SynthError::TooManyGoals);
+        }
+        if self.materials.len() != self.goals.len() {
+            return Err(SynthError::MismatchedMaterialGroups);
+        }
+        if (self.materials.iter()).any(|group| group.len() > MAX_ITEMS_IN_GROUP) {
+            return Err(SynthError::TooManyMaterials);
+        }
+        if (self.materials.iter().flatten()).any(|material| material.shape.is_empty()) {
+            return Err(SynthError::EmptyShape);
+        }
+        let size = self.cauldron.size;
+        if !(1..=6).contains(&size) || self.cauldron.tiles.len() != size * size {
+            return Err(SynthError::InvalidCauldronSize);
+        }
+
+        let all_known = self
+            .cauldron
+            .tiles
+            .iter()
+            .flatten()
+            .filter_map(|tile| tile.played_material_index)
+            .all(|material_index| self.has_material(material_index));
+        if !all_known {
+            return Err(SynthError::UnknownMaterial);
+        }
+        Ok(())
+    }
+
+    /// Replay the moves of a route, eg. one that was shared in route notation, and calculate the
+    /// goals it achieves.
+    pub fn score_route(&self, moves: &[Move]) -> Result<SolverRoute, SynthError> {
+        self.validate()?;
+        for (i, move_) in moves.iter().enumerate() {
+            if !self.has_material(move_.material_index) {
+                return Err(SynthError::UnknownMaterial);
+            }
+            if moves[..i]
+                .iter()
+                .any(|m| m.material_index == move_.material_index)
+            {
+                return Err(SynthError::DuplicateMaterial);
+            }
+        }
+
+        let rules = self.settings.rules();
+        let mut cauldron = self.cauldron.clone();
+        let score_sets = cauldron.place_all_with_rules(
+            &rules,
+            &self.materials,
+            moves,
+            self.settings.overlap_limits,
+        )?;
+        let scores =
+            cauldron.calculate_final_score_with_rules(&rules, &self.materials, &score_sets);
+        let mut goal_result = GoalResult::from_scores(&scores, &self.goals);
+        if let Some(quality_model) = self.settings.quality_model {
+            goal_result = goal_result
+                .with_quality(cauldron.calculate_final_quality(&self.materials, quality_model));
+        }
+
+        Ok(SolverRoute {
+            goal_result,
+            moves: moves.iter().cloned().collect(),
+            overlaps: cauldron.overlaps,
+        })
+    }
+
+    fn has_material(&self, (group, index): (usize, usize)) -> bool {
+        self.materials
+            .get(group)
+            .is_some_and(|group| index < group.len())
+    }
+}
+
+/// A route found by the solver.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverRoute {
+    /// The goals that are achieved by this route.
+    pub goal_result: GoalResult,
+    /// The moves that make up this route.
+    pub moves: ArrayVec<[Move; MAX_ITEMS]>,
+    /// The amount of placements in this route that overlapped other materials.
+    pub overlaps: usize,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move {
     pub material_index: (usize, usize),
     pub placement: Placement,
 }
 
+/// Find the optimal routes with the [rules of the settings](SolverSettings::rules).
 pub fn find_optimal_routes(
     playfield: &Cauldron,
     materials: &[Vec<Material>],
     goals: &[Goal],
     properties: &SolverSettings,
     progress_reporter: Option<ProgressReporter<SolverResult>>,
+) -> SolverResult {
+    find_optimal_routes_with_rules(
+        &properties.rules(),
+        playfield,
+        materials,
+        goals,
+        properties,
+        progress_reporter,
+    )
+}
+
+pub fn find_optimal_routes_with_rules(
+    rules: &impl SynthRules,
+    playfield: &Cauldron,
+    materials: &[Vec<Material>],
+    goals: &[Goal],
+    properties: &SolverSettings,
+    progress_reporter: Option<ProgressReporter<SolverResult>>,
 ) -> SolverResult {
     assert_eq!(materials.len(), goals.len());
 
@@ -47,7 +205,8 @@ pub fn find_optimal_routes(
 
     let mut max_scores = Default::default();
 
-    find_optimal_recursive(
+    _ = find_optimal_recursive(
+        rules,
         playfield,
         materials,
         goals,
@@ -63,6 +222,7 @@ pub fn find_optimal_routes(
 
 #[allow(clippy::too_many_arguments)] // FIXME
 fn find_optimal_recursive(
+    rules: &impl SynthRules,
     playfield: &Cauldron,
     materials: &[Vec<Material>],
     goals: &[Goal],
@@ -77,7 +237,9 @@ fn find_optimal_recursive(
     let desired_depth = material_count;
 
     if current_depth == desired_depth {
-        return check_end_of_path(playfield, materials, goals, path, score_sets, max_scores);
+        return check_end_of_path(
+            rules, playfield, materials, goals, properties, path, score_sets, max_scores,
+        );
     }
 
     let material_count_in_current_iteration = material_count - current_depth;
@@ -116,16 +278,18 @@ fn find_optimal_recursive(
                     let mut new_playfield = playfield.clone();
                     let mut new_score_sets = score_sets;
                     if new_playfield
-                        .place(
+                        .place_with_rules(
+                            rules,
                             materials,
                             (material_group_index, material_index),
                             placement,
-                            properties.allow_overlaps,
+                            properties.overlap_limits,
                             &mut new_score_sets,
                         )
                         .is_ok()
                     {
                         find_optimal_recursive(
+                            rules,
                             &new_playfield,
                             materials,
                             goals,
@@ -150,36 +314,60 @@ fn find_optimal_recursive(
     ControlFlow::Continue(())
 }
 
+#[allow(clippy::too_many_arguments)] // FIXME
 fn check_end_of_path(
+    rules: &impl SynthRules,
     playfield: &Cauldron,
     materials: &[Vec<Material>],
     goals: &[Goal],
+    properties: &SolverSettings,
     path: ArrayVec<[Move; MAX_ITEMS]>,
     score_sets: ArrayVec<[ColorScoreSet; MAX_GOALS]>,
     max_scores: &mut SolverResult,
 ) -> ControlFlow<()> {
-    let scores = playfield.calculate_final_score(materials, &score_sets);
-    let current_results = GoalResult::from_scores(&scores, goals);
+    let scores = playfield.calculate_final_score_with_rules(rules, materials, &score_sets);
+    let mut current_results = GoalResult::from_scores(&scores, goals);
+    if let Some(quality_model) = properties.quality_model {
+        current_results = current_results
+            .with_quality(playfield.calculate_final_quality(materials, quality_model));
+    }
 
-    max_scores.retain(|r| !current_results.is_strictly_better(&r.0) || r.0 == current_results);
+    max_scores.retain(|r| {
+        !current_results.is_strictly_better(&r.goal_result) || r.goal_result == current_results
+    });
 
-    if max_scores.is_empty()
-        || (!max_scores.iter().any(|ms| ms.0 == current_results)
-            && max_scores
-                .iter()
-                .all(|ms| !ms.0.is_strictly_better(&current_results)))
+    // prefer routes that need fewer overlaps to achieve the same result
+    if let Some(existing) = max_scores
+        .iter_mut()
+        .find(|r| r.goal_result == current_results)
     {
-        max_scores.push((current_results.clone(), path));
+        if playfield.overlaps < existing.overlaps {
+            existing.moves = path;
+            existing.overlaps = playfield.overlaps;
+        }
+    } else if max_scores
+        .iter()
+        .all(|r| !r.goal_result.is_strictly_better(&current_results))
+    {
+        max_scores.push(SolverRoute {
+            goal_result: current_results.clone(),
+            moves: path,
+            overlaps: playfield.overlaps,
+        });
+    }
 
-        // check if we reached a perfect score, which is where we meet all goals
-        if current_results
+    // check if we reached a perfect score, which is where we meet all goals without overlapping
+    // any materials. a perfect route that needs overlaps may still be improved on, and when
+    // optimizing for quality, a route with a higher quality may still be found.
+    if properties.quality_model.is_none()
+        && playfield.overlaps == 0
+        && current_results
             .achieved_goals
             .iter()
             .zip(goals.iter().map(|g| g.effect_value_thresholds.len()))
             .all(|(s, g)| *s == g)
-        {
-            return ControlFlow::Break(());
-        }
+    {
+        return ControlFlow::Break(());
     }
 
     ControlFlow::Continue(())
@@ -0,0 +1,262 @@
+//! A compact notation for routes, so they can be shared as text.
+//!
+//! A route is written as its moves separated by `;`, eg. `1.0@c3R90; 0.1@a1`. Each move is the
+//! material group and the index of the material in that group, then `@`, the column of the tile
+//! as a letter and its row as a number starting at 1, and an optional transformation: `FH`, `FV`,
+//! `R90`, `R180` or `R270`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{solver::Move, *};
+
+const TRANSFORMATIONS: [Transformation; 5] = [
+    Transformation::FlipHorizontal,
+    Transformation::FlipVertical,
+    Transformation::Rotate90,
+    Transformation::Rotate180,
+    Transformation::Rotate270,
+];
+
+impl Move {
+    /// Write this move in route notation, for a cauldron with the given size.
+    pub fn to_notation(&self, size: usize) -> String {
+        let (group, index) = self.material_index;
+        let (x, y) = (self.placement.index % size, self.placement.index / size);
+        let column = char::from(b'a' + x as u8);
+        let transformation = match self.placement.transformation {
+            Some(transformation) => transformation_name(transformation),
+            None => "",
+        };
+        format!("{group}.{index}@{column}{}{transformation}", y + 1)
+    }
+
+    /// Read a move in route notation, for a cauldron with the given size.
+    pub fn parse_notation(s: &str, size: usize) -> Result<Self, NotationErrorKind> {
+        let (material, position) = s
+            .split_once('@')
+            .ok_or(NotationErrorKind::MissingPosition)?;
+
+        let material_index = material
+            .split_once('.')
+            .and_then(|(group, index)| Some((group.parse().ok()?, index.parse().ok()?)))
+            .ok_or_else(|| NotationErrorKind::InvalidMaterial(material.to_string()))?;
+
+        let mut chars = position.chars();
+        let column = chars.next().ok_or(NotationErrorKind::MissingPosition)?;
+        let x = match column.to_ascii_lowercase() {
+            c @ 'a'..='z' => c as usize - 'a' as usize,
+            _ => return Err(NotationErrorKind::InvalidColumn(column)),
+        };
+
+        let rest = chars.as_str();
+        let row_len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (row, transformation) = rest.split_at(row_len);
+        let y = match row.parse::<usize>() {
+            Ok(row @ 1..) => row - 1,
+            _ => return Err(NotationErrorKind::InvalidRow(row.to_string())),
+        };
+        if x >= size || y >= size {
+            return Err(NotationErrorKind::OutsideCauldron {
+                position: format!("{column}{row}"),
+                size,
+            });
+        }
+
+        let transformation = match transformation {
+            "" => None,
+            name => Some(
+                TRANSFORMATIONS
+                    .into_iter()
+                    .find(|&t| transformation_name(t).eq_ignore_ascii_case(name))
+                    .ok_or_else(|| NotationErrorKind::InvalidTransformation(name.to_string()))?,
+            ),
+        };
+
+        Ok(Self {
+            material_index,
+            placement: Placement::new(y * size + x, transformation),
+        })
+    }
+}
+
+/// Write a route in route notation, for a cauldron with the given size.
+pub fn format_route(moves: &[Move], size: usize) -> String {
+    moves
+        .iter()
+        .map(|move_| move_.to_notation(size))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Read a route in route notation, for a cauldron with the given size. Whitespace around moves is
+/// ignored, and an empty string is an empty route.
+pub fn parse_route(s: &str, size: usize) -> Result<Vec<Move>, NotationError> {
+    if s.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    s.split(';')
+        .map(str::trim)
+        .enumerate()
+        .map(|(i, text)| {
+            Move::parse_notation(text, size).map_err(|kind| NotationError {
+                move_number: i + 1,
+                text: text.to_string(),
+                kind,
+            })
+        })
+        .collect()
+}
+
+fn transformation_name(transformation: Transformation) -> &'static str {
+    match transformation {
+        Transformation::FlipHorizontal => "FH",
+        Transformation::FlipVertical => "FV",
+        Transformation::Rotate90 => "R90",
+        Transformation::Rotate180 => "R180",
+        Transformation::Rotate270 => "R270",
+    }
+}
+
+/// A move in route notation that could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotationError {
+    /// The number of the move in the route, starting at 1.
+    pub move_number: usize,
+    /// The text of the move.
+    pub text: String,
+    pub kind: NotationErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationErrorKind {
+    /// The move has no `@` followed by a position
+    MissingPosition,
+    /// The material is not written as `GROUP.INDEX`
+    InvalidMaterial(String),
+    /// The column is not a letter
+    InvalidColumn(char),
+    /// The row is not a number starting at 1
+    InvalidRow(String),
+    /// The position is outside of the cauldron
+    OutsideCauldron { position: String, size: usize },
+    /// The transformation is not one of the known names
+    InvalidTransformation(String),
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid move {} {:?}: {}",
+            self.move_number, self.text, self.kind
+        )
+    }
+}
+
+impl Display for NotationErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPosition => write!(f, "expected a position after '@', eg. 0.1@c3"),
+            Self::InvalidMaterial(material) => write!(
+                f,
+                "expected a material written as GROUP.INDEX, eg. 0.1, but got {material:?}"
+            ),
+            Self::InvalidColumn(column) => {
+                write!(f, "expected a column letter, but got {column:?}")
+            }
+            Self::InvalidRow(row) => {
+                write!(f, "expected a row number starting at 1, but got {row:?}")
+            }
+            Self::OutsideCauldron { position, size } => {
+                write!(f, "{position} is outside of the {size}x{size} cauldron")
+            }
+            Self::InvalidTransformation(name) => write!(
+                f,
+                "unknown transformation {name:?}, expected one of FH, FV, R90, R180 or R270"
+            ),
+        }
+    }
+}
+
+impl Error for NotationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn move_(material_index: (usize, usize), index: usize, t: Option<Transformation>) -> Move {
+        Move {
+            material_index,
+            placement: Placement::new(index, t),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let moves = [
+            move_((1, 0), 2 + 5 * 2, Some(Transformation::Rotate90)),
+            move_((0, 1), 0, None),
+            move_((2, 3), 4 + 5 * 4, Some(Transformation::FlipHorizontal)),
+        ];
+
+        let notation = format_route(&moves, 5);
+        assert_eq!(notation, "1.0@c3R90; 0.1@a1; 2.3@e5FH");
+
+        let parsed = parse_route(&notation, 5).unwrap();
+        assert_eq!(format!("{parsed:?}"), format!("{moves:?}"));
+        assert_eq!(
+            format!(
+                "{:?}",
+                parse_route(" 1.0@C3r90 ;0.1@a1;2.3@e5fh ", 5).unwrap()
+            ),
+            format!("{moves:?}")
+        );
+        assert!(parse_route("", 5).unwrap().is_empty());
+
+        let problem = crate::utils::test_data::problems::uni_bag();
+        for route in problem.solve(None).unwrap() {
+            let notation = format_route(&route.moves, problem.cauldron.size);
+            let moves = parse_route(&notation, problem.cauldron.size).unwrap();
+            assert_eq!(
+                problem.score_route(&moves).unwrap().goal_result,
+                route.goal_result
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |s: &str| parse_route(s, 5).unwrap_err().to_string();
+
+        assert_eq!(
+            error("0.0@a1; 0.1"),
+            "Invalid move 2 \"0.1\": expected a position after '@', eg. 0.1@c3"
+        );
+        assert_eq!(
+            error("0-1@a1"),
+            "Invalid move 1 \"0-1@a1\": expected a material written as GROUP.INDEX, eg. 0.1, but got \"0-1\""
+        );
+        assert_eq!(
+            error("0.1@31"),
+            "Invalid move 1 \"0.1@31\": expected a column letter, but got '3'"
+        );
+        assert_eq!(
+            error("0.1@a0"),
+            "Invalid move 1 \"0.1@a0\": expected a row number starting at 1, but got \"0\""
+        );
+        assert_eq!(
+            error("0.1@f1"),
+            "Invalid move 1 \"0.1@f1\": f1 is outside of the 5x5 cauldron"
+        );
+        assert_eq!(
+            error("0.1@a1R45"),
+            "Invalid move 1 \"0.1@a1R45\": unknown transformation \"R45\", expected one of FH, FV, R90, R180 or R270"
+        );
+    }
+}
@@ -83,19 +83,19 @@ mod tests {
 
         tracker.start_loop(5);
         for _ in 0..5 {
-            tracker.report_progress(&());
+            _ = tracker.report_progress(&());
             tracker.start_loop(4);
             for _ in 0..4 {
-                tracker.report_progress(&());
+                _ = tracker.report_progress(&());
                 tracker.start_loop(3);
                 for _ in 0..3 {
-                    tracker.report_progress(&());
+                    _ = tracker.report_progress(&());
                     tracker.start_loop(2);
                     for _ in 0..2 {
-                        tracker.report_progress(&());
+                        _ = tracker.report_progress(&());
                         tracker.start_loop(1);
                         for _ in 0..1 {
-                            tracker.report_progress(&());
+                            _ = tracker.report_progress(&());
                             tracker.start_loop(0);
                             tracker.end_loop();
 
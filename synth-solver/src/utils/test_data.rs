@@ -10,9 +10,10 @@ pub mod cauldron {
                 R 0, Y 0, R 0, R 0, Y 1,
                 W 0, Y 2, Y 0, Y 0, Y 0,
             ],
-            bonus_scores: (3, 5, 7),
+            bonus_scores: BonusScores::Flat(3, 5, 7),
             color: Color::White,
             properties: CauldronProperties::empty(),
+            overlaps: 0,
         }
     }
 }
@@ -42,6 +43,7 @@ pub mod material {
             color: Color::Yellow,
             effect_value: 15,
             shape: Shape::from_binary([0b100, 0b100, 0b100]),
+            quality: 40,
         }
     }
 
@@ -50,6 +52,7 @@ pub mod material {
             color: Color::Yellow,
             effect_value: 10,
             shape: Shape::from_binary([0b100, 0b110, 0b000]),
+            quality: 25,
         }
     }
 
@@ -58,6 +61,26 @@ pub mod material {
             color: Color::White,
             effect_value: 15,
             shape: Shape::from_binary([0b100, 0b100, 0b100]),
+            quality: 60,
+        }
+    }
+}
+
+pub mod problems {
+    use super::*;
+    use crate::solver::{SolverProblem, SolverSettings};
+
+    /// The uni bag problem, which is also stored in `tests/data/uni_bag.toml`.
+    pub fn uni_bag() -> SolverProblem {
+        SolverProblem {
+            cauldron: cauldron::uni_bag_5x5_bonus1(),
+            materials: vec![
+                vec![material::uni(), material::uni()],
+                vec![material::beehive()],
+                vec![material::broken_stone()],
+            ],
+            goals: goals::uni_bag().to_vec(),
+            settings: SolverSettings::default(),
         }
     }
 }
@@ -1,4 +1,4 @@
-use synth_solver::*;
+use synth_solver::{errors::SynthError, utils::test_data::*, *};
 
 #[test]
 fn test_calculation_basic() {
@@ -25,31 +25,56 @@ fn test_calculation_basic() {
             R 0, Y 0, R 0, R 0, Y 1,
             W 0, Y 2, Y 0, Y 0, Y 0,
         ],
-        bonus_scores: (3, 5, 7),
+        bonus_scores: BonusScores::Flat(3, 5, 7),
         color: Color::White,
         properties: CauldronProperties::empty(),
+        overlaps: 0,
     };
 
     let mut scores = vec![ColorScoreSet::default(); materials.len()];
 
     let placement1 = Placement::new(2 + 5, None);
     cauldron
-        .place(&materials, (0, 0), placement1, true, &mut scores)
+        .place(
+            &materials,
+            (0, 0),
+            placement1,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
 
     let placement2 = Placement::new(1 + 5 * 3, None);
     cauldron
-        .place(&materials, (1, 0), placement2, true, &mut scores)
+        .place(
+            &materials,
+            (1, 0),
+            placement2,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
 
     let placement3 = Placement::new(3 + 5 * 2, None);
     cauldron
-        .place(&materials, (2, 0), placement3, true, &mut scores)
+        .place(
+            &materials,
+            (2, 0),
+            placement3,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
 
     let placement4 = Placement::new(0, None);
     cauldron
-        .place(&materials, (0, 1), placement4, true, &mut scores)
+        .place(
+            &materials,
+            (0, 1),
+            placement4,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
 
     let coverage = cauldron.calculate_coverage(&materials);
@@ -93,34 +118,59 @@ fn test_calculation_overlap_synergy() {
             G 0, G 0, G 0, G 2, G 0,
             B 0, B 1, G 0, G 0, B 0,
         ],
-        bonus_scores: (3, 5, 7),
+        bonus_scores: BonusScores::Flat(3, 5, 7),
         color: Color::Green,
         properties: CauldronProperties::SYNERGY,
+        overlaps: 0,
     };
 
     let mut scores = vec![ColorScoreSet::default(); materials.len()];
 
     let placement1 = Placement::new(0, None);
     cauldron
-        .place(&materials, (0, 0), placement1, true, &mut scores)
+        .place(
+            &materials,
+            (0, 0),
+            placement1,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
     println!("scores after placement 1: {scores:?}");
 
     let placement2 = Placement::new(2, None);
     cauldron
-        .place(&materials, (2, 0), placement2, true, &mut scores)
+        .place(
+            &materials,
+            (2, 0),
+            placement2,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
     println!("scores after placement 2: {scores:?}");
 
     let placement3 = Placement::new(2 + 5, Some(Transformation::Rotate270));
     cauldron
-        .place(&materials, (1, 0), placement3, true, &mut scores)
+        .place(
+            &materials,
+            (1, 0),
+            placement3,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
     println!("scores after placement 3: {scores:?}");
 
     let placement4 = Placement::new(1 + 5 * 2, None);
     cauldron
-        .place(&materials, (0, 1), placement4, true, &mut scores)
+        .place(
+            &materials,
+            (0, 1),
+            placement4,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap();
     println!("scores after placement 4: {scores:?}");
 
@@ -140,6 +190,25 @@ fn test_calculation_overlap_synergy() {
 
 #[test]
 fn test_calculation_overlap_synergy_2() {
+    let (materials, cauldron, scores) = overlap_synergy_2_route();
+
+    let coverage = cauldron.calculate_coverage(&materials);
+    println!("Coverage: {coverage:?}");
+    assert_eq!(coverage.get_color_ratio(Color::Red, &cauldron), 0.);
+    assert_eq!(coverage.get_color_ratio(Color::Blue, &cauldron), 0.);
+    assert_eq!(coverage.get_color_ratio(Color::Green, &cauldron), 0.16);
+    assert_eq!(coverage.get_color_ratio(Color::Yellow, &cauldron), 0.16);
+    assert_eq!(coverage.get_color_ratio(Color::White, &cauldron), 0.);
+
+    let final_scores = cauldron.calculate_final_score(&materials, &scores);
+
+    // tested on Berg Medicine whose max scores are [100, 60, 60], so third value is not verified
+    // TODO: rounding works very strangely here, the game reports [53, 55, 69] but the middle value is `54.52` before rounding
+    assert_eq!(final_scores.as_slice(), vec![53, 54, 69]);
+}
+
+/// A route on a synergy cauldron that ends with green and yellow tied for the highest coverage.
+fn overlap_synergy_2_moves() -> (Vec<Vec<Material>>, Cauldron, [solver::Move; 4]) {
     let materials = vec![
         vec![
             Material::new(Color::Green, 12, Shape::from_binary([0b110, 0b111, 0b000])),
@@ -157,7 +226,7 @@ fn test_calculation_overlap_synergy_2() {
         )],
     ];
 
-    let mut cauldron = Cauldron {
+    let cauldron = Cauldron {
         size: 5,
         tiles: tiles![
             B 0, B 0, G 0, G 0, B 0,
@@ -166,50 +235,37 @@ fn test_calculation_overlap_synergy_2() {
             G 0, G 0, G 0, G 2, G 0,
             B 0, B 1, G 0, G 0, B 0,
         ],
-        bonus_scores: (3, 5, 7),
+        bonus_scores: BonusScores::Flat(3, 5, 7),
         color: Color::Green,
         properties: CauldronProperties::SYNERGY,
+        overlaps: 0,
     };
 
-    let mut scores = vec![ColorScoreSet::default(); materials.len()];
-
-    let placement1 = Placement::new(0, None);
-    cauldron
-        .place(&materials, (0, 0), placement1, true, &mut scores)
-        .unwrap();
-    println!("scores after placement 1: {scores:?}");
-
-    let placement2 = Placement::new(2 + 5, Some(Transformation::Rotate270));
-    cauldron
-        .place(&materials, (0, 1), placement2, true, &mut scores)
-        .unwrap();
-    println!("scores after placement 2: {scores:?}");
+    let moves = [
+        ((0, 0), Placement::new(0, None)),
+        (
+            (0, 1),
+            Placement::new(2 + 5, Some(Transformation::Rotate270)),
+        ),
+        ((2, 0), Placement::new(1, Some(Transformation::Rotate270))),
+        ((1, 0), Placement::new(1 + 5 * 2, None)),
+    ]
+    .map(|(material_index, placement)| solver::Move {
+        material_index,
+        placement,
+    });
+
+    (materials, cauldron, moves)
+}
 
-    let placement3 = Placement::new(1, Some(Transformation::Rotate270));
-    cauldron
-        .place(&materials, (2, 0), placement3, true, &mut scores)
+/// The route of [`overlap_synergy_2_moves`] placed in its cauldron, with the scores it places.
+fn overlap_synergy_2_route() -> (Vec<Vec<Material>>, Cauldron, Vec<ColorScoreSet>) {
+    let (materials, mut cauldron, moves) = overlap_synergy_2_moves();
+    let scores = cauldron
+        .place_all(&materials, &moves, OverlapLimits::UNLIMITED)
         .unwrap();
-    println!("scores after placement 3: {scores:?}");
 
-    let placement4 = Placement::new(1 + 5 * 2, None);
-    cauldron
-        .place(&materials, (1, 0), placement4, true, &mut scores)
-        .unwrap();
-    println!("scores after placement 4: {scores:?}");
-
-    let coverage = cauldron.calculate_coverage(&materials);
-    println!("Coverage: {coverage:?}");
-    assert_eq!(coverage.get_color_ratio(Color::Red, &cauldron), 0.);
-    assert_eq!(coverage.get_color_ratio(Color::Blue, &cauldron), 0.);
-    assert_eq!(coverage.get_color_ratio(Color::Green, &cauldron), 0.16);
-    assert_eq!(coverage.get_color_ratio(Color::Yellow, &cauldron), 0.16);
-    assert_eq!(coverage.get_color_ratio(Color::White, &cauldron), 0.);
-
-    let final_scores = cauldron.calculate_final_score(&materials, &scores);
-
-    // tested on Berg Medicine whose max scores are [100, 60, 60], so third value is not verified
-    // TODO: rounding works very strangely here, the game reports [53, 55, 69] but the middle value is `54.52` before rounding
-    assert_eq!(final_scores.as_slice(), vec![53, 54, 69]);
+    (materials, cauldron, scores)
 }
 
 #[test]
@@ -226,9 +282,10 @@ fn test_overlap() {
             W 0, W 0, W 0, W 0,
             W 0, W 0, W 0, W 0,
         ],
-        bonus_scores: (3, 5, 7),
+        bonus_scores: BonusScores::Flat(3, 5, 7),
         color: Color::White,
         properties: CauldronProperties::empty(),
+        overlaps: 0,
     };
 
     let mut scores = vec![ColorScoreSet::default(); materials.len()];
@@ -239,7 +296,13 @@ fn test_overlap() {
     let placement_2 = Placement::new(1 + 2 * 4, Some(Transformation::Rotate90));
 
     cauldron
-        .place(&materials, (0, 0), placement_1, true, &mut scores)
+        .place(
+            &materials,
+            (0, 0),
+            placement_1,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap(); // cover (1,1) and (1,2)
 
     assert_eq!(cauldron.get_tile((0, 2)).unwrap().level, 1);
@@ -247,7 +310,13 @@ fn test_overlap() {
 
     // overwrite placement
     cauldron
-        .place(&materials, (0, 1), placement_2, true, &mut scores)
+        .place(
+            &materials,
+            (0, 1),
+            placement_2,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap(); // cover (1,2) and (2,2)
 
     assert_eq!(cauldron.get_tile((0, 2)).unwrap().level, 2);
@@ -256,9 +325,352 @@ fn test_overlap() {
     // the tile that was originally occupied by that placement should have level 0 again
 
     cauldron
-        .place(&materials, (0, 2), placement_1, true, &mut scores)
+        .place(
+            &materials,
+            (0, 2),
+            placement_1,
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
         .unwrap(); // cover (1,1) and (1,2)
 
     assert_eq!(cauldron.get_tile((0, 2)).unwrap().level, 3);
     assert_eq!(cauldron.get_tile((2, 2)).unwrap().level, 0);
 }
+
+#[test]
+fn test_overlap_limits() {
+    let straight_material =
+        Material::new(Color::White, 15, Shape::from_binary([0b100, 0b100, 0b000]));
+    let materials = vec![vec![straight_material; 4]];
+
+    let mut cauldron = Cauldron {
+        size: 4,
+        tiles: tiles![
+            W 0, W 0, W 0, W 0,
+            W 0, W 0, W 0, W 0,
+            W 0, W 0, W 0, W 0,
+            W 0, W 0, W 0, W 0,
+        ],
+        bonus_scores: BonusScores::Flat(3, 5, 7),
+        color: Color::White,
+        properties: CauldronProperties::empty(),
+        overlaps: 0,
+    };
+
+    let limits = OverlapLimits {
+        max_overlaps: 1,
+        max_materials_per_overlap: 1,
+    };
+    let mut scores = vec![ColorScoreSet::default(); materials.len()];
+
+    // two materials next to each other, covering (0,0), (0,1), (1,0) and (1,1)
+    cauldron
+        .place(
+            &materials,
+            (0, 0),
+            Placement::new(0, None),
+            limits,
+            &mut scores,
+        )
+        .unwrap();
+    cauldron
+        .place(
+            &materials,
+            (0, 1),
+            Placement::new(1, None),
+            limits,
+            &mut scores,
+        )
+        .unwrap();
+
+    // overlapping both materials at once is not allowed
+    let mut overlap_both = cauldron.clone();
+    assert!(matches!(
+        overlap_both.place(
+            &materials,
+            (0, 2),
+            Placement::new(0, Some(Transformation::Rotate90)),
+            limits,
+            &mut scores.clone(),
+        ),
+        Err(SynthError::OverlapLimitReached)
+    ));
+    // a placement that is not allowed leaves the cauldron untouched
+    assert_eq!(overlap_both.to_string(), cauldron.to_string());
+
+    // overlapping a single material is allowed once
+    cauldron
+        .place(
+            &materials,
+            (0, 2),
+            Placement::new(4, None),
+            limits,
+            &mut scores,
+        )
+        .unwrap();
+    assert_eq!(cauldron.overlaps, 1);
+    assert!(matches!(
+        cauldron.place(
+            &materials,
+            (0, 3),
+            Placement::new(5, None),
+            limits,
+            &mut scores
+        ),
+        Err(SynthError::OverlapLimitReached)
+    ));
+
+    // without overlaps, placing on top of another material is disallowed entirely
+    assert!(matches!(
+        cauldron.place(
+            &materials,
+            (0, 3),
+            Placement::new(5, None),
+            OverlapLimits::NONE,
+            &mut scores,
+        ),
+        Err(SynthError::DisallowedOverlap)
+    ));
+}
+
+#[test]
+fn test_calculation_custom_rules() {
+    /// Rules without the coverage bonus and without bonus multipliers.
+    struct FlatRules;
+
+    impl rules::SynthRules for FlatRules {
+        fn bonus_multiplier(&self, _: &Cauldron, _: Material, _: &Tile) -> f32 {
+            1.0
+        }
+
+        fn coverage_multiplier(&self, _: &Cauldron, _: &CoverageInfo, _: Color) -> f32 {
+            1.0
+        }
+    }
+
+    let materials = vec![
+        vec![material::uni(), material::uni()],
+        vec![material::beehive()],
+        vec![material::broken_stone()],
+    ];
+    let moves = [
+        ((0, 0), Placement::new(2 + 5, None)),
+        ((1, 0), Placement::new(1 + 5 * 3, None)),
+        ((2, 0), Placement::new(3 + 5 * 2, None)),
+        ((0, 1), Placement::new(0, None)),
+    ]
+    .map(|(material_index, placement)| solver::Move {
+        material_index,
+        placement,
+    });
+
+    // the default rules are the rules of Atelier Sophie
+    let mut cauldron = cauldron::uni_bag_5x5_bonus1();
+    let scores = cauldron
+        .place_all_with_rules(
+            &rules::SophieRules::default(),
+            &materials,
+            &moves,
+            OverlapLimits::NONE,
+        )
+        .unwrap();
+    let final_scores = cauldron.calculate_final_score_with_rules(
+        &rules::SophieRules::default(),
+        &materials,
+        &scores,
+    );
+    assert_eq!(final_scores.as_slice(), vec![48, 39, 28]);
+
+    let mut cauldron = cauldron::uni_bag_5x5_bonus1();
+    let scores = cauldron
+        .place_all_with_rules(&FlatRules, &materials, &moves, OverlapLimits::NONE)
+        .unwrap();
+    let final_scores = cauldron.calculate_final_score_with_rules(&FlatRules, &materials, &scores);
+    assert_eq!(final_scores.as_slice(), vec![36, 23, 28]);
+}
+
+#[test]
+fn test_coverage_tie_rules() {
+    // the game reports [53, 55, 69] for this route
+    let (materials, cauldron, scores) = overlap_synergy_2_route();
+
+    let coverage = cauldron.calculate_coverage(&materials);
+    assert_eq!(coverage.get_color(Color::Green), 4);
+    assert_eq!(coverage.get_color(Color::Yellow), 4);
+
+    // the cauldron is green, so only the tie rules that give the bonus to all tied colors give it
+    // to yellow
+    assert_eq!(
+        coverage.get_color_ratio_with_tie_rule(Color::Yellow, &cauldron, CoverageTieRule::AllTied),
+        0.16
+    );
+    assert_eq!(
+        coverage.get_color_ratio_with_tie_rule(
+            Color::Yellow,
+            &cauldron,
+            CoverageTieRule::CauldronColor
+        ),
+        0.
+    );
+    assert_eq!(
+        coverage.get_color_ratio_with_tie_rule(
+            Color::Green,
+            &cauldron,
+            CoverageTieRule::CauldronColor
+        ),
+        0.16
+    );
+
+    let final_scores_with_tie_rule = |tie_rule| {
+        let rules = rules::SophieRules {
+            coverage_tie_rule: tie_rule,
+        };
+        cauldron
+            .calculate_final_score_with_rules(&rules, &materials, &scores)
+            .to_vec()
+    };
+
+    // only giving the bonus to all tied colors comes close to the in-game result of the yellow
+    // group, but it doesn't match it. see `test_calculation_overlap_synergy_2`.
+    assert_eq!(
+        final_scores_with_tie_rule(CoverageTieRule::AllTied),
+        vec![53, 54, 69]
+    );
+    assert_eq!(
+        final_scores_with_tie_rule(CoverageTieRule::None),
+        vec![46, 47, 60]
+    );
+    assert_eq!(
+        final_scores_with_tie_rule(CoverageTieRule::FirstByColorIndex),
+        vec![53, 47, 69]
+    );
+    assert_eq!(
+        final_scores_with_tie_rule(CoverageTieRule::CauldronColor),
+        vec![53, 47, 69]
+    );
+
+    // with a yellow liquid, the tied color with the lowest index is still green
+    let yellow_cauldron = Cauldron {
+        color: Color::Yellow,
+        ..cauldron.clone()
+    };
+    for (tie_rule, green_ratio, yellow_ratio, final_scores) in [
+        (CoverageTieRule::FirstByColorIndex, 0.16, 0., [53, 47, 69]),
+        (CoverageTieRule::CauldronColor, 0., 0.16, [46, 54, 60]),
+    ] {
+        assert_eq!(
+            coverage.get_color_ratio_with_tie_rule(Color::Green, &yellow_cauldron, tie_rule),
+            green_ratio
+        );
+        assert_eq!(
+            coverage.get_color_ratio_with_tie_rule(Color::Yellow, &yellow_cauldron, tie_rule),
+            yellow_ratio
+        );
+        let rules = rules::SophieRules {
+            coverage_tie_rule: tie_rule,
+        };
+        assert_eq!(
+            yellow_cauldron
+                .calculate_final_score_with_rules(&rules, &materials, &scores)
+                .as_slice(),
+            final_scores
+        );
+    }
+}
+
+#[test]
+fn test_score_route_with_tie_rule() {
+    let (materials, cauldron, moves) = overlap_synergy_2_moves();
+    let problem = |coverage_tie_rule| solver::SolverProblem {
+        cauldron: cauldron.clone(),
+        materials: materials.clone(),
+        goals: vec![
+            Goal {
+                effect_value_thresholds: vec![50],
+            };
+            3
+        ],
+        settings: solver::SolverSettings {
+            overlap_limits: OverlapLimits::UNLIMITED,
+            coverage_tie_rule,
+            ..Default::default()
+        },
+    };
+
+    // the yellow group only reaches 50 with the coverage bonus
+    let route = problem(CoverageTieRule::AllTied)
+        .score_route(&moves)
+        .unwrap();
+    assert_eq!(route.goal_result.achieved_goals.as_ref(), [1, 1, 1]);
+    let route = (problem(CoverageTieRule::CauldronColor).score_route(&moves)).unwrap();
+    assert_eq!(route.goal_result.achieved_goals.as_ref(), [1, 0, 1]);
+}
+
+#[test]
+fn test_quality_prediction() {
+    let straight_material =
+        Material::new(Color::White, 15, Shape::from_binary([0b100, 0b100, 0b000]));
+    let materials = vec![vec![
+        straight_material.with_quality(30),
+        straight_material.with_quality(60),
+        straight_material.with_quality(100),
+    ]];
+
+    let mut cauldron = Cauldron {
+        size: 4,
+        tiles: tiles![
+            W 0, W 0, W 0, W 0,
+            W 0, W 0, W 0, W 0,
+            W 0, W 0, W 0, W 0,
+            W 0, W 0, W 0, W 0,
+        ],
+        bonus_scores: BonusScores::Flat(3, 5, 7),
+        color: Color::White,
+        properties: CauldronProperties::empty(),
+        overlaps: 0,
+    };
+    let model = QualityModel::default();
+    let mut scores = vec![ColorScoreSet::default(); materials.len()];
+
+    assert_eq!(cauldron.calculate_final_quality(&materials, model), 0);
+
+    cauldron
+        .place(
+            &materials,
+            (0, 0),
+            Placement::new(0, None),
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
+        .unwrap();
+    cauldron
+        .place(
+            &materials,
+            (0, 1),
+            Placement::new(1, None),
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
+        .unwrap();
+    assert_eq!(cauldron.calculate_final_quality(&materials, model), 45);
+
+    // overlapping the first material removes its quality from the average
+    cauldron
+        .place(
+            &materials,
+            (0, 2),
+            Placement::new(4, None),
+            OverlapLimits::UNLIMITED,
+            &mut scores,
+        )
+        .unwrap();
+    assert_eq!(cauldron.calculate_final_quality(&materials, model), 80);
+
+    // bonus is applied after averaging and the result is capped
+    let model = QualityModel {
+        quality_bonus: 50,
+        max_quality: 100,
+    };
+    assert_eq!(cauldron.calculate_final_quality(&materials, model), 100);
+}
@@ -0,0 +1,88 @@
+use synth_solver::{errors::ParseCauldronError, utils::test_data::*, *};
+
+#[test]
+pub fn test_parse_matches_tiles_macro() {
+    let text = "
+        color: white
+        bonus_scores: flat 3 5 7
+        B0 G0 Y0 Y0 W0
+        W0 Y0 Y0 Y0 Y1
+        R0 Y0 R1 R0 Y0
+        R0 Y0 R0 R0 Y1
+        W0 Y2 Y0 Y0 Y0
+    ";
+    let cauldron = text.parse::<Cauldron>().unwrap();
+    let expected = cauldron::uni_bag_5x5_bonus1();
+
+    assert_eq!(cauldron.size, expected.size);
+    assert_eq!(cauldron.tiles, expected.tiles);
+    assert_eq!(cauldron.bonus_scores, expected.bonus_scores);
+    assert_eq!(cauldron.color, expected.color);
+    assert_eq!(cauldron.properties, expected.properties);
+}
+
+#[test]
+pub fn test_display_round_trip() {
+    let materials = vec![
+        vec![material::uni(), material::uni()],
+        vec![material::beehive()],
+        vec![material::broken_stone()],
+    ];
+    let mut cauldron = cauldron::uni_bag_5x5_bonus1();
+    cauldron
+        .place_all(
+            &materials,
+            &[solver::Move {
+                material_index: (1, 0),
+                placement: Placement::new(1, None),
+            }],
+            OverlapLimits::NONE,
+        )
+        .unwrap();
+    cauldron.properties = CauldronProperties::SYNERGY | CauldronProperties::TUNING;
+
+    let text = cauldron.to_string();
+    assert_eq!(
+        text,
+        "color: white
+bonus_scores: flat 3 5 7
+properties: synergy tuning
+B1     G0:1.0 Y1     Y1     W0
+W1     Y0:1.0 Y0:1.0 Y1     Y1
+R1     Y1     R2     R1     Y0
+R0     Y0     R0     R0     Y1
+W0     Y2     Y0     Y0     Y0
+"
+    );
+
+    let parsed = text.parse::<Cauldron>().unwrap();
+    assert_eq!(parsed.tiles, cauldron.tiles);
+    assert_eq!(parsed.properties, cauldron.properties);
+    assert_eq!(parsed.to_string(), text);
+}
+
+#[test]
+pub fn test_parse_errors() {
+    let error = |text: &str| text.parse::<Cauldron>().err().unwrap();
+
+    assert_eq!(
+        error("R0 --\nR0 P1"),
+        ParseCauldronError {
+            line: 2,
+            message: "invalid tile \"P1\"".to_string()
+        }
+    );
+    assert_eq!(
+        error("color: purple\nR0").to_string(),
+        "Invalid cauldron on line 1: unknown color \"purple\""
+    );
+    assert_eq!(
+        error("R0 R0\nR0").to_string(),
+        "Invalid cauldron on line 2: expected 2 tiles in row \"R0\", got 1"
+    );
+    assert_eq!(
+        error("bonus_scores: flat 1 2\nR0").message,
+        "expected bonus scores like \"flat 3 5 7\", got \"flat 1 2\""
+    );
+    assert_eq!(error("").message, "expected 1 to 6 rows of tiles, got 0");
+}
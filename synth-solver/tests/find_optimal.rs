@@ -1,4 +1,4 @@
-use synth_solver::{utils::test_data::*, *};
+use synth_solver::{errors::SynthError, utils::test_data::*, *};
 
 #[test]
 pub fn test_basic_uni_bag() {
@@ -17,16 +17,14 @@ pub fn test_basic_uni_bag() {
     assert_eq!(
         optimal_routes
             .iter()
-            .cloned()
-            .filter(|r| r.0.achieved_goals.as_ref() == [1, 1, 1])
+            .filter(|r| r.goal_result.achieved_goals.as_ref() == [1, 1, 1])
             .count(),
         1
     );
     assert_eq!(
         optimal_routes
             .iter()
-            .cloned()
-            .filter(|r| r.0.achieved_goals.as_ref() == [2, 0, 0])
+            .filter(|r| r.goal_result.achieved_goals.as_ref() == [2, 0, 0])
             .count(),
         1
     );
@@ -43,7 +41,9 @@ pub fn test_basic_uni_bag_with_rotations() {
     ];
     let properties = solver::SolverSettings {
         transformations: TransformationType::Rotate,
-        allow_overlaps: false,
+        overlap_limits: OverlapLimits::NONE,
+        quality_model: None,
+        ..Default::default()
     };
     let optimal_routes =
         solver::find_optimal_routes(&cauldron, &materials, &goals, &properties, None);
@@ -52,25 +52,189 @@ pub fn test_basic_uni_bag_with_rotations() {
     assert_eq!(
         optimal_routes
             .iter()
-            .cloned()
-            .filter(|r| r.0.achieved_goals.as_ref() == [1, 1, 1])
+            .filter(|r| r.goal_result.achieved_goals.as_ref() == [1, 1, 1])
             .count(),
         1
     );
     assert_eq!(
         optimal_routes
             .iter()
-            .cloned()
-            .filter(|r| r.0.achieved_goals.as_ref() == [1, 2, 0])
+            .filter(|r| r.goal_result.achieved_goals.as_ref() == [1, 2, 0])
             .count(),
         1
     );
     assert_eq!(
         optimal_routes
             .iter()
-            .cloned()
-            .filter(|r| r.0.achieved_goals.as_ref() == [2, 0, 0])
+            .filter(|r| r.goal_result.achieved_goals.as_ref() == [2, 0, 0])
             .count(),
         1
     );
 }
+
+#[test]
+pub fn test_basic_uni_bag_with_overlap_limit() {
+    let cauldron = cauldron::uni_bag_5x5_bonus1();
+    let goals = goals::uni_bag();
+    let materials = vec![
+        vec![material::uni(), material::uni()],
+        vec![material::beehive()],
+        vec![material::broken_stone()],
+    ];
+    let properties = solver::SolverSettings {
+        transformations: TransformationType::None,
+        overlap_limits: OverlapLimits {
+            max_overlaps: 1,
+            max_materials_per_overlap: 1,
+        },
+        quality_model: None,
+        ..Default::default()
+    };
+    let optimal_routes =
+        solver::find_optimal_routes(&cauldron, &materials, &goals, &properties, None);
+
+    assert!(!optimal_routes.is_empty());
+    for route in &optimal_routes {
+        assert!(route.overlaps <= 1);
+
+        // replaying the route should give the same amount of overlaps
+        let mut replayed = cauldron.clone();
+        replayed
+            .place_all(&materials, &route.moves, properties.overlap_limits)
+            .unwrap();
+        assert_eq!(replayed.overlaps, route.overlaps);
+    }
+}
+
+#[test]
+pub fn test_perfect_route_prefers_fewer_overlaps() {
+    let cauldron = Cauldron {
+        size: 3,
+        tiles: tiles![
+            W 0, W 0, W 0,
+            W 0, W 0, W 0,
+            W 0, W 0, W 0,
+        ],
+        bonus_scores: BonusScores::Flat(3, 5, 7),
+        color: Color::White,
+        properties: CauldronProperties::empty(),
+        overlaps: 0,
+    };
+    let dot = Material::new(Color::White, 10, Shape::from_binary([0b100, 0b000, 0b000]));
+    let materials = vec![vec![dot, dot]];
+    let goals = [Goal {
+        effect_value_thresholds: vec![1],
+    }];
+    let properties = solver::SolverSettings {
+        overlap_limits: OverlapLimits {
+            max_overlaps: 1,
+            max_materials_per_overlap: 1,
+        },
+        ..Default::default()
+    };
+    let optimal_routes =
+        solver::find_optimal_routes(&cauldron, &materials, &goals, &properties, None);
+
+    // the first perfect route that is found places both materials on the same tile, but the
+    // solver keeps searching until it finds one without overlaps
+    assert_eq!(optimal_routes.len(), 1);
+    assert_eq!(optimal_routes[0].goal_result.achieved_goals.as_ref(), [1]);
+    assert_eq!(optimal_routes[0].overlaps, 0);
+}
+
+#[test]
+pub fn test_basic_uni_bag_with_quality() {
+    let cauldron = cauldron::uni_bag_5x5_bonus1();
+    let goals = goals::uni_bag();
+    let materials = vec![
+        vec![material::uni(), material::uni()],
+        vec![material::beehive()],
+        vec![material::broken_stone()],
+    ];
+    let properties = solver::SolverSettings {
+        quality_model: Some(QualityModel::default()),
+        ..Default::default()
+    };
+    let optimal_routes =
+        solver::find_optimal_routes(&cauldron, &materials, &goals, &properties, None);
+
+    // without overlaps, all materials stay in the cauldron so every route has the same quality
+    assert_eq!(optimal_routes.len(), 2);
+    for route in &optimal_routes {
+        assert_eq!(route.goal_result.quality, Some((40 + 40 + 25 + 60) / 4));
+    }
+}
+
+#[test]
+pub fn test_unknown_placed_material() {
+    let mut problem = problems::uni_bag();
+    problem
+        .cauldron
+        .get_tile_mut((2, 0))
+        .as_mut()
+        .unwrap()
+        .played_material_index = Some((9, 9));
+
+    assert!(matches!(
+        problem.solve(None),
+        Err(SynthError::UnknownMaterial)
+    ));
+    assert!(matches!(
+        problem.score_route(&[]),
+        Err(SynthError::UnknownMaterial)
+    ));
+
+    problem
+        .cauldron
+        .get_tile_mut((2, 0))
+        .as_mut()
+        .unwrap()
+        .played_material_index = Some((0, 2));
+    assert!(problem.validate().is_err());
+    problem
+        .cauldron
+        .get_tile_mut((2, 0))
+        .as_mut()
+        .unwrap()
+        .played_material_index = Some((0, 1));
+    assert!(problem.validate().is_ok());
+}
+
+#[test]
+pub fn test_problem_limits() {
+    let problem = problems::uni_bag();
+    let with = |f: fn(&mut solver::SolverProblem)| {
+        let mut problem = problem.clone();
+        f(&mut problem);
+        problem.solve(None).err()
+    };
+
+    assert!(matches!(
+        with(|p| {
+            p.goals.extend([p.goals[0].clone(), p.goals[0].clone()]);
+            p.materials
+                .extend([p.materials[0].clone(), p.materials[0].clone()]);
+        }),
+        Some(SynthError::TooManyGoals)
+    ));
+    assert!(matches!(
+        with(|p| _ = p.materials.pop()),
+        Some(SynthError::MismatchedMaterialGroups)
+    ));
+    assert!(matches!(
+        with(|p| p.materials[1] = vec![material::beehive(); MAX_ITEMS_IN_GROUP + 1]),
+        Some(SynthError::TooManyMaterials)
+    ));
+    assert!(matches!(
+        with(|p| p.materials[2][0].shape = Shape::from_binary([0; 3])),
+        Some(SynthError::EmptyShape)
+    ));
+    assert!(matches!(
+        with(|p| p.cauldron.size = 0),
+        Some(SynthError::InvalidCauldronSize)
+    ));
+    assert!(matches!(
+        with(|p| _ = p.cauldron.tiles.pop()),
+        Some(SynthError::InvalidCauldronSize)
+    ));
+}
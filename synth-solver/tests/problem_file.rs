@@ -0,0 +1,28 @@
+#![cfg(feature = "serde")]
+
+use synth_solver::{problem_file::ProblemFile, utils::test_data::*};
+
+#[test]
+pub fn test_uni_bag_file_matches_test_data() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/uni_bag.toml");
+    let file = ProblemFile::read(path).unwrap();
+
+    assert_eq!(file, ProblemFile::new(&problems::uni_bag()));
+
+    let routes = file.to_problem().unwrap().solve(None).unwrap();
+    assert_eq!(routes.len(), 2);
+}
+
+#[test]
+pub fn test_write_and_read_json() {
+    let path = std::env::temp_dir().join(format!("uni_bag_{}.json", std::process::id()));
+    let file = ProblemFile::new(&problems::uni_bag()).with_tags("ITEM_MIX_UNI_BAG", "KETTLE");
+
+    file.write(&path).unwrap();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let read = ProblemFile::read(&path);
+    _ = std::fs::remove_file(&path);
+
+    assert!(contents.trim_start().starts_with('{'));
+    assert_eq!(read.unwrap(), file);
+}